@@ -336,6 +336,7 @@ mod tests {
             partition,
             record_count: 1,
             file_size_in_bytes: 1,
+            block_size_in_bytes: None,
             column_sizes: HashMap::new(),
             value_counts: HashMap::new(),
             null_value_counts: HashMap::new(),
@@ -346,6 +347,7 @@ mod tests {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -360,6 +362,7 @@ mod tests {
             partition,
             record_count: 1,
             file_size_in_bytes: 1,
+            block_size_in_bytes: None,
             column_sizes: HashMap::new(),
             value_counts: HashMap::new(),
             null_value_counts: HashMap::new(),
@@ -370,6 +373,7 @@ mod tests {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
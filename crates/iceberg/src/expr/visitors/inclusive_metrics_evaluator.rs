@@ -1986,6 +1986,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 10,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
             column_sizes: Default::default(),
             value_counts: Default::default(),
             null_value_counts: Default::default(),
@@ -1996,6 +1997,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -2008,6 +2010,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 0,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
             column_sizes: Default::default(),
             value_counts: Default::default(),
             null_value_counts: Default::default(),
@@ -2018,6 +2021,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -2030,6 +2034,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 50,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
 
             value_counts: HashMap::from([
                 (4, 50),
@@ -2076,6 +2081,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -2087,6 +2093,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 50,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
 
             value_counts: HashMap::from([(3, 20)]),
 
@@ -2103,6 +2110,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -2115,6 +2123,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 50,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
 
             value_counts: HashMap::from([(3, 20)]),
 
@@ -2131,6 +2140,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -2143,6 +2153,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 50,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
 
             value_counts: HashMap::from([(3, 20)]),
 
@@ -2159,6 +2170,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
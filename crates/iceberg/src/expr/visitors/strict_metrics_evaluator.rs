@@ -541,6 +541,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 50,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
             value_counts: HashMap::from([
                 (1, 50),
                 (4, 50),
@@ -581,6 +582,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -593,6 +595,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 0,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
             column_sizes: HashMap::new(),
             value_counts: Default::default(),
             null_value_counts: Default::default(),
@@ -603,6 +606,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -615,6 +619,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 10,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
             value_counts: HashMap::from([(1, 10)]),
             null_value_counts: HashMap::from([(1, 0)]),
             nan_value_counts: HashMap::from([(1, 0)]),
@@ -625,6 +630,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -638,6 +644,7 @@ mod test {
             partition: Struct::empty(),
             record_count: 50,
             file_size_in_bytes: 10,
+            block_size_in_bytes: None,
             value_counts: HashMap::from([(3, 20)]),
             null_value_counts: HashMap::from([(3, 2)]),
             nan_value_counts: HashMap::default(),
@@ -648,6 +655,7 @@ mod test {
             split_offsets: vec![],
             equality_ids: vec![],
             sort_order_id: None,
+            referenced_data_file: None,
             partition_spec_id: 0,
         }
     }
@@ -16,29 +16,36 @@
 // under the License.
 
 //! Manifest for Iceberg.
+use std::borrow::Cow;
 use std::cmp::min;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io::{Read, Write};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
-use apache_avro::{from_value, to_value, Reader as AvroReader, Writer as AvroWriter};
+use apache_avro::rabin::Rabin;
+use apache_avro::schema::{Name, Namespace, ResolvedSchema};
+use apache_avro::{
+    from_value, to_value, Reader as AvroReader, Schema as AvroSchema, Writer as AvroWriter,
+};
 use bytes::Bytes;
+use futures::{stream, Stream, StreamExt, TryStreamExt};
 use itertools::Itertools;
 use serde_derive::{Deserialize, Serialize};
 use serde_json::to_vec;
 use serde_with::{DeserializeFromStr, SerializeDisplay};
+use sha2::{Digest, Sha256};
 use typed_builder::TypedBuilder;
 
 use self::_const_schema::{manifest_schema_v1, manifest_schema_v2};
 use super::{
-    Datum, FieldSummary, FormatVersion, ManifestContentType, ManifestFile, PartitionSpec,
-    PrimitiveLiteral, PrimitiveType, Schema, SchemaId, SchemaRef, Struct, StructType,
-    INITIAL_SEQUENCE_NUMBER, UNASSIGNED_SEQUENCE_NUMBER, UNASSIGNED_SNAPSHOT_ID,
+    Datum, FieldSummary, FormatVersion, Literal, ManifestContentType, ManifestFile, PartitionSpec,
+    PrimitiveLiteral, PrimitiveType, Schema, SchemaId, SchemaRef, Struct, StructType, Transform,
+    Type, INITIAL_SEQUENCE_NUMBER, UNASSIGNED_SEQUENCE_NUMBER, UNASSIGNED_SNAPSHOT_ID,
 };
 use crate::error::Result;
-use crate::io::OutputFile;
-use crate::spec::PartitionField;
+use crate::io::{InputFile, OutputFile};
+use crate::spec::{PartitionField, UnboundPartitionField};
 use crate::{Error, ErrorKind};
 
 /// A manifest contains metadata and a list of entries.
@@ -51,47 +58,7 @@ pub struct Manifest {
 impl Manifest {
     /// Parse manifest metadata and entries from bytes of avro file.
     pub(crate) fn try_from_avro_bytes(bs: &[u8]) -> Result<(ManifestMetadata, Vec<ManifestEntry>)> {
-        let reader = AvroReader::new(bs)?;
-
-        // Parse manifest metadata
-        let meta = reader.user_metadata();
-        let metadata = ManifestMetadata::parse(meta)?;
-
-        // Parse manifest entries
-        let partition_type = metadata.partition_spec.partition_type(&metadata.schema)?;
-
-        let entries = match metadata.format_version {
-            FormatVersion::V1 => {
-                let schema = manifest_schema_v1(&partition_type)?;
-                let reader = AvroReader::with_schema(&schema, bs)?;
-                reader
-                    .into_iter()
-                    .map(|value| {
-                        from_value::<_serde::ManifestEntryV1>(&value?)?.try_into(
-                            metadata.partition_spec.spec_id(),
-                            &partition_type,
-                            &metadata.schema,
-                        )
-                    })
-                    .collect::<Result<Vec<_>>>()?
-            }
-            FormatVersion::V2 => {
-                let schema = manifest_schema_v2(&partition_type)?;
-                let reader = AvroReader::with_schema(&schema, bs)?;
-                reader
-                    .into_iter()
-                    .map(|value| {
-                        from_value::<_serde::ManifestEntryV2>(&value?)?.try_into(
-                            metadata.partition_spec.spec_id(),
-                            &partition_type,
-                            &metadata.schema,
-                        )
-                    })
-                    .collect::<Result<Vec<_>>>()?
-            }
-        };
-
-        Ok((metadata, entries))
+        ManifestReader::default().read_parts(bs)
     }
 
     /// Parse manifest from bytes of avro file.
@@ -100,2471 +67,9729 @@ impl Manifest {
         Ok(Self::new(metadata, entries))
     }
 
-    /// Entries slice.
-    pub fn entries(&self) -> &[ManifestEntryRef] {
-        &self.entries
+    /// Read the user metadata of the avro file as written, without interpreting it into
+    /// [`ManifestMetadata`], e.g. to inspect custom keys of a producer. Keys reserved by
+    /// avro, like `avro.schema` and `avro.codec`, aren't included.
+    ///
+    /// Only the header of the file is read, the entries aren't decoded.
+    pub fn read_raw_metadata(bs: &[u8]) -> Result<HashMap<String, Vec<u8>>> {
+        Ok(AvroReader::new(bs)?.user_metadata().clone())
     }
 
-    /// Consume this Manifest, returning its constituent parts
-    pub fn into_parts(self) -> (Vec<ManifestEntryRef>, ManifestMetadata) {
-        let Self { entries, metadata } = self;
-        (entries, metadata)
+    /// Parse the manifests of several avro object container files concatenated in `bs`,
+    /// e.g. by an archival tool, in order.
+    ///
+    /// Each file is delimited by walking its data blocks up to the magic header of the
+    /// next one, an error is returned when `bs` can't be split this way.
+    pub fn parse_avro_multi(bs: &[u8]) -> Result<Vec<Self>> {
+        let mut manifests = vec![];
+        let mut offset = 0;
+        loop {
+            let len = avro_container_len(&bs[offset..]).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::DataInvalid,
+                    format!(
+                        "Bytes at offset {offset} are not a complete avro object container file"
+                    ),
+                )
+            })?;
+            manifests.push(Self::parse_avro(&bs[offset..offset + len])?);
+            offset += len;
+            if offset == bs.len() {
+                return Ok(manifests);
+            }
+        }
     }
 
-    /// Constructor from [`ManifestMetadata`] and [`ManifestEntry`]s.
-    pub fn new(metadata: ManifestMetadata, entries: Vec<ManifestEntry>) -> Self {
-        Self {
-            metadata,
-            entries: entries.into_iter().map(Arc::new).collect(),
+    /// Parse manifest from a local avro file, memory-mapping it instead of reading it
+    /// into memory first. Meant for tools inspecting large local manifests.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, while
+    /// it's being parsed. Reading a page of the mapping past the end of a truncated file
+    /// raises `SIGBUS`, and other modifications change bytes the parser already checked.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn parse_avro_mmap(path: &std::path::Path) -> Result<Self> {
+        let file = std::fs::File::open(path).map_err(|err| {
+            Error::new(
+                ErrorKind::Unexpected,
+                format!("Failed to open manifest file {}", path.display()),
+            )
+            .with_source(err)
+        })?;
+        let len = file
+            .metadata()
+            .map_err(|err| {
+                Error::new(
+                    ErrorKind::Unexpected,
+                    format!(
+                        "Failed to read metadata of manifest file {}",
+                        path.display()
+                    ),
+                )
+                .with_source(err)
+            })?
+            .len();
+        // Mapping an empty file fails on some platforms, report it as what it is.
+        if len == 0 {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!("Manifest file {} is empty", path.display()),
+            ));
         }
+        // SAFETY: the mapping is only read while parsing, and callers guarantee the file
+        // isn't modified or truncated in the meantime, see the safety section above.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(|err| {
+            Error::new(
+                ErrorKind::Unexpected,
+                format!("Failed to memory-map manifest file {}", path.display()),
+            )
+            .with_source(err)
+        })?;
+        Self::parse_avro(&mmap)
     }
-}
 
-/// The builder used to create a [`ManifestWriter`].
-pub struct ManifestWriterBuilder {
-    output: OutputFile,
-    snapshot_id: Option<i64>,
-    key_metadata: Vec<u8>,
-    schema: SchemaRef,
-    partition_spec: PartitionSpec,
-}
+    /// Parse manifest from bytes of avro file, and check that it was written with the
+    /// `expected` schema, e.g. the table schema at the manifest's snapshot.
+    ///
+    /// The schemas match when they have the same schema id and the same fields, identifier
+    /// field ids are not compared. Fails with [`ErrorKind::DataInvalid`] on mismatch.
+    pub fn parse_avro_expecting(bs: &[u8], expected: &Schema) -> Result<Self> {
+        let manifest = Self::parse_avro(bs)?;
+        let schema = manifest.metadata.schema();
+        if schema.schema_id() != expected.schema_id() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Manifest schema id {} doesn't match the expected schema id {}",
+                    schema.schema_id(),
+                    expected.schema_id()
+                ),
+            ));
+        }
+        if schema.as_struct() != expected.as_struct() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Manifest schema doesn't match the expected schema with id {}",
+                    expected.schema_id()
+                ),
+            ));
+        }
+        Ok(manifest)
+    }
 
-impl ManifestWriterBuilder {
-    /// Create a new builder.
-    pub fn new(
-        output: OutputFile,
-        snapshot_id: Option<i64>,
-        key_metadata: Vec<u8>,
-        schema: SchemaRef,
-        partition_spec: PartitionSpec,
-    ) -> Self {
-        Self {
-            output,
-            snapshot_id,
-            key_metadata,
-            schema,
-            partition_spec,
+    /// Parse manifest from bytes of avro file, keeping only the column stats of the
+    /// fields of `projection`, e.g. the columns read by a scan of a wide table.
+    ///
+    /// Column sizes, value, null and NaN counts, and lower and upper bounds of fields
+    /// that aren't in `projection` are dropped, like the bounds of fields dropped by
+    /// schema evolution. The schema in the manifest metadata is still the embedded one.
+    pub fn parse_avro_projected(bs: &[u8], projection: &Schema) -> Result<Self> {
+        let (metadata, mut entries) = ManifestReader::default().read_parts(bs)?;
+        let projected = |field_id: &i32| projection.field_by_id(*field_id).is_some();
+        for entry in &mut entries {
+            let data_file = &mut entry.data_file;
+            data_file.column_sizes.retain(|k, _| projected(k));
+            data_file.value_counts.retain(|k, _| projected(k));
+            data_file.null_value_counts.retain(|k, _| projected(k));
+            data_file.nan_value_counts.retain(|k, _| projected(k));
+            data_file.lower_bounds.retain(|k, _| projected(k));
+            data_file.upper_bounds.retain(|k, _| projected(k));
         }
+        Ok(Self::new(metadata, entries))
     }
 
-    /// Build a [`ManifestWriter`] for format version 1.
-    pub fn build_v1(self) -> ManifestWriter {
-        let metadata = ManifestMetadata::builder()
-            .schema_id(self.schema.schema_id())
-            .schema(self.schema)
-            .partition_spec(self.partition_spec)
-            .format_version(FormatVersion::V1)
-            .content(ManifestContentType::Data)
-            .build();
-        ManifestWriter::new(self.output, self.snapshot_id, self.key_metadata, metadata)
+    /// Parse manifest from bytes of avro file, using `schema` instead of the schema
+    /// embedded in the manifest metadata.
+    ///
+    /// This is a recovery path for manifests whose embedded `schema` is missing or
+    /// corrupt: the provided schema, e.g. from the table metadata, is used to bind the
+    /// partition spec and to resolve the lower and upper bounds. The partition spec itself
+    /// and the other metadata are still read from the file.
+    ///
+    /// See [`Manifest::parse_avro_with_schema_diagnostics`] for how decimal bounds are
+    /// handled when a column's scale differs from the embedded schema.
+    pub fn parse_avro_with_schema(bs: &[u8], schema: SchemaRef) -> Result<Self> {
+        let (manifest, _) = Self::parse_avro_with_schema_diagnostics(bs, schema)?;
+        Ok(manifest)
     }
 
-    /// Build a [`ManifestWriter`] for format version 2, data content.
-    pub fn build_v2_data(self) -> ManifestWriter {
-        let metadata = ManifestMetadata::builder()
-            .schema_id(self.schema.schema_id())
-            .schema(self.schema)
-            .partition_spec(self.partition_spec)
-            .format_version(FormatVersion::V2)
-            .content(ManifestContentType::Data)
-            .build();
-        ManifestWriter::new(self.output, self.snapshot_id, self.key_metadata, metadata)
+    /// Like [`Manifest::parse_avro_with_schema`], also returning diagnostics about the
+    /// decimal bounds that couldn't be reinterpreted.
+    ///
+    /// Decimal bounds are stored as unscaled integers, so when the embedded schema is
+    /// readable and a decimal column has a different scale in `schema`, its bounds are
+    /// rescaled to the scale in `schema`. A bound is dropped, with a diagnostic, when it
+    /// can't be rescaled exactly or doesn't fit the precision in `schema`. When the
+    /// embedded schema is missing or corrupt, bounds are read with the types in `schema`.
+    pub fn parse_avro_with_schema_diagnostics(
+        bs: &[u8],
+        schema: SchemaRef,
+    ) -> Result<(Self, Vec<String>)> {
+        let (metadata, mut entries) =
+            ManifestReader::default().read_parts_with_schema(bs, Some(schema))?;
+        let embedded_schema = AvroReader::new(bs)?
+            .user_metadata()
+            .get("schema")
+            .and_then(|bs| serde_json::from_slice::<Schema>(bs).ok());
+        let diagnostics = match embedded_schema {
+            Some(embedded_schema) => rescale_decimal_bounds(&mut entries, &embedded_schema),
+            None => vec![],
+        };
+        Ok((Self::new(metadata, entries), diagnostics))
     }
 
-    /// Build a [`ManifestWriter`] for format version 2, deletes content.
-    pub fn build_v2_deletes(self) -> ManifestWriter {
-        let metadata = ManifestMetadata::builder()
-            .schema_id(self.schema.schema_id())
-            .schema(self.schema)
-            .partition_spec(self.partition_spec)
-            .format_version(FormatVersion::V2)
-            .content(ManifestContentType::Deletes)
-            .build();
-        ManifestWriter::new(self.output, self.snapshot_id, self.key_metadata, metadata)
+    /// Entries slice.
+    pub fn entries(&self) -> &[ManifestEntryRef] {
+        &self.entries
     }
-}
 
-/// A manifest writer.
-pub struct ManifestWriter {
-    output: OutputFile,
+    /// Iterate over the data files of live entries, i.e. entries whose status is
+    /// not [`ManifestStatus::Deleted`].
+    pub fn data_files(&self) -> impl Iterator<Item = &DataFile> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_alive())
+            .map(|entry| entry.data_file())
+    }
 
-    snapshot_id: Option<i64>,
+    /// Consume this Manifest, returning the data files of live entries.
+    pub fn into_data_files(self) -> Vec<DataFile> {
+        self.entries
+            .into_iter()
+            .filter(|entry| entry.is_alive())
+            .map(|entry| Arc::unwrap_or_clone(entry).data_file)
+            .collect()
+    }
 
-    added_files: u32,
-    added_rows: u64,
-    existing_files: u32,
-    existing_rows: u64,
-    deleted_files: u32,
-    deleted_rows: u64,
+    /// Returns true if both manifests have the same metadata and the same entries,
+    /// regardless of the order of the entries.
+    ///
+    /// Entries are compared after sorting them by file path and status, which is useful
+    /// to check that a rewrite that reordered entries preserved the manifest contents.
+    pub fn equivalent_to(&self, other: &Manifest) -> bool {
+        fn sorted_entries(manifest: &Manifest) -> Vec<&ManifestEntryRef> {
+            manifest
+                .entries
+                .iter()
+                .sorted_by_key(|entry| (entry.file_path(), entry.status as i32))
+                .collect()
+        }
 
-    min_seq_num: Option<i64>,
+        self.metadata == other.metadata
+            && self.entries.len() == other.entries.len()
+            && sorted_entries(self) == sorted_entries(other)
+    }
 
-    key_metadata: Vec<u8>,
+    /// Compare the entries of two manifests, e.g. of a manifest and its rewrite.
+    ///
+    /// Entries are matched on `file_path`: entries only in `new` are added, entries only in
+    /// `old` are removed, and matched entries that differ in any way, including their
+    /// status, sequence numbers or data file stats, are changed. When a manifest has several
+    /// entries for the same file path, only its last entry is compared. Each list is sorted
+    /// by file path.
+    pub fn diff<'a>(old: &'a Manifest, new: &'a Manifest) -> ManifestDiff<'a> {
+        fn by_path(manifest: &Manifest) -> HashMap<&str, &ManifestEntryRef> {
+            manifest
+                .entries
+                .iter()
+                .map(|entry| (entry.file_path(), entry))
+                .collect()
+        }
 
-    manifest_entries: Vec<ManifestEntry>,
+        let old_entries = by_path(old);
+        let new_entries = by_path(new);
+        let mut diff = ManifestDiff::default();
+        for (path, new_entry) in new_entries.iter().sorted_by_key(|(path, _)| **path) {
+            match old_entries.get(path) {
+                None => diff.added.push(*new_entry),
+                Some(old_entry) if old_entry != new_entry => {
+                    diff.changed.push((*old_entry, *new_entry))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = old_entries
+            .iter()
+            .filter(|(path, _)| !new_entries.contains_key(*path))
+            .sorted_by_key(|(path, _)| **path)
+            .map(|(_, entry)| *entry)
+            .collect();
+        diff
+    }
 
-    metadata: ManifestMetadata,
-}
+    /// Data files whose partition spec id differs from the spec id declared in the
+    /// manifest metadata.
+    ///
+    /// This is normally empty, a non-empty result indicates a producer bug. Note that
+    /// parsing assigns the declared spec id to every data file, so mismatches can only
+    /// appear in manifests built from entries with [`Manifest::new`].
+    pub fn spec_id_mismatches(&self) -> Vec<&DataFile> {
+        let spec_id = self.metadata.partition_spec.spec_id();
+        self.entries
+            .iter()
+            .map(|entry| entry.data_file())
+            .filter(|data_file| data_file.partition_spec_id != spec_id)
+            .collect()
+    }
 
-struct PartitionFieldStats {
-    partition_type: PrimitiveType,
-    summary: FieldSummary,
-}
+    /// Returns true if every entry's partition spec id equals the spec id declared in
+    /// the manifest metadata, as manifests are required to use a single partition spec.
+    pub fn is_single_spec(&self) -> bool {
+        let spec_id = self.metadata.partition_spec.spec_id();
+        self.entries
+            .iter()
+            .all(|entry| entry.data_file.partition_spec_id == spec_id)
+    }
 
-impl PartitionFieldStats {
-    pub(crate) fn new(partition_type: PrimitiveType) -> Self {
-        Self {
-            partition_type,
-            summary: FieldSummary::default(),
-        }
+    /// The distinct partition spec ids of the entries in this manifest.
+    pub fn spec_ids(&self) -> HashSet<i32> {
+        self.entries
+            .iter()
+            .map(|entry| entry.data_file.partition_spec_id)
+            .collect()
     }
 
-    pub(crate) fn update(&mut self, value: Option<PrimitiveLiteral>) -> Result<()> {
-        let Some(value) = value else {
-            self.summary.contains_null = true;
-            return Ok(());
-        };
-        if !self.partition_type.compatible(&value) {
+    /// Check that the content type of every entry matches the manifest content: data
+    /// manifests may only contain data files, and delete manifests only delete files.
+    ///
+    /// The writer enforces this, so a failure indicates a manifest written by a buggy
+    /// producer.
+    pub fn validate_content(&self) -> Result<()> {
+        let expect_data = self.metadata.content == ManifestContentType::Data;
+        if let Some(entry) = self
+            .entries
+            .iter()
+            .find(|entry| (entry.content_type() == DataContentType::Data) != expect_data)
+        {
             return Err(Error::new(
                 ErrorKind::DataInvalid,
-                "value is not compatitable with type",
+                format!(
+                    "Entry {} with content type {:?} is not allowed in a {} manifest",
+                    entry.file_path(),
+                    entry.content_type(),
+                    self.metadata.content
+                ),
             ));
         }
-        let value = Datum::new(self.partition_type.clone(), value);
+        Ok(())
+    }
 
-        if value.is_nan() {
-            self.summary.contains_nan = Some(true);
-            return Ok(());
+    /// Run all checks on the entries of this manifest, returning every problem found
+    /// instead of failing on the first one.
+    ///
+    /// Each entry is checked for:
+    /// - a content type allowed in this manifest, see [`Manifest::validate_content`],
+    /// - metrics consistent with the manifest schema, see [`DataFile::validate`], and
+    ///   bounds only for fields of the schema,
+    /// - bounds consistent with the partition, see [`DataFile::validate_bounds_against_partition`],
+    /// - equality ids for equality delete files only, all fields of the schema,
+    /// - non-negative split offsets, sorted ascending, and a non-negative block size,
+    /// - the partition spec id of the manifest,
+    /// - a lower bound not above the upper bound of each column.
+    ///
+    /// Counts are unsigned in memory, negative counts are rejected when reading.
+    pub fn validate(&self) -> Result<Vec<ManifestValidationIssue>> {
+        let schema = &self.metadata.schema;
+        let partition_spec = &self.metadata.partition_spec;
+        let expect_data = self.metadata.content == ManifestContentType::Data;
+        let mut issues = vec![];
+        for entry in &self.entries {
+            let data_file = &entry.data_file;
+            let mut problems = vec![];
+
+            if (data_file.content == DataContentType::Data) != expect_data {
+                problems.push(format!(
+                    "Content type {:?} is not allowed in a {} manifest",
+                    data_file.content, self.metadata.content
+                ));
+            }
+            match data_file.validate(schema) {
+                Ok(diagnostics) => problems.extend(diagnostics),
+                Err(err) => problems.push(err.message().to_string()),
+            }
+            for field_id in data_file
+                .lower_bounds
+                .keys()
+                .chain(data_file.upper_bounds.keys())
+                .copied()
+                .sorted()
+                .dedup()
+            {
+                if schema.field_by_id(field_id).is_none() {
+                    problems.push(format!("Bound for field id {field_id} not found in schema"));
+                }
+            }
+            match data_file.validate_bounds_against_partition(partition_spec, &data_file.partition)
+            {
+                Ok(diagnostics) => problems.extend(diagnostics),
+                Err(err) => problems.push(err.message().to_string()),
+            }
+            match data_file.content {
+                DataContentType::EqualityDeletes if data_file.equality_ids.is_empty() => {
+                    problems.push("Equality delete file has no equality ids".to_string())
+                }
+                DataContentType::Data | DataContentType::PositionDeletes
+                    if !data_file.equality_ids.is_empty() =>
+                {
+                    problems.push(format!(
+                        "{:?} file has equality ids, they are only valid for equality delete files",
+                        data_file.content
+                    ))
+                }
+                _ => {}
+            }
+            for field_id in &data_file.equality_ids {
+                if schema.field_by_id(*field_id).is_none() {
+                    problems.push(format!("Equality id {field_id} not found in schema"));
+                }
+            }
+            if let Some(offset) = data_file.split_offsets.iter().find(|offset| **offset < 0) {
+                problems.push(format!("Split offset {offset} is negative"));
+            }
+            if !data_file
+                .split_offsets
+                .iter()
+                .tuple_windows()
+                .all(|(a, b)| a <= b)
+            {
+                problems.push(format!(
+                    "Split offsets {:?} are not sorted ascending",
+                    data_file.split_offsets
+                ));
+            }
+            if let Some(block_size) = data_file.block_size_in_bytes.filter(|size| *size < 0) {
+                problems.push(format!("Block size {block_size} is negative"));
+            }
+            if data_file.partition_spec_id != partition_spec.spec_id() {
+                problems.push(format!(
+                    "Partition spec id {} doesn't match the manifest partition spec id {}",
+                    data_file.partition_spec_id,
+                    partition_spec.spec_id()
+                ));
+            }
+            for (field_id, lower) in sorted_bounds(&data_file.lower_bounds) {
+                if let Some(upper) = data_file.upper_bounds.get(&field_id) {
+                    if lower.partial_cmp(upper) == Some(std::cmp::Ordering::Greater) {
+                        problems.push(format!(
+                            "Field id {field_id} has lower bound {lower} above upper bound {upper}"
+                        ));
+                    }
+                }
+            }
+
+            issues.extend(problems.into_iter().map(|message| ManifestValidationIssue {
+                file_path: data_file.file_path.clone(),
+                message,
+            }));
         }
+        Ok(issues)
+    }
 
-        self.summary.lower_bound = Some(self.summary.lower_bound.take().map_or(
-            value.clone(),
-            |original| {
-                if value < original {
-                    value.clone()
-                } else {
-                    original
-                }
-            },
-        ));
-        self.summary.upper_bound = Some(self.summary.upper_bound.take().map_or(
-            value.clone(),
-            |original| {
-                if value > original {
-                    value
-                } else {
-                    original
+    /// Count the entries of this manifest by the content type of their data file.
+    ///
+    /// Entries with status [`ManifestStatus::Deleted`] are only counted when
+    /// `include_deleted` is true. Content types without entries are absent from the map.
+    pub fn count_by_content(&self, include_deleted: bool) -> HashMap<DataContentType, usize> {
+        let mut counts = HashMap::new();
+        for entry in &self.entries {
+            if include_deleted || entry.is_alive() {
+                *counts.entry(entry.content_type()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Count the entries of this manifest by [`ManifestStatus`], returned as the
+    /// `(added, existing, deleted)` counts.
+    ///
+    /// All entries are counted regardless of the content type of their data file.
+    pub fn status_counts(&self) -> (usize, usize, usize) {
+        self.entries
+            .iter()
+            .fold((0, 0, 0), |(added, existing, deleted), entry| {
+                match entry.status() {
+                    ManifestStatus::Added => (added + 1, existing, deleted),
+                    ManifestStatus::Existing => (added, existing + 1, deleted),
+                    ManifestStatus::Deleted => (added, existing, deleted + 1),
                 }
-            },
-        ));
+            })
+    }
 
-        Ok(())
+    /// Compute the partition summaries of this manifest from its entries, the way
+    /// [`ManifestWriter`] computes them on write, e.g. to check the `partitions` of the
+    /// [`ManifestFile`] recorded in a manifest list.
+    ///
+    /// Entries of all statuses are summarized, like the writer does.
+    pub fn compute_partition_summaries(&self) -> Result<Vec<FieldSummary>> {
+        let partition_type = self
+            .metadata
+            .partition_spec
+            .partition_type(&self.metadata.schema)?;
+        let mut field_stats = ManifestWriter::new_partition_field_stats(&partition_type);
+        for entry in &self.entries {
+            update_partition_field_stats(&mut field_stats, &entry.data_file)?;
+        }
+        Ok(field_stats.into_iter().map(|stat| stat.finish()).collect())
     }
 
-    pub(crate) fn finish(mut self) -> FieldSummary {
-        // Always set contains_nan
-        self.summary.contains_nan = self.summary.contains_nan.or(Some(false));
-        self.summary
+    /// Sum the `record_count` of live entries by the content type of their data file,
+    /// e.g. to estimate the number of rows a merge-on-read scan has to delete.
+    ///
+    /// Entries with status [`ManifestStatus::Deleted`] are excluded, entries with status
+    /// [`ManifestStatus::Added`] and [`ManifestStatus::Existing`] are included. Content
+    /// types without live entries are absent from the map.
+    pub fn row_counts_by_content(&self) -> HashMap<DataContentType, u64> {
+        let mut counts = HashMap::new();
+        for entry in self.entries.iter().filter(|entry| entry.is_alive()) {
+            *counts.entry(entry.content_type()).or_insert(0) += entry.record_count();
+        }
+        counts
     }
-}
 
-impl ManifestWriter {
-    /// Create a new manifest writer.
-    pub(crate) fn new(
-        output: OutputFile,
-        snapshot_id: Option<i64>,
-        key_metadata: Vec<u8>,
-        metadata: ManifestMetadata,
-    ) -> Self {
-        Self {
-            output,
-            snapshot_id,
-            added_files: 0,
-            added_rows: 0,
-            existing_files: 0,
-            existing_rows: 0,
-            deleted_files: 0,
-            deleted_rows: 0,
-            min_seq_num: None,
-            key_metadata,
-            manifest_entries: Vec::new(),
-            metadata,
+    /// Estimate the fraction of null values of each column over the data files of live
+    /// entries, from their `null_value_counts` and `value_counts` metrics.
+    ///
+    /// Delete files are ignored. Columns without values, or without value counts, are
+    /// absent from the map.
+    pub fn column_null_fraction(&self) -> HashMap<i32, f64> {
+        let mut totals: HashMap<i32, (u64, u64)> = HashMap::new();
+        for data_file in self
+            .data_files()
+            .filter(|data_file| data_file.content == DataContentType::Data)
+        {
+            for (field_id, value_count) in &data_file.value_counts {
+                let null_count = data_file
+                    .null_value_counts
+                    .get(field_id)
+                    .copied()
+                    .unwrap_or_default();
+                let (nulls, values) = totals.entry(*field_id).or_default();
+                *nulls += null_count;
+                *values += value_count;
+            }
         }
+        totals
+            .into_iter()
+            .filter(|(_, (_, values))| *values > 0)
+            .map(|(field_id, (nulls, values))| (field_id, nulls as f64 / values as f64))
+            .collect()
     }
 
-    fn construct_partition_summaries(
-        &mut self,
-        partition_type: &StructType,
-    ) -> Result<Vec<FieldSummary>> {
-        let mut field_stats: Vec<_> = partition_type
-            .fields()
+    /// Group the delete files of live entries by the path of the data file they
+    /// reference.
+    ///
+    /// Delete files without a `referenced_data_file` may apply to any data file in
+    /// their partition, they are returned separately as the second element.
+    pub fn group_deletes_by_referenced_file(
+        &self,
+    ) -> (HashMap<&str, Vec<&DataFile>>, Vec<&DataFile>) {
+        let mut scoped: HashMap<&str, Vec<&DataFile>> = HashMap::new();
+        let mut unscoped = Vec::new();
+        for data_file in self
+            .data_files()
+            .filter(|data_file| data_file.content != DataContentType::Data)
+        {
+            match data_file.referenced_data_file.as_deref() {
+                Some(path) => scoped.entry(path).or_default().push(data_file),
+                None => unscoped.push(data_file),
+            }
+        }
+        (scoped, unscoped)
+    }
+
+    /// Metadata of this manifest.
+    pub fn metadata(&self) -> &ManifestMetadata {
+        &self.metadata
+    }
+
+    /// SHA-256 hash of the logical content of this manifest, invariant to the order of
+    /// entries and of map iteration, e.g. to skip rewriting a manifest that didn't change.
+    ///
+    /// The hash covers a canonical encoding, in which integers are little-endian, strings
+    /// and bytes are prefixed with their length as a u64, optional values with a `0` byte
+    /// when absent or a `1` byte when present, and lists and maps with their length as a
+    /// u64. It consists of, in order:
+    ///
+    /// - the tag `iceberg-manifest-content-v1`, changed whenever the encoding changes,
+    /// - the format version and the content type as u8,
+    /// - the schema id, the sorted identifier field ids and the schema fields as JSON,
+    /// - the partition spec id and, for each partition field, its source id, field id,
+    ///   name and transform,
+    /// - the entries, each encoded as described below, sorted by file path and then by
+    ///   their encoding.
+    ///
+    /// An entry is encoded as its file path, status, snapshot id, sequence number, file
+    /// sequence number, content type, file format, partition values, record count, file
+    /// size, block size, column sizes, value counts, null value counts, NaN value counts,
+    /// lower bounds, upper bounds, key metadata, split offsets, equality ids, sort order
+    /// id, referenced data file and partition spec id. Maps are sorted by field id. A
+    /// value, in the partition or the bounds, is a tag byte for its literal variant
+    /// followed by its little-endian or length-prefixed representation. Partition values
+    /// produced by partition transforms are primitive, a nested one is encoded as a tag
+    /// byte, 11 for a struct, 12 for a list and 13 for a map, followed by the list of its
+    /// optional items, map entries as key-value pairs sorted by their encoding.
+    ///
+    /// The extra metadata of the manifest isn't covered.
+    pub fn content_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+
+        let mut encoder = CanonicalEncoder::default();
+        encoder.bytes(b"iceberg-manifest-content-v1");
+        encoder.0.push(self.metadata.format_version as u8);
+        encoder.0.push(self.metadata.content as u8);
+        let schema = &self.metadata.schema;
+        encoder.i32(schema.schema_id());
+        encoder.list(schema.identifier_field_ids().sorted(), |e, id| e.i32(id));
+        encoder.bytes(&to_vec(schema.as_struct()).expect("A schema always serializes to JSON"));
+        let partition_spec = &self.metadata.partition_spec;
+        encoder.i32(partition_spec.spec_id());
+        encoder.list(partition_spec.fields(), |e, field| {
+            e.i32(field.source_id);
+            e.i32(field.field_id);
+            e.bytes(field.name.as_bytes());
+            e.bytes(field.transform.to_string().as_bytes());
+        });
+        hasher.update(&encoder.0);
+
+        let entries = self
+            .entries
             .iter()
-            .map(|f| PartitionFieldStats::new(f.field_type.as_primitive_type().unwrap().clone()))
-            .collect();
-        for partition in self.manifest_entries.iter().map(|e| &e.data_file.partition) {
-            for (literal, stat) in partition.iter().zip_eq(field_stats.iter_mut()) {
-                let primitive_literal = literal.map(|v| v.as_primitive_literal().unwrap());
-                stat.update(primitive_literal)?;
+            .map(|entry| (entry.file_path(), entry.canonical_encoding()))
+            .sorted()
+            .collect_vec();
+        hasher.update((entries.len() as u64).to_le_bytes());
+        for (_, encoding) in entries {
+            hasher.update(encoding);
+        }
+
+        hasher.finalize().into()
+    }
+
+    /// The `schema-id` recorded in the manifest metadata, i.e. the id of the schema used to
+    /// write the manifest.
+    ///
+    /// It's normally the id of the embedded schema, `metadata().schema().schema_id()`, but
+    /// some producers record a stale id, see [`ManifestMetadata::validate_schema_id`] to
+    /// check that they agree.
+    pub fn schema_id(&self) -> SchemaId {
+        self.metadata.schema_id
+    }
+
+    /// Returns true if this manifest tracks delete files.
+    pub fn is_delete_manifest(&self) -> bool {
+        self.metadata.content == ManifestContentType::Deletes
+    }
+
+    /// Returns true if this manifest tracks data files.
+    pub fn is_data_manifest(&self) -> bool {
+        self.metadata.content == ManifestContentType::Data
+    }
+
+    /// Fill the snapshot id, sequence number and file sequence number missing from the
+    /// entries with the values of `manifest_file`, the manifest list entry of this
+    /// manifest, following the inheritance rules of the spec: the snapshot id is always
+    /// inherited, sequence numbers only by added entries or when `manifest_file` has the
+    /// initial sequence number.
+    ///
+    /// Entries are copied on write, so entries shared with other manifests aren't changed.
+    pub fn inherit_from(&mut self, manifest_file: &ManifestFile) {
+        for entry in &mut self.entries {
+            if entry.snapshot_id.is_none()
+                || entry.sequence_number.is_none()
+                || entry.file_sequence_number.is_none()
+            {
+                Arc::make_mut(entry).inherit_data(manifest_file);
             }
         }
-        Ok(field_stats.into_iter().map(|stat| stat.finish()).collect())
     }
 
-    fn check_data_file(&self, data_file: &DataFile) -> Result<()> {
-        match self.metadata.content {
-            ManifestContentType::Data => {
-                if data_file.content != DataContentType::Data {
-                    return Err(Error::new(
-                        ErrorKind::DataInvalid,
-                        format!(
-                            "Content type of entry {:?} should have DataContentType::Data",
-                            data_file.content
-                        ),
-                    ));
-                }
+    /// Rewrite the file path of every entry's data file, and the referenced data file of
+    /// delete files, with `f`, e.g. to relocate a table. Paths for which `f` returns `None`
+    /// are kept as is.
+    ///
+    /// Entries are copied on write, so entries shared with other manifests aren't changed.
+    pub fn rewrite_paths(&mut self, f: impl Fn(&str) -> Option<String>) {
+        for entry in &mut self.entries {
+            let file_path = f(entry.file_path());
+            let referenced_data_file = entry.data_file.referenced_data_file.as_deref().and_then(&f);
+            if file_path.is_none() && referenced_data_file.is_none() {
+                continue;
             }
-            ManifestContentType::Deletes => {
-                if data_file.content != DataContentType::EqualityDeletes
-                    && data_file.content != DataContentType::PositionDeletes
-                {
-                    return Err(Error::new(
-                    ErrorKind::DataInvalid,
-                    format!("Content type of entry {:?} should have DataContentType::EqualityDeletes or DataContentType::PositionDeletes", data_file.content),
-                ));
-                }
+            let data_file = &mut Arc::make_mut(entry).data_file;
+            if let Some(file_path) = file_path {
+                data_file.file_path = file_path;
+            }
+            if referenced_data_file.is_some() {
+                data_file.referenced_data_file = referenced_data_file;
             }
         }
-        Ok(())
     }
 
-    /// Add a new manifest entry. This method will update following status of the entry:
-    /// - Update the entry status to `Added`
-    /// - Set the snapshot id to the current snapshot id
-    /// - Set the sequence number to `None` if it is invalid(smaller than 0)
-    /// - Set the file sequence number to `None`
-    pub(crate) fn add_entry(&mut self, mut entry: ManifestEntry) -> Result<()> {
-        self.check_data_file(&entry.data_file)?;
-        if entry.sequence_number().is_some_and(|n| n >= 0) {
-            entry.status = ManifestStatus::Added;
-            entry.snapshot_id = self.snapshot_id;
-            entry.file_sequence_number = None;
-        } else {
-            entry.status = ManifestStatus::Added;
-            entry.snapshot_id = self.snapshot_id;
-            entry.sequence_number = None;
-            entry.file_sequence_number = None;
-        };
-        self.add_entry_inner(entry)?;
-        Ok(())
+    /// Consume this Manifest, returning its constituent parts
+    pub fn into_parts(self) -> (Vec<ManifestEntryRef>, ManifestMetadata) {
+        let Self { entries, metadata } = self;
+        (entries, metadata)
     }
 
-    /// Add file as an added entry with a specific sequence number. The entry's snapshot ID will be this manifest's snapshot ID. The entry's data sequence
-    /// number will be the provided data sequence number. The entry's file sequence number will be
-    /// assigned at commit.
-    pub fn add_file(&mut self, data_file: DataFile, sequence_number: i64) -> Result<()> {
-        self.check_data_file(&data_file)?;
-        let entry = ManifestEntry {
-            status: ManifestStatus::Added,
-            snapshot_id: self.snapshot_id,
-            sequence_number: (sequence_number >= 0).then_some(sequence_number),
-            file_sequence_number: None,
-            data_file,
-        };
-        self.add_entry_inner(entry)?;
-        Ok(())
+    /// Constructor from [`ManifestMetadata`] and [`ManifestEntry`]s.
+    pub fn new(metadata: ManifestMetadata, entries: Vec<ManifestEntry>) -> Self {
+        Self {
+            metadata,
+            entries: entries.into_iter().map(Arc::new).collect(),
+        }
     }
 
-    /// Add a delete manifest entry. This method will update following status of the entry:
-    /// - Update the entry status to `Deleted`
-    /// - Set the snapshot id to the current snapshot id
+    /// Constructor from [`ManifestMetadata`] and shared [`ManifestEntryRef`]s, e.g. the
+    /// filtered entries of a parsed manifest, without cloning them.
+    pub fn from_refs(metadata: ManifestMetadata, entries: Vec<ManifestEntryRef>) -> Self {
+        Self { metadata, entries }
+    }
+}
+
+/// A problem found by [`Manifest::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestValidationIssue {
+    /// The file path of the entry with the problem.
+    pub file_path: String,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl std::fmt::Display for ManifestValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.file_path, self.message)
+    }
+}
+
+/// The entries that differ between two manifests, see [`Manifest::diff`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ManifestDiff<'a> {
+    /// Entries whose file path is only in the new manifest.
+    pub added: Vec<&'a ManifestEntryRef>,
+    /// Entries whose file path is only in the old manifest.
+    pub removed: Vec<&'a ManifestEntryRef>,
+    /// The old and new entries of the file paths in both manifests whose entries differ.
+    pub changed: Vec<(&'a ManifestEntryRef, &'a ManifestEntryRef)>,
+}
+
+impl ManifestDiff<'_> {
+    /// Returns true if the manifests have the same entries.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Reinterpret the decimal bounds of `entries`, which were read with the types of the
+/// table schema, as written with the scales in `embedded_schema`, and rescale them back to
+/// the scales they were read with. Bounds that can't be rescaled are dropped, returning a
+/// diagnostic for each.
+fn rescale_decimal_bounds(entries: &mut [ManifestEntry], embedded_schema: &Schema) -> Vec<String> {
+    let mut diagnostics = vec![];
+    for entry in entries {
+        let DataFile {
+            file_path,
+            lower_bounds,
+            upper_bounds,
+            ..
+        } = &mut entry.data_file;
+        for (kind, bounds) in [("lower", lower_bounds), ("upper", upper_bounds)] {
+            bounds.retain(|field_id, datum| {
+                let (
+                    PrimitiveType::Decimal { precision, scale },
+                    PrimitiveLiteral::Int128(unscaled),
+                ) = (datum.data_type().clone(), datum.literal().clone())
+                else {
+                    return true;
+                };
+                let written_scale = match embedded_schema
+                    .field_by_id(*field_id)
+                    .and_then(|field| field.field_type.as_primitive_type())
+                {
+                    Some(PrimitiveType::Decimal { scale, .. }) => *scale,
+                    _ => return true,
+                };
+                if written_scale == scale {
+                    return true;
+                }
+                match rescale_decimal(unscaled, written_scale, precision, scale) {
+                    Some(rescaled) => {
+                        *datum = Datum::new(
+                            PrimitiveType::Decimal { precision, scale },
+                            PrimitiveLiteral::Int128(rescaled),
+                        );
+                        true
+                    }
+                    None => {
+                        diagnostics.push(format!(
+                            "Dropped {kind} bound of field id {field_id} in {file_path}: unscaled value {unscaled} with scale {written_scale} can't be rescaled to decimal({precision}, {scale})"
+                        ));
+                        false
+                    }
+                }
+            });
+        }
+    }
+    diagnostics.sort();
+    diagnostics
+}
+
+/// Rescale the unscaled decimal value `unscaled` from scale `from_scale` to `scale`, returning
+/// `None` when that isn't exact or the result doesn't fit in `precision` digits.
+fn rescale_decimal(unscaled: i128, from_scale: u32, precision: u32, scale: u32) -> Option<i128> {
+    let rescaled = if scale >= from_scale {
+        unscaled.checked_mul(10i128.checked_pow(scale - from_scale)?)?
+    } else {
+        let factor = 10i128.checked_pow(from_scale - scale)?;
+        if unscaled % factor != 0 {
+            return None;
+        }
+        unscaled / factor
+    };
+    (rescaled.unsigned_abs() < 10u128.checked_pow(precision)?).then_some(rescaled)
+}
+
+/// Envelope encryption of manifest files.
+///
+/// When set on a [`ManifestWriterBuilder`], the avro bytes of the manifest are encrypted
+/// before they are written, and the wrapped data key is stored in the `key_metadata` of
+/// the resulting [`ManifestFile`]. [`ManifestReader::read_encrypted`] decrypts the bytes
+/// with that key metadata before parsing them.
+#[async_trait::async_trait]
+pub trait EncryptionManager: std::fmt::Debug + Send + Sync {
+    /// Encrypt the avro bytes of a manifest, updating `key_metadata` with whatever is
+    /// needed to decrypt them again, e.g. the data key wrapped by a master key.
     ///
-    /// # TODO
-    /// Remove this allow later
-    #[allow(dead_code)]
-    pub(crate) fn add_delete_entry(&mut self, mut entry: ManifestEntry) -> Result<()> {
-        self.check_data_file(&entry.data_file)?;
-        entry.status = ManifestStatus::Deleted;
-        entry.snapshot_id = self.snapshot_id;
-        self.add_entry_inner(entry)?;
-        Ok(())
+    /// `key_metadata` initially holds the key metadata given to the
+    /// [`ManifestWriterBuilder`].
+    async fn encrypt(&self, bytes: Bytes, key_metadata: &mut Vec<u8>) -> Result<Bytes>;
+
+    /// Decrypt bytes encrypted by [`EncryptionManager::encrypt`], given the key metadata
+    /// it produced.
+    async fn decrypt(&self, bytes: Bytes, key_metadata: &[u8]) -> Result<Bytes>;
+}
+
+/// A reader of manifest avro files, with options controlling how strictly the
+/// manifest is validated while parsing.
+///
+/// [`Manifest::parse_avro`] reads with the default options.
+///
+/// Hostile input is rejected with an error rather than aborting: every array, map and
+/// bytes length declared in the file is checked against the bytes left in its avro block
+/// before decoding, so e.g. a bogus `split_offsets` length can't cause an OOM, and blocks
+/// are decompressed up to 64 MiB.
+#[derive(Debug, Clone, Default, TypedBuilder)]
+pub struct ManifestReader {
+    /// Fail when the `schema-id` recorded in the manifest metadata doesn't match the
+    /// id of the embedded `schema`. Off by default since the `schema-id` key is known
+    /// to be stale in manifests written by some producers, in which case a mismatch is
+    /// reported as a diagnostic.
+    #[builder(default)]
+    strict_schema_id: bool,
+    /// Map the fields of the partition tuple by position when their field ids, as
+    /// written in the avro schema of the file, don't match the partition spec. Some
+    /// producers are known to write wrong partition field ids. Off by default, in
+    /// which case such manifests are rejected.
+    #[builder(default)]
+    tolerant_partition_field_ids: bool,
+    /// Read a negative `record_count`, written as a sentinel by some producers, as 0
+    /// instead of failing the whole manifest. Off by default.
+    #[builder(default)]
+    lenient_record_count: bool,
+    /// Assign ids by position, starting at 1000, to the fields of the `partition-spec`
+    /// metadata written without field ids, as done by very old producers. Off by
+    /// default, in which case such manifests are rejected.
+    #[builder(default)]
+    assign_missing_partition_field_ids: bool,
+    /// Read a `sort_order_id` of 0, written by some producers to mean unsorted, as
+    /// missing, so it compares equal to files of producers that leave it out. Off by
+    /// default, in which case sort order ids are kept as written.
+    #[builder(default)]
+    normalize_sort_order_id: bool,
+    /// Decrypt manifests in [`ManifestReader::read_encrypted`]. Manifests are read as
+    /// plain avro by default.
+    #[builder(default, setter(strip_option))]
+    encryption_manager: Option<Arc<dyn EncryptionManager>>,
+}
+
+impl ManifestReader {
+    /// Parse manifest from bytes of avro file.
+    pub fn read(&self, bs: &[u8]) -> Result<Manifest> {
+        let (metadata, entries) = self.read_parts(bs)?;
+        Ok(Manifest::new(metadata, entries))
     }
 
-    /// Add a file as delete manifest entry. The entry's snapshot ID will be this manifest's snapshot ID.
-    /// However, the original data and file sequence numbers of the file must be preserved when
-    /// the file is marked as deleted.
-    pub fn add_delete_file(
-        &mut self,
-        data_file: DataFile,
-        sequence_number: i64,
-        file_sequence_number: Option<i64>,
-    ) -> Result<()> {
-        self.check_data_file(&data_file)?;
-        let entry = ManifestEntry {
-            status: ManifestStatus::Deleted,
-            snapshot_id: self.snapshot_id,
-            sequence_number: Some(sequence_number),
-            file_sequence_number,
-            data_file,
+    /// Parse manifest from the bytes of a manifest file, which are first decrypted with
+    /// `key_metadata`, the `key_metadata` of its [`ManifestFile`], when an encryption
+    /// manager is set.
+    pub async fn read_encrypted(&self, bs: Bytes, key_metadata: &[u8]) -> Result<Manifest> {
+        let bs = match &self.encryption_manager {
+            Some(encryption_manager) => encryption_manager.decrypt(bs, key_metadata).await?,
+            None => bs,
         };
-        self.add_entry_inner(entry)?;
-        Ok(())
+        self.read(&bs)
     }
 
-    /// Add an existing manifest entry. This method will update following status of the entry:
-    /// - Update the entry status to `Existing`
+    /// Read and parse the manifests of `files` concurrently, with at most `concurrency`
+    /// reads in flight, yielding each manifest with its [`ManifestFile`] in the order of
+    /// `files`, e.g. to apply inheritance from the manifest list entry downstream.
     ///
-    /// # TODO
-    /// Remove this allow later
-    #[allow(dead_code)]
-    pub(crate) fn add_existing_entry(&mut self, mut entry: ManifestEntry) -> Result<()> {
-        self.check_data_file(&entry.data_file)?;
-        entry.status = ManifestStatus::Existing;
-        self.add_entry_inner(entry)?;
-        Ok(())
+    /// Manifests are only read as the stream is polled, so a slow consumer holds back
+    /// the reads instead of manifests being buffered. A failed read or parse is yielded
+    /// as an error without ending the stream. Manifests are decrypted as in
+    /// [`ManifestReader::read_encrypted`].
+    pub fn stream(
+        &self,
+        files: Vec<(ManifestFile, InputFile)>,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<(ManifestFile, Manifest)>> {
+        let reader = self.clone();
+        stream::iter(files)
+            .map(move |(manifest_file, input)| {
+                let reader = reader.clone();
+                async move {
+                    let bs = input.read().await?;
+                    let manifest = reader
+                        .read_encrypted(bs, &manifest_file.key_metadata)
+                        .await?;
+                    Ok((manifest_file, manifest))
+                }
+            })
+            .buffered(concurrency.max(1))
     }
 
-    /// Add an file as existing manifest entry. The original data and file sequence numbers, snapshot ID,
-    /// which were assigned at commit, must be preserved when adding an existing entry.
-    pub fn add_existing_file(
-        &mut self,
-        data_file: DataFile,
-        snapshot_id: i64,
-        sequence_number: i64,
-        file_sequence_number: Option<i64>,
-    ) -> Result<()> {
-        self.check_data_file(&data_file)?;
-        let entry = ManifestEntry {
-            status: ManifestStatus::Existing,
-            snapshot_id: Some(snapshot_id),
-            sequence_number: Some(sequence_number),
-            file_sequence_number,
-            data_file,
-        };
-        self.add_entry_inner(entry)?;
-        Ok(())
+    /// Parse manifest from bytes of avro file, also returning diagnostics about the
+    /// recoveries made while reading, e.g. partition fields mapped by position.
+    pub fn read_with_diagnostics(&self, bs: &[u8]) -> Result<(Manifest, Vec<String>)> {
+        let (metadata, entries, diagnostics) = self.read_parts_with_diagnostics(bs, None)?;
+        Ok((Manifest::new(metadata, entries), diagnostics))
     }
 
-    fn add_entry_inner(&mut self, entry: ManifestEntry) -> Result<()> {
-        // Check if the entry has sequence number
-        if (entry.status == ManifestStatus::Deleted || entry.status == ManifestStatus::Existing)
-            && (entry.sequence_number.is_none() || entry.file_sequence_number.is_none())
-        {
-            return Err(Error::new(
-                ErrorKind::DataInvalid,
-                "Manifest entry with status Existing or Deleted should have sequence number",
-            ));
-        }
+    /// Parse manifest metadata and entries from bytes of avro file.
+    pub(crate) fn read_parts(&self, bs: &[u8]) -> Result<(ManifestMetadata, Vec<ManifestEntry>)> {
+        self.read_parts_with_schema(bs, None)
+    }
 
-        // Update the statistics
-        match entry.status {
-            ManifestStatus::Added => {
-                self.added_files += 1;
-                self.added_rows += entry.data_file.record_count;
-            }
-            ManifestStatus::Deleted => {
-                self.deleted_files += 1;
-                self.deleted_rows += entry.data_file.record_count;
-            }
-            ManifestStatus::Existing => {
-                self.existing_files += 1;
-                self.existing_rows += entry.data_file.record_count;
-            }
-        }
-        if entry.is_alive() {
-            if let Some(seq_num) = entry.sequence_number {
-                self.min_seq_num = Some(self.min_seq_num.map_or(seq_num, |v| min(v, seq_num)));
+    /// Parse manifest metadata and entries from bytes of avro file, using `schema`
+    /// instead of the embedded schema when given.
+    fn read_parts_with_schema(
+        &self,
+        bs: &[u8],
+        schema: Option<SchemaRef>,
+    ) -> Result<(ManifestMetadata, Vec<ManifestEntry>)> {
+        let (metadata, entries, _) = self.read_parts_with_diagnostics(bs, schema)?;
+        Ok((metadata, entries))
+    }
+
+    fn read_parts_with_diagnostics(
+        &self,
+        bs: &[u8],
+        schema: Option<SchemaRef>,
+    ) -> Result<(ManifestMetadata, Vec<ManifestEntry>, Vec<String>)> {
+        let mut diagnostics = vec![];
+        let (metadata, partition_type) = self.read_metadata(bs, schema, &mut diagnostics)?;
+        let spec_id = metadata.partition_spec.spec_id();
+        let mut entries = self.read_entries(
+            bs,
+            metadata.format_version,
+            &partition_type,
+            &mut diagnostics,
+            |entry| entry.try_into(spec_id, &partition_type, &metadata.schema),
+        )?;
+        if self.normalize_sort_order_id {
+            for entry in &mut entries {
+                normalize_sort_order_id(&mut entry.data_file);
             }
         }
-        self.manifest_entries.push(entry);
-        Ok(())
+
+        Ok((metadata, entries, diagnostics))
     }
 
-    /// Write manifest file and return it.
-    pub async fn write_manifest_file(mut self) -> Result<ManifestFile> {
-        // Create the avro writer
-        let partition_type = self
-            .metadata
-            .partition_spec
-            .partition_type(&self.metadata.schema)?;
-        let table_schema = &self.metadata.schema;
-        let avro_schema = match self.metadata.format_version {
-            FormatVersion::V1 => manifest_schema_v1(&partition_type)?,
-            FormatVersion::V2 => manifest_schema_v2(&partition_type)?,
-        };
-        let mut avro_writer = AvroWriter::new(&avro_schema, Vec::new());
-        avro_writer.add_user_metadata(
-            "schema".to_string(),
-            to_vec(table_schema).map_err(|err| {
-                Error::new(ErrorKind::DataInvalid, "Fail to serialize table schema")
-                    .with_source(err)
-            })?,
-        )?;
-        avro_writer.add_user_metadata(
-            "schema-id".to_string(),
-            table_schema.schema_id().to_string(),
-        )?;
-        avro_writer.add_user_metadata(
-            "partition-spec".to_string(),
-            to_vec(&self.metadata.partition_spec.fields()).map_err(|err| {
-                Error::new(ErrorKind::DataInvalid, "Fail to serialize partition spec")
-                    .with_source(err)
-            })?,
-        )?;
-        avro_writer.add_user_metadata(
-            "partition-spec-id".to_string(),
-            self.metadata.partition_spec.spec_id().to_string(),
-        )?;
-        avro_writer.add_user_metadata(
-            "format-version".to_string(),
-            (self.metadata.format_version as u8).to_string(),
+    /// Parse the manifest metadata from bytes of avro file, using `schema` instead of the
+    /// embedded schema when given, and return it with the type to decode the partition
+    /// tuples of the entries with.
+    fn read_metadata(
+        &self,
+        bs: &[u8],
+        schema: Option<SchemaRef>,
+        diagnostics: &mut Vec<String>,
+    ) -> Result<(ManifestMetadata, StructType)> {
+        check_avro_container(bs)?;
+        let reader = AvroReader::new(bs)?;
+
+        let meta = reader.user_metadata();
+        let metadata = ManifestMetadata::parse_with_schema(
+            meta,
+            schema,
+            self.assign_missing_partition_field_ids,
         )?;
-        if self.metadata.format_version == FormatVersion::V2 {
-            avro_writer
-                .add_user_metadata("content".to_string(), self.metadata.content.to_string())?;
+        if let Err(err) = metadata.validate_schema_id() {
+            if self.strict_schema_id {
+                return Err(err);
+            }
+            diagnostics.push(format!("{}, kept it as recorded", err.message()));
         }
 
-        let partition_summary = self.construct_partition_summaries(&partition_type)?;
-        // Write manifest entries
-        for entry in std::mem::take(&mut self.manifest_entries) {
-            let value = match self.metadata.format_version {
-                FormatVersion::V1 => {
-                    to_value(_serde::ManifestEntryV1::try_from(entry, &partition_type)?)?
-                        .resolve(&avro_schema)?
-                }
-                FormatVersion::V2 => {
-                    to_value(_serde::ManifestEntryV2::try_from(entry, &partition_type)?)?
-                        .resolve(&avro_schema)?
+        let partition_type = self.partition_type_for_read(
+            reader.writer_schema(),
+            metadata.partition_spec.partition_type(&metadata.schema)?,
+            diagnostics,
+        )?;
+        Ok((metadata, partition_type))
+    }
+
+    /// Decode the entries in bytes of avro file written with `format_version`, applying
+    /// `decode` to each once the reader options are applied to it.
+    fn read_entries<T>(
+        &self,
+        bs: &[u8],
+        format_version: FormatVersion,
+        partition_type: &StructType,
+        diagnostics: &mut Vec<String>,
+        mut decode: impl FnMut(_serde::VersionedManifestEntry) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let schema = match format_version {
+            FormatVersion::V1 => manifest_schema_v1(partition_type)?,
+            FormatVersion::V2 => manifest_schema_v2(partition_type)?,
+        };
+        read_avro_values(bs, &schema)?
+            .map(|value| {
+                let value = value?;
+                let mut entry = match format_version {
+                    FormatVersion::V1 => _serde::VersionedManifestEntry::V1(from_value(&value)?),
+                    FormatVersion::V2 => _serde::VersionedManifestEntry::V2(from_value(&value)?),
+                };
+                if self.lenient_record_count {
+                    if let Some((file_path, record_count)) = entry.clamp_negative_record_count() {
+                        diagnostics.push(format!(
+                            "Negative record count {record_count} of data file {file_path} read as 0"
+                        ));
+                    }
                 }
-            };
+                decode(entry)
+            })
+            .collect()
+    }
 
-            avro_writer.append(value)?;
+    /// Check the field ids of the partition tuple written in the file against
+    /// `partition_type`, derived from the partition spec, and return the type to decode
+    /// the partition tuple with.
+    ///
+    /// Partition tuples are decoded by field name, so when the ids don't match and
+    /// `tolerant_partition_field_ids` is set, the returned type carries the written
+    /// names and ids with the types of `partition_type`, position by position. The
+    /// decoded tuple then lines up with the partition spec.
+    fn partition_type_for_read(
+        &self,
+        writer_schema: &AvroSchema,
+        partition_type: StructType,
+        diagnostics: &mut Vec<String>,
+    ) -> Result<StructType> {
+        let Some(written) = written_partition_fields(writer_schema) else {
+            return Ok(partition_type);
+        };
+        let expected_ids = partition_type.fields().iter().map(|f| f.id).collect_vec();
+        let matches = written.len() == expected_ids.len()
+            && written
+                .iter()
+                .zip(&expected_ids)
+                .all(|((_, written_id), id)| written_id.unwrap_or(*id) == *id);
+        if matches {
+            return Ok(partition_type);
         }
 
-        let content = avro_writer.into_inner()?;
-        let length = content.len();
-        self.output.write(Bytes::from(content)).await?;
+        let written_ids = written.iter().map(|(_, id)| *id).collect_vec();
+        if !self.tolerant_partition_field_ids || written.len() != expected_ids.len() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Partition field ids {written_ids:?} written in the manifest don't match the partition spec field ids {expected_ids:?}"
+                ),
+            ));
+        }
 
-        Ok(ManifestFile {
-            manifest_path: self.output.location().to_string(),
-            manifest_length: length as i64,
-            partition_spec_id: self.metadata.partition_spec.spec_id(),
-            content: self.metadata.content,
-            // sequence_number and min_sequence_number with UNASSIGNED_SEQUENCE_NUMBER will be replace with
-            // real sequence number in `ManifestListWriter`.
-            sequence_number: UNASSIGNED_SEQUENCE_NUMBER,
-            min_sequence_number: self.min_seq_num.unwrap_or(UNASSIGNED_SEQUENCE_NUMBER),
-            added_snapshot_id: self.snapshot_id.unwrap_or(UNASSIGNED_SNAPSHOT_ID),
-            added_files_count: Some(self.added_files),
-            existing_files_count: Some(self.existing_files),
-            deleted_files_count: Some(self.deleted_files),
-            added_rows_count: Some(self.added_rows),
-            existing_rows_count: Some(self.existing_rows),
-            deleted_rows_count: Some(self.deleted_rows),
-            partitions: partition_summary,
-            key_metadata: self.key_metadata,
-        })
+        diagnostics.push(format!(
+            "Partition field ids {written_ids:?} written in the manifest don't match the partition spec field ids {expected_ids:?}, mapped partition fields by position"
+        ));
+        let fields = partition_type
+            .fields()
+            .iter()
+            .zip(written)
+            .map(|(field, (name, id))| {
+                let mut field = field.as_ref().clone();
+                field.name = name;
+                field.id = id.unwrap_or(field.id);
+                Arc::new(field)
+            })
+            .collect();
+        Ok(StructType::new(fields))
+    }
+
+    /// Parse manifest metadata and entries from bytes of avro file, deferring the
+    /// decoding of column level statistics until [`LazyManifestEntry::decode_stats`]
+    /// is called. Diagnostics are returned as in [`ManifestReader::read_with_diagnostics`].
+    ///
+    /// This is meant for scans that prune most entries on their partition tuple
+    /// before looking at column stats. See [`LazyManifestEntry`] for the tradeoff.
+    pub fn read_lazy(
+        &self,
+        bs: &[u8],
+    ) -> Result<(ManifestMetadata, Vec<LazyManifestEntry>, Vec<String>)> {
+        let mut diagnostics = vec![];
+        let (metadata, partition_type) = self.read_metadata(bs, None, &mut diagnostics)?;
+        let spec_id = metadata.partition_spec.spec_id();
+        let mut entries = self.read_entries(
+            bs,
+            metadata.format_version,
+            &partition_type,
+            &mut diagnostics,
+            |mut entry| {
+                let raw_stats = entry.take_raw_stats();
+                let entry = entry.try_into(spec_id, &partition_type, &metadata.schema)?;
+                Ok(LazyManifestEntry::new(
+                    entry,
+                    raw_stats,
+                    metadata.schema.clone(),
+                ))
+            },
+        )?;
+        if self.normalize_sort_order_id {
+            for entry in &mut entries {
+                normalize_sort_order_id(&mut entry.entry.data_file);
+            }
+        }
+
+        Ok((metadata, entries, diagnostics))
     }
 }
 
-/// This is a helper module that defines the schema field of the manifest list entry.
-mod _const_schema {
-    use std::sync::Arc;
+/// Treat a `sort_order_id` of 0 as missing, see `ManifestReader::normalize_sort_order_id`.
+fn normalize_sort_order_id(data_file: &mut DataFile) {
+    if data_file.sort_order_id == Some(0) {
+        data_file.sort_order_id = None;
+    }
+}
 
-    use apache_avro::Schema as AvroSchema;
-    use once_cell::sync::Lazy;
+/// The metadata key-value pairs of the header of an avro object container file.
+type AvroHeaderMetadata<'a> = Vec<(&'a [u8], &'a [u8])>;
+
+/// The length and the metadata of the header of an avro object container file, or `None`
+/// if the header is incomplete.
+fn avro_header(bs: &[u8]) -> Option<(usize, AvroHeaderMetadata<'_>)> {
+    // The header is the magic, the metadata map and the sync marker. The map is encoded as
+    // blocks of key-value pairs, each block starting with its item count, and a negative
+    // count is followed by the block size in bytes. Keys and values are length-prefixed.
+    fn read_bytes<'a>(bs: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+        let len = usize::try_from(decode_avro_long(bs, pos)?).ok()?;
+        let end = pos.checked_add(len)?;
+        let bytes = bs.get(*pos..end)?;
+        *pos = end;
+        Some(bytes)
+    }
 
-    use crate::avro::schema_to_avro_schema;
-    use crate::spec::{
-        ListType, MapType, NestedField, NestedFieldRef, PrimitiveType, Schema, StructType, Type,
-    };
-    use crate::Error;
+    let mut pos = 4;
+    let mut metadata = vec![];
+    loop {
+        let count = decode_avro_long(bs, &mut pos)?;
+        if count == 0 {
+            break;
+        }
+        if count < 0 {
+            decode_avro_long(bs, &mut pos)?;
+        }
+        for _ in 0..count.unsigned_abs() {
+            let key = read_bytes(bs, &mut pos)?;
+            let value = read_bytes(bs, &mut pos)?;
+            metadata.push((key, value));
+        }
+    }
+    let header_len = pos.checked_add(16)?;
+    (header_len <= bs.len()).then_some((header_len, metadata))
+}
 
-    static STATUS: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::required(
-                0,
-                "status",
-                Type::Primitive(PrimitiveType::Int),
-            ))
-        })
-    };
+/// Decode a zigzag varint encoded avro long at `pos`, advancing `pos` past it.
+fn decode_avro_long(bs: &[u8], pos: &mut usize) -> Option<i64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let b = *bs.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(b & 0x7f) << shift;
+        if b & 0x80 == 0 {
+            return Some((value >> 1) as i64 ^ -((value & 1) as i64));
+        }
+        shift += 7;
+        if shift >= 64 {
+            return None;
+        }
+    }
+}
 
-    static SNAPSHOT_ID_V1: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::required(
-                1,
-                "snapshot_id",
-                Type::Primitive(PrimitiveType::Long),
-            ))
-        })
-    };
+/// The largest size of a decompressed avro data block in a manifest, well above the blocks
+/// writers produce, so that a small compressed block can't expand into an arbitrarily large
+/// allocation.
+const MAX_AVRO_BLOCK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Decode the values in the data blocks of the avro object container file `bs`, resolved to
+/// `reader_schema`.
+///
+/// This stands in for the avro reader to bound the memory a malformed file can take. The
+/// avro decoder caps a declared array, map, bytes or string length at its 512 MiB allocation
+/// limit, but counts items rather than bytes against it, and reserves room for that many
+/// decoded values before reading any of them. A single bogus length, e.g. of
+/// `split_offsets`, is enough to exhaust the memory. So each value is first walked with
+/// [`skip_avro_value`], checking every length it declares against the bytes left in its
+/// block, and then decoded from the same bytes. Blocks are decompressed once, up to
+/// [`MAX_AVRO_BLOCK_SIZE`].
+fn read_avro_values<'a>(bs: &'a [u8], reader_schema: &'a AvroSchema) -> Result<AvroValues<'a>> {
+    let writer_schema = AvroReader::new(bs)?.writer_schema().clone();
+    let names = ResolvedSchema::try_from(&writer_schema)?
+        .get_names()
+        .iter()
+        .map(|(name, schema)| (name.clone(), (*schema).clone()))
+        .collect();
+    let (header_len, metadata) = avro_header(bs).ok_or_else(malformed_avro_blocks)?;
+    let codec = metadata
+        .into_iter()
+        .find(|(key, _)| *key == b"avro.codec")
+        .map_or_else(
+            || "null".to_string(),
+            |(_, codec)| String::from_utf8_lossy(codec).into_owned(),
+        );
+    Ok(AvroValues {
+        bs,
+        pos: header_len,
+        sync_marker: &bs[header_len - 16..header_len],
+        codec,
+        writer_schema,
+        names,
+        reader_schema,
+        block: Cow::Borrowed(&[]),
+        block_pos: 0,
+        block_count: 0,
+    })
+}
 
-    static SNAPSHOT_ID_V2: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                1,
-                "snapshot_id",
-                Type::Primitive(PrimitiveType::Long),
-            ))
-        })
-    };
+/// The values of an avro object container file, see [`read_avro_values`].
+struct AvroValues<'a> {
+    bs: &'a [u8],
+    pos: usize,
+    sync_marker: &'a [u8],
+    codec: String,
+    writer_schema: AvroSchema,
+    names: HashMap<Name, AvroSchema>,
+    reader_schema: &'a AvroSchema,
+    /// The decompressed data block being read, and the position and the number of values
+    /// left in it.
+    block: Cow<'a, [u8]>,
+    block_pos: usize,
+    block_count: i64,
+}
 
-    static SEQUENCE_NUMBER: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                3,
-                "sequence_number",
-                Type::Primitive(PrimitiveType::Long),
-            ))
-        })
-    };
+impl AvroValues<'_> {
+    fn read_next(&mut self) -> Result<Option<apache_avro::types::Value>> {
+        while self.block_count == 0 {
+            if self.pos >= self.bs.len() {
+                return Ok(None);
+            }
+            self.read_block()?;
+        }
+        let start = self.block_pos;
+        skip_avro_value(
+            &self.writer_schema,
+            &self.names,
+            &None,
+            &self.block,
+            &mut self.block_pos,
+        )?;
+        self.block_count -= 1;
+        let value = apache_avro::from_avro_datum(
+            &self.writer_schema,
+            &mut &self.block[start..self.block_pos],
+            Some(self.reader_schema),
+        )?;
+        Ok(Some(value))
+    }
 
-    static FILE_SEQUENCE_NUMBER: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                4,
-                "file_sequence_number",
-                Type::Primitive(PrimitiveType::Long),
-            ))
-        })
-    };
+    /// Read the data block at `pos`, made of an object count, a size in bytes, the objects
+    /// and the sync marker.
+    fn read_block(&mut self) -> Result<()> {
+        let count = decode_avro_long(self.bs, &mut self.pos).ok_or_else(malformed_avro_blocks)?;
+        let size = decode_avro_long(self.bs, &mut self.pos)
+            .and_then(|size| usize::try_from(size).ok())
+            .ok_or_else(malformed_avro_blocks)?;
+        let end = self
+            .pos
+            .checked_add(size)
+            .filter(|end| self.bs.get(*end..end + 16) == Some(self.sync_marker))
+            .ok_or_else(malformed_avro_blocks)?;
+        let block = decompress_avro_block(&self.codec, &self.bs[self.pos..end])?;
+        check_avro_length(count, &block, 0)?;
+        self.block = block;
+        self.block_pos = 0;
+        self.block_count = count.max(0);
+        self.pos = end + 16;
+        Ok(())
+    }
+}
 
-    static CONTENT: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::required(
-                134,
-                "content",
-                Type::Primitive(PrimitiveType::Int),
-            ))
-        })
-    };
+impl Iterator for AvroValues<'_> {
+    type Item = Result<apache_avro::types::Value>;
 
-    static FILE_PATH: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::required(
-                100,
-                "file_path",
-                Type::Primitive(PrimitiveType::String),
-            ))
-        })
-    };
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.read_next();
+        if value.is_err() {
+            self.pos = self.bs.len();
+            self.block_count = 0;
+        }
+        value.transpose()
+    }
+}
 
-    static FILE_FORMAT: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::required(
-                101,
-                "file_format",
-                Type::Primitive(PrimitiveType::String),
-            ))
-        })
-    };
+fn malformed_avro_blocks() -> Error {
+    Error::new(
+        ErrorKind::DataInvalid,
+        "Manifest file has malformed avro data blocks",
+    )
+}
 
-    static RECORD_COUNT: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::required(
-                103,
-                "record_count",
-                Type::Primitive(PrimitiveType::Long),
-            ))
-        })
+/// Decompress an avro data block written with `codec`, failing once it exceeds
+/// [`MAX_AVRO_BLOCK_SIZE`].
+fn decompress_avro_block<'a>(codec: &str, block: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+    let too_large = || {
+        Error::new(
+            ErrorKind::DataInvalid,
+            format!(
+                "Manifest file has an avro block larger than {MAX_AVRO_BLOCK_SIZE} bytes once decompressed"
+            ),
+        )
     };
-
-    static FILE_SIZE_IN_BYTES: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::required(
-                104,
-                "file_size_in_bytes",
-                Type::Primitive(PrimitiveType::Long),
-            ))
-        })
+    let read_capped = |reader: &mut dyn Read| {
+        let mut decompressed = vec![];
+        reader
+            .take(MAX_AVRO_BLOCK_SIZE as u64 + 1)
+            .read_to_end(&mut decompressed)
+            .map_err(|err| malformed_avro_blocks().with_source(err))?;
+        if decompressed.len() > MAX_AVRO_BLOCK_SIZE {
+            return Err(too_large());
+        }
+        Ok(Cow::Owned(decompressed))
     };
+    match codec {
+        "null" => Ok(Cow::Borrowed(block)),
+        "deflate" => read_capped(&mut libflate::deflate::Decoder::new(block)),
+        "zstandard" => read_capped(
+            &mut zstd::stream::read::Decoder::new(block)
+                .map_err(|err| malformed_avro_blocks().with_source(err))?,
+        ),
+        "snappy" => {
+            // The block starts with its decompressed length as a varint, checked before
+            // decompressing it.
+            let mut len = 0u64;
+            for (i, b) in block.iter().take(10).enumerate() {
+                len |= u64::from(b & 0x7f) << (7 * i);
+                if b & 0x80 == 0 {
+                    break;
+                }
+            }
+            if len > MAX_AVRO_BLOCK_SIZE as u64 {
+                return Err(too_large());
+            }
+            let mut decompressed = block.to_vec();
+            apache_avro::Codec::from_str(codec)
+                .map_err(|_| malformed_avro_blocks())?
+                .decompress(&mut decompressed)?;
+            Ok(Cow::Owned(decompressed))
+        }
+        _ => Err(Error::new(
+            ErrorKind::FeatureUnsupported,
+            format!("unsupported avro codec: {codec}"),
+        )),
+    }
+}
 
-    // Deprecated. Always write a default in v1. Do not write in v2.
-    static BLOCK_SIZE_IN_BYTES: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::required(
-                105,
-                "block_size_in_bytes",
-                Type::Primitive(PrimitiveType::Long),
-            ))
-        })
+/// Fail when `len` items can't fit in the bytes of `block` left after `pos`.
+fn check_avro_length(len: i64, block: &[u8], pos: usize) -> Result<()> {
+    let left = block.len().saturating_sub(pos);
+    if usize::try_from(len).is_ok_and(|len| len > left) {
+        return Err(Error::new(
+            ErrorKind::DataInvalid,
+            format!("Manifest file declares a length of {len} with only {left} bytes left in its avro block"),
+        ));
+    }
+    Ok(())
+}
+
+/// Move `pos` past the avro value of type `schema` encoded at `pos` in `block`, checking
+/// the lengths it declares with [`check_avro_length`]. Mirrors how the avro decoder reads
+/// each type.
+fn skip_avro_value(
+    schema: &AvroSchema,
+    names: &HashMap<Name, AvroSchema>,
+    namespace: &Namespace,
+    block: &[u8],
+    pos: &mut usize,
+) -> Result<()> {
+    let malformed = malformed_avro_blocks;
+    let read_long = |pos: &mut usize| decode_avro_long(block, pos).ok_or_else(malformed);
+    let skip = |pos: &mut usize, len: usize| {
+        *pos = pos
+            .checked_add(len)
+            .filter(|end| *end <= block.len())
+            .ok_or_else(malformed)?;
+        Ok::<_, Error>(())
+    };
+    // Arrays and maps are encoded as blocks of items, each block starting with its item
+    // count, and a negative count is followed by the block size in bytes.
+    let read_item_count = |pos: &mut usize| {
+        let count = read_long(pos)?;
+        if count < 0 {
+            read_long(pos)?;
+        }
+        let count = count.unsigned_abs() as i64;
+        check_avro_length(count, block, *pos)?;
+        Ok::<_, Error>(count)
+    };
+    let skip_bytes = |pos: &mut usize| {
+        let len = read_long(pos)?;
+        check_avro_length(len, block, *pos)?;
+        skip(pos, usize::try_from(len).map_err(|_| malformed())?)
     };
 
-    static COLUMN_SIZES: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                108,
-                "column_sizes",
-                Type::Map(MapType {
-                    key_field: Arc::new(NestedField::required(
-                        117,
-                        "key",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                    value_field: Arc::new(NestedField::required(
-                        118,
-                        "value",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                }),
-            ))
-        })
-    };
+    match schema {
+        AvroSchema::Null => Ok(()),
+        AvroSchema::Boolean => skip(pos, 1),
+        AvroSchema::Int
+        | AvroSchema::Long
+        | AvroSchema::Enum(_)
+        | AvroSchema::Date
+        | AvroSchema::TimeMillis
+        | AvroSchema::TimeMicros
+        | AvroSchema::TimestampMillis
+        | AvroSchema::TimestampMicros
+        | AvroSchema::TimestampNanos
+        | AvroSchema::LocalTimestampMillis
+        | AvroSchema::LocalTimestampMicros
+        | AvroSchema::LocalTimestampNanos => read_long(pos).map(|_| ()),
+        AvroSchema::Float => skip(pos, 4),
+        AvroSchema::Double => skip(pos, 8),
+        AvroSchema::Duration => skip(pos, 12),
+        AvroSchema::Fixed(fixed) => skip(pos, fixed.size),
+        AvroSchema::Bytes | AvroSchema::String | AvroSchema::BigDecimal | AvroSchema::Uuid => {
+            skip_bytes(pos)
+        }
+        AvroSchema::Decimal(decimal) => {
+            skip_avro_value(&decimal.inner, names, namespace, block, pos)
+        }
+        AvroSchema::Array(array) => loop {
+            let count = read_item_count(pos)?;
+            if count == 0 {
+                return Ok(());
+            }
+            for _ in 0..count {
+                skip_avro_value(&array.items, names, namespace, block, pos)?;
+            }
+        },
+        AvroSchema::Map(map) => loop {
+            let count = read_item_count(pos)?;
+            if count == 0 {
+                return Ok(());
+            }
+            for _ in 0..count {
+                skip_bytes(pos)?;
+                skip_avro_value(&map.types, names, namespace, block, pos)?;
+            }
+        },
+        AvroSchema::Union(union) => {
+            let index = read_long(pos)?;
+            let variant = usize::try_from(index)
+                .ok()
+                .and_then(|index| union.variants().get(index))
+                .ok_or_else(malformed)?;
+            skip_avro_value(variant, names, namespace, block, pos)
+        }
+        AvroSchema::Record(record) => {
+            let namespace = record.name.fully_qualified_name(namespace).namespace;
+            for field in &record.fields {
+                skip_avro_value(&field.schema, names, &namespace, block, pos)?;
+            }
+            Ok(())
+        }
+        AvroSchema::Ref { name } => {
+            let name = name.fully_qualified_name(namespace);
+            let schema = names.get(&name).ok_or_else(malformed)?;
+            skip_avro_value(schema, names, &name.namespace, block, pos)
+        }
+    }
+}
 
-    static VALUE_COUNTS: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                109,
-                "value_counts",
-                Type::Map(MapType {
-                    key_field: Arc::new(NestedField::required(
-                        119,
-                        "key",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                    value_field: Arc::new(NestedField::required(
-                        120,
-                        "value",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                }),
-            ))
-        })
-    };
+/// A manifest entry whose column level statistics are decoded on demand.
+///
+/// The identity fields (status, snapshot id, sequence numbers, path, partition, ...)
+/// are decoded eagerly, while the column level statistics are kept in their raw avro
+/// form until [`LazyManifestEntry::decode_stats`] is called.
+///
+/// Entries are still fully read by the avro decoder, so this doesn't save any IO or
+/// avro decoding work. What's deferred is turning the stats into maps, most notably
+/// converting every lower and upper bound into a [`Datum`]. For manifests where most
+/// entries get pruned on their partition, this cuts CPU noticeably. The tradeoff is
+/// memory: until decoded, each entry holds the raw stats, and once decoded it holds
+/// both the raw stats and a full copy of the [`DataFile`].
+#[derive(Debug)]
+pub struct LazyManifestEntry {
+    /// The entry, with a data file carrying no column level statistics.
+    entry: ManifestEntry,
+    raw_stats: _serde::RawStats,
+    schema: SchemaRef,
+    decoded: OnceLock<DataFile>,
+}
 
-    static NULL_VALUE_COUNTS: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                110,
-                "null_value_counts",
-                Type::Map(MapType {
-                    key_field: Arc::new(NestedField::required(
-                        121,
-                        "key",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                    value_field: Arc::new(NestedField::required(
-                        122,
-                        "value",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                }),
-            ))
-        })
-    };
+impl LazyManifestEntry {
+    fn new(entry: ManifestEntry, raw_stats: _serde::RawStats, schema: SchemaRef) -> Self {
+        Self {
+            entry,
+            raw_stats,
+            schema,
+            decoded: OnceLock::new(),
+        }
+    }
 
-    static NAN_VALUE_COUNTS: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                137,
-                "nan_value_counts",
-                Type::Map(MapType {
-                    key_field: Arc::new(NestedField::required(
-                        138,
-                        "key",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                    value_field: Arc::new(NestedField::required(
-                        139,
-                        "value",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                }),
-            ))
-        })
-    };
+    /// Status of this manifest entry
+    pub fn status(&self) -> ManifestStatus {
+        self.entry.status
+    }
 
-    static LOWER_BOUNDS: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                125,
-                "lower_bounds",
-                Type::Map(MapType {
-                    key_field: Arc::new(NestedField::required(
-                        126,
-                        "key",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                    value_field: Arc::new(NestedField::required(
-                        127,
-                        "value",
-                        Type::Primitive(PrimitiveType::Binary),
-                    )),
-                }),
-            ))
-        })
-    };
+    /// Snapshot id
+    pub fn snapshot_id(&self) -> Option<i64> {
+        self.entry.snapshot_id
+    }
 
-    static UPPER_BOUNDS: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                128,
-                "upper_bounds",
-                Type::Map(MapType {
-                    key_field: Arc::new(NestedField::required(
-                        129,
-                        "key",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                    value_field: Arc::new(NestedField::required(
-                        130,
-                        "value",
-                        Type::Primitive(PrimitiveType::Binary),
-                    )),
-                }),
-            ))
-        })
-    };
+    /// Data sequence number.
+    pub fn sequence_number(&self) -> Option<i64> {
+        self.entry.sequence_number
+    }
 
-    static KEY_METADATA: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                131,
-                "key_metadata",
-                Type::Primitive(PrimitiveType::Binary),
-            ))
-        })
-    };
+    /// File sequence number, i.e. the sequence number of the snapshot that added the file.
+    pub fn file_sequence_number(&self) -> Option<i64> {
+        self.entry.file_sequence_number
+    }
 
-    static SPLIT_OFFSETS: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                132,
-                "split_offsets",
-                Type::List(ListType {
-                    element_field: Arc::new(NestedField::required(
-                        133,
-                        "element",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                }),
-            ))
-        })
-    };
+    /// The data file of this entry, without column level statistics.
+    pub fn data_file(&self) -> &DataFile {
+        &self.entry.data_file
+    }
 
-    static EQUALITY_IDS: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                135,
-                "equality_ids",
-                Type::List(ListType {
-                    element_field: Arc::new(NestedField::required(
-                        136,
-                        "element",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                }),
-            ))
-        })
-    };
+    /// The data file of this entry including its column level statistics, decoding
+    /// them on first access. Later calls return the cached result.
+    pub fn decode_stats(&self) -> Result<&DataFile> {
+        if let Some(data_file) = self.decoded.get() {
+            return Ok(data_file);
+        }
 
-    static SORT_ORDER_ID: Lazy<NestedFieldRef> = {
-        Lazy::new(|| {
-            Arc::new(NestedField::optional(
-                140,
-                "sort_order_id",
-                Type::Primitive(PrimitiveType::Int),
-            ))
-        })
-    };
+        let mut data_file = self.entry.data_file.clone();
+        self.raw_stats.decode_into(&mut data_file, &self.schema)?;
+        // Another thread may have won the race, both results are identical.
+        Ok(self.decoded.get_or_init(|| data_file))
+    }
 
-    fn data_file_fields_v2(partition_type: &StructType) -> Vec<NestedFieldRef> {
-        vec![
-            CONTENT.clone(),
-            FILE_PATH.clone(),
-            FILE_FORMAT.clone(),
-            Arc::new(NestedField::required(
-                102,
-                "partition",
-                Type::Struct(partition_type.clone()),
-            )),
-            RECORD_COUNT.clone(),
-            FILE_SIZE_IN_BYTES.clone(),
-            COLUMN_SIZES.clone(),
-            VALUE_COUNTS.clone(),
-            NULL_VALUE_COUNTS.clone(),
-            NAN_VALUE_COUNTS.clone(),
-            LOWER_BOUNDS.clone(),
-            UPPER_BOUNDS.clone(),
-            KEY_METADATA.clone(),
-            SPLIT_OFFSETS.clone(),
-            EQUALITY_IDS.clone(),
-            SORT_ORDER_ID.clone(),
-        ]
+    /// Convert into a [`ManifestEntry`] with column level statistics decoded.
+    pub fn into_entry(self) -> Result<ManifestEntry> {
+        let mut entry = self.entry;
+        match self.decoded.into_inner() {
+            Some(data_file) => entry.data_file = data_file,
+            None => self
+                .raw_stats
+                .decode_into(&mut entry.data_file, &self.schema)?,
+        }
+        Ok(entry)
     }
+}
 
-    pub(super) fn data_file_schema_v2(partition_type: &StructType) -> Result<AvroSchema, Error> {
-        let schema = Schema::builder()
-            .with_fields(data_file_fields_v2(partition_type))
-            .build()?;
-        schema_to_avro_schema("data_file", &schema)
+/// Names and field ids of the partition tuple fields, read from the `data_file.partition`
+/// record of the avro schema a manifest was written with.
+fn written_partition_fields(schema: &AvroSchema) -> Option<Vec<(String, Option<i32>)>> {
+    fn field_schema<'a>(schema: &'a AvroSchema, name: &str) -> Option<&'a AvroSchema> {
+        match schema {
+            AvroSchema::Record(record) => record
+                .fields
+                .iter()
+                .find(|field| field.name == name)
+                .map(|field| &field.schema),
+            _ => None,
+        }
     }
 
-    pub(super) fn manifest_schema_v2(partition_type: &StructType) -> Result<AvroSchema, Error> {
-        let fields = vec![
-            STATUS.clone(),
-            SNAPSHOT_ID_V2.clone(),
-            SEQUENCE_NUMBER.clone(),
-            FILE_SEQUENCE_NUMBER.clone(),
-            Arc::new(NestedField::required(
-                2,
-                "data_file",
-                Type::Struct(StructType::new(data_file_fields_v2(partition_type))),
-            )),
-        ];
-        let schema = Schema::builder().with_fields(fields).build()?;
-        schema_to_avro_schema("manifest_entry", &schema)
+    let data_file = field_schema(schema, "data_file")?;
+    match field_schema(data_file, "partition")? {
+        AvroSchema::Record(record) => Some(
+            record
+                .fields
+                .iter()
+                .map(|field| {
+                    let id = field
+                        .custom_attributes
+                        .get("field-id")
+                        .and_then(|id| id.as_i64())
+                        .and_then(|id| i32::try_from(id).ok());
+                    (field.name.clone(), id)
+                })
+                .collect(),
+        ),
+        _ => None,
     }
+}
 
-    fn data_file_fields_v1(partition_type: &StructType) -> Vec<NestedFieldRef> {
-        vec![
-            FILE_PATH.clone(),
-            FILE_FORMAT.clone(),
-            Arc::new(NestedField::required(
-                102,
-                "partition",
-                Type::Struct(partition_type.clone()),
-            )),
-            RECORD_COUNT.clone(),
-            FILE_SIZE_IN_BYTES.clone(),
-            BLOCK_SIZE_IN_BYTES.clone(),
-            COLUMN_SIZES.clone(),
-            VALUE_COUNTS.clone(),
-            NULL_VALUE_COUNTS.clone(),
-            NAN_VALUE_COUNTS.clone(),
-            LOWER_BOUNDS.clone(),
-            UPPER_BOUNDS.clone(),
-            KEY_METADATA.clone(),
-            SPLIT_OFFSETS.clone(),
-            SORT_ORDER_ID.clone(),
-        ]
+const AVRO_MAGIC: &[u8] = b"Obj\x01";
+
+/// Check that `bs` looks like a complete avro object container file, to fail early with a
+/// clear error on the common case of a partially uploaded manifest.
+///
+/// The file must start with the avro magic and end with the sync marker recorded in its
+/// header. A file truncated exactly at a block boundary can't be detected this way.
+fn check_avro_container(bs: &[u8]) -> Result<()> {
+    let truncated = || Error::new(ErrorKind::DataInvalid, "Manifest file appears truncated");
+    if !bs.starts_with(AVRO_MAGIC) {
+        if AVRO_MAGIC.starts_with(bs) {
+            return Err(truncated());
+        }
+        return Err(Error::new(
+            ErrorKind::DataInvalid,
+            "Manifest file is not an avro file, the magic header is missing",
+        ));
     }
-
-    pub(super) fn data_file_schema_v1(partition_type: &StructType) -> Result<AvroSchema, Error> {
-        let schema = Schema::builder()
-            .with_fields(data_file_fields_v1(partition_type))
-            .build()?;
-        schema_to_avro_schema("data_file", &schema)
+    let sync_marker = avro_sync_marker(bs).ok_or_else(truncated)?;
+    if !bs.ends_with(sync_marker) {
+        return Err(truncated());
+    }
+    // The avro reader fails on codecs it wasn't compiled with, with an error that doesn't
+    // tell it's the codec.
+    if let Some(codec) = avro_header_metadata(bs, b"avro.codec") {
+        let codec = String::from_utf8_lossy(codec);
+        if apache_avro::Codec::from_str(&codec).is_err() {
+            return Err(Error::new(
+                ErrorKind::FeatureUnsupported,
+                format!("unsupported avro codec: {codec}"),
+            ));
+        }
     }
+    Ok(())
+}
 
-    pub(super) fn manifest_schema_v1(partition_type: &StructType) -> Result<AvroSchema, Error> {
-        let fields = vec![
-            STATUS.clone(),
-            SNAPSHOT_ID_V1.clone(),
-            Arc::new(NestedField::required(
-                2,
-                "data_file",
-                Type::Struct(StructType::new(data_file_fields_v1(partition_type))),
-            )),
-        ];
-        let schema = Schema::builder().with_fields(fields).build()?;
-        schema_to_avro_schema("manifest_entry", &schema)
+/// The sync marker ending the header of an avro object container file, or `None` if the
+/// header is incomplete.
+fn avro_sync_marker(bs: &[u8]) -> Option<&[u8]> {
+    let header_len = avro_header_len(bs)?;
+    bs.get(header_len - 16..header_len)
+}
+
+/// The length of the avro object container file at the start of `bs`, which may be
+/// followed by other data, or `None` if it's not a complete container file.
+///
+/// The end is found by walking the data blocks, each made of an object count, a size in
+/// bytes, the objects and the sync marker, until the end of `bs` or the magic of another
+/// container file.
+fn avro_container_len(bs: &[u8]) -> Option<usize> {
+    if !bs.starts_with(AVRO_MAGIC) {
+        return None;
+    }
+    let mut pos = avro_header_len(bs)?;
+    let sync_marker = &bs[pos - 16..pos];
+    while pos < bs.len() && !bs[pos..].starts_with(AVRO_MAGIC) {
+        decode_avro_long(bs, &mut pos)?;
+        let size = usize::try_from(decode_avro_long(bs, &mut pos)?).ok()?;
+        pos = pos.checked_add(size)?;
+        if bs.get(pos..pos.checked_add(16)?)? != sync_marker {
+            return None;
+        }
+        pos += 16;
     }
+    Some(pos)
 }
 
-/// Meta data of a manifest that is stored in the key-value metadata of the Avro file
-#[derive(Debug, PartialEq, Clone, Eq, TypedBuilder)]
-pub struct ManifestMetadata {
-    /// The table schema at the time the manifest
-    /// was written
+/// The length of the header of an avro object container file, ending with its sync
+/// marker, or `None` if the header is incomplete.
+fn avro_header_len(bs: &[u8]) -> Option<usize> {
+    avro_header(bs).map(|(header_len, _)| header_len)
+}
+
+/// The value of `key` in the metadata of the header of an avro object container file, or
+/// `None` if it's missing or the header is incomplete.
+fn avro_header_metadata<'a>(bs: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    let (_, metadata) = avro_header(bs)?;
+    metadata
+        .into_iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+/// The builder used to create a [`ManifestWriter`].
+pub struct ManifestWriterBuilder {
+    output: Option<OutputFile>,
+    manifest_path: String,
+    snapshot_id: Option<i64>,
+    key_metadata: Vec<u8>,
     schema: SchemaRef,
-    /// ID of the schema used to write the manifest as a string
-    schema_id: SchemaId,
-    /// The partition spec used to write the manifest
     partition_spec: PartitionSpec,
-    /// Table format version number of the manifest as a string
-    format_version: FormatVersion,
-    /// Type of content files tracked by the manifest: “data” or “deletes”
-    content: ManifestContentType,
+    min_entries: Option<usize>,
+    partition_summaries: bool,
+    default_metrics_mode: MetricsMode,
+    column_metrics_modes: HashMap<i32, MetricsMode>,
+    sequence_number: Option<i64>,
+    user_metadata: HashMap<String, Vec<u8>>,
+    schema_fingerprint: bool,
+    strict_sequence_numbers: bool,
+    known_sort_orders: Option<HashSet<i32>>,
+    path_check: bool,
+    encryption_manager: Option<Arc<dyn EncryptionManager>>,
+    sort_equality_ids: bool,
+    bounds_byte_budget: Option<usize>,
 }
 
-impl ManifestMetadata {
-    /// Parse from metadata in avro file.
-    pub fn parse(meta: &HashMap<String, Vec<u8>>) -> Result<Self> {
-        let schema = Arc::new({
-            let bs = meta.get("schema").ok_or_else(|| {
-                Error::new(
-                    ErrorKind::DataInvalid,
-                    "schema is required in manifest metadata but not found",
-                )
-            })?;
-            serde_json::from_slice::<Schema>(bs).map_err(|err| {
-                Error::new(
-                    ErrorKind::DataInvalid,
-                    "Fail to parse schema in manifest metadata",
-                )
-                .with_source(err)
-            })?
-        });
-        let schema_id: i32 = meta
-            .get("schema-id")
-            .map(|bs| {
-                String::from_utf8_lossy(bs).parse().map_err(|err| {
-                    Error::new(
-                        ErrorKind::DataInvalid,
-                        "Fail to parse schema id in manifest metadata",
-                    )
-                    .with_source(err)
-                })
-            })
-            .transpose()?
-            .unwrap_or(0);
-        let partition_spec = {
-            let fields = {
-                let bs = meta.get("partition-spec").ok_or_else(|| {
-                    Error::new(
-                        ErrorKind::DataInvalid,
-                        "partition-spec is required in manifest metadata but not found",
-                    )
-                })?;
-                serde_json::from_slice::<Vec<PartitionField>>(bs).map_err(|err| {
-                    Error::new(
-                        ErrorKind::DataInvalid,
-                        "Fail to parse partition spec in manifest metadata",
-                    )
-                    .with_source(err)
-                })?
-            };
-            let spec_id = meta
-                .get("partition-spec-id")
-                .map(|bs| {
-                    String::from_utf8_lossy(bs).parse().map_err(|err| {
-                        Error::new(
-                            ErrorKind::DataInvalid,
-                            "Fail to parse partition spec id in manifest metadata",
-                        )
-                        .with_source(err)
-                    })
-                })
-                .transpose()?
-                .unwrap_or(0);
-            PartitionSpec::builder(schema.clone())
-                .with_spec_id(spec_id)
-                .add_unbound_fields(fields.into_iter().map(|f| f.into_unbound()))?
-                .build()?
-        };
-        let format_version = if let Some(bs) = meta.get("format-version") {
-            serde_json::from_slice::<FormatVersion>(bs).map_err(|err| {
-                Error::new(
-                    ErrorKind::DataInvalid,
-                    "Fail to parse format version in manifest metadata",
-                )
-                .with_source(err)
-            })?
-        } else {
-            FormatVersion::V1
-        };
-        let content = if let Some(v) = meta.get("content") {
-            let v = String::from_utf8_lossy(v);
-            v.parse()?
-        } else {
-            ManifestContentType::Data
-        };
-        Ok(ManifestMetadata {
+impl ManifestWriterBuilder {
+    /// Create a new builder.
+    ///
+    /// `snapshot_id` is the id of the snapshot the manifest is written for. When it's `None`,
+    /// added entries are written without a snapshot id and inherit it from the manifest list
+    /// entry at read time, see [`ManifestWriter::snapshot_id`].
+    pub fn new(
+        output: OutputFile,
+        snapshot_id: Option<i64>,
+        key_metadata: Vec<u8>,
+        schema: SchemaRef,
+        partition_spec: PartitionSpec,
+    ) -> Self {
+        let manifest_path = output.location().to_string();
+        Self::from_output(
+            Some(output),
+            manifest_path,
+            snapshot_id,
+            key_metadata,
             schema,
-            schema_id,
             partition_spec,
-            format_version,
-            content,
-        })
+        )
     }
 
-    /// Get the schema of table at the time manifest was written
-    pub fn schema(&self) -> &SchemaRef {
-        &self.schema
+    /// Create a new builder for a manifest encoded in memory with
+    /// [`ManifestWriter::write_to_bytes`], without an output file.
+    ///
+    /// `manifest_path` is only used as the `manifest_path` of the returned
+    /// [`ManifestFile`], e.g. where the caller stores the bytes or a placeholder. The
+    /// other arguments are as in [`ManifestWriterBuilder::new`].
+    pub fn new_in_memory(
+        manifest_path: impl Into<String>,
+        snapshot_id: Option<i64>,
+        key_metadata: Vec<u8>,
+        schema: SchemaRef,
+        partition_spec: PartitionSpec,
+    ) -> Self {
+        Self::from_output(
+            None,
+            manifest_path.into(),
+            snapshot_id,
+            key_metadata,
+            schema,
+            partition_spec,
+        )
     }
 
-    /// Get the ID of schema used to write the manifest
-    pub fn schema_id(&self) -> SchemaId {
-        self.schema_id
+    fn from_output(
+        output: Option<OutputFile>,
+        manifest_path: String,
+        snapshot_id: Option<i64>,
+        key_metadata: Vec<u8>,
+        schema: SchemaRef,
+        partition_spec: PartitionSpec,
+    ) -> Self {
+        Self {
+            output,
+            manifest_path,
+            snapshot_id,
+            key_metadata,
+            schema,
+            partition_spec,
+            min_entries: None,
+            partition_summaries: true,
+            default_metrics_mode: MetricsMode::default(),
+            column_metrics_modes: HashMap::new(),
+            sequence_number: None,
+            user_metadata: HashMap::new(),
+            schema_fingerprint: false,
+            strict_sequence_numbers: false,
+            known_sort_orders: None,
+            path_check: false,
+            encryption_manager: None,
+            sort_equality_ids: false,
+            bounds_byte_budget: None,
+        }
     }
 
-    /// Get the partition spec used to write manifest
-    pub fn partition_spec(&self) -> &PartitionSpec {
-        &self.partition_spec
+    /// Set the minimum number of entries the manifest should contain, similar to
+    /// `write.manifest.min-count-to-merge`.
+    ///
+    /// This is advisory: when fewer entries were added, [`ManifestWriter::is_below_min_entries`]
+    /// returns true and [`ManifestWriter::write_manifest_file`] fails without writing anything,
+    /// so that a higher level merger can combine the entries with another manifest instead.
+    pub fn with_min_entries(mut self, min_entries: usize) -> Self {
+        self.min_entries = Some(min_entries);
+        self
     }
 
-    /// Get the table format version
-    pub fn format_version(&self) -> &FormatVersion {
-        &self.format_version
+    /// Set whether to compute the partition field summaries of the written [`ManifestFile`].
+    /// Enabled by default.
+    ///
+    /// Computing the summaries can be costly for huge manifests, e.g. delete manifests, where
+    /// they are less useful. When disabled, the returned [`ManifestFile`] has no partition field
+    /// summaries at all, which readers treat as "may contain any partition", so the manifest is
+    /// never pruned by partition filters.
+    pub fn with_partition_summaries(mut self, enabled: bool) -> Self {
+        self.partition_summaries = enabled;
+        self
     }
 
-    /// Get the type of content files tracked by manifest
-    pub fn content(&self) -> &ManifestContentType {
-        &self.content
+    /// Set the [`MetricsMode`] of columns without a mode set by
+    /// [`ManifestWriterBuilder::with_column_metrics_mode`], similar to
+    /// `write.metadata.metrics.default`. Defaults to [`MetricsMode::Full`].
+    pub fn with_default_metrics_mode(mut self, mode: MetricsMode) -> Self {
+        self.default_metrics_mode = mode;
+        self
     }
-}
 
-/// Reference to [`ManifestEntry`].
-pub type ManifestEntryRef = Arc<ManifestEntry>;
+    /// Set the [`MetricsMode`] of the column with the given field id, similar to
+    /// `write.metadata.metrics.column.<col>`.
+    pub fn with_column_metrics_mode(mut self, field_id: i32, mode: MetricsMode) -> Self {
+        self.column_metrics_modes.insert(field_id, mode);
+        self
+    }
 
-/// A manifest is an immutable Avro file that lists data files or delete
-/// files, along with each file’s partition data tuple, metrics, and tracking
-/// information.
-#[derive(Debug, PartialEq, Eq, Clone, TypedBuilder)]
-pub struct ManifestEntry {
-    /// field: 0
-    ///
-    /// Used to track additions and deletions.
-    status: ManifestStatus,
-    /// field id: 1
-    ///
-    /// Snapshot id where the file was added, or deleted if status is 2.
-    /// Inherited when null.
-    #[builder(default, setter(strip_option(fallback = snapshot_id_opt)))]
-    snapshot_id: Option<i64>,
-    /// field id: 3
+    /// Cap the total size of the serialized lower and upper bounds of each data file at
+    /// `budget` bytes. The bounds of the columns with the largest bounds are dropped until
+    /// the rest fit, see [`ManifestWriter::bounds_dropped_by_budget`]. Other metrics are
+    /// kept. By default bounds aren't capped.
+    pub fn with_bounds_byte_budget(mut self, budget: Option<usize>) -> Self {
+        self.bounds_byte_budget = budget;
+        self
+    }
+
+    /// Set the sequence number of the returned [`ManifestFile`], for manifests that are
+    /// assembled outside of a snapshot commit and whose sequence number is already known.
     ///
-    /// Data sequence number of the file.
-    /// Inherited when null and status is 1 (added).
-    #[builder(default, setter(strip_option(fallback = sequence_number_opt)))]
-    sequence_number: Option<i64>,
-    /// field id: 4
+    /// By default it's [`UNASSIGNED_SEQUENCE_NUMBER`], which the manifest list writer
+    /// replaces with the sequence number of the committed snapshot. The override is also
+    /// used as the minimum sequence number when no entry has a sequence number.
+    pub fn with_sequence_number(mut self, sequence_number: i64) -> Self {
+        self.sequence_number = Some(sequence_number);
+        self
+    }
+
+    /// Add custom key-value metadata to the written avro file, e.g. lineage annotations.
+    /// They can be read back with [`ManifestMetadata::extra_metadata`].
     ///
-    /// File sequence number indicating when the file was added.
-    /// Inherited when null and status is 1 (added).
-    #[builder(default, setter(strip_option(fallback = file_sequence_number_opt)))]
-    file_sequence_number: Option<i64>,
-    /// field id: 2
+    /// Fails if a key is reserved, either by the spec (like `schema` or `format-version`)
+    /// or by avro (keys starting with `avro.`).
+    pub fn with_user_metadata_map(mut self, metadata: HashMap<String, Vec<u8>>) -> Result<Self> {
+        if let Some(key) = metadata
+            .keys()
+            .find(|key| RESERVED_METADATA_KEYS.contains(&key.as_str()) || key.starts_with("avro."))
+        {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!("Manifest metadata key {key} is reserved and can't be overwritten"),
+            ));
+        }
+        self.user_metadata.extend(metadata);
+        Ok(self)
+    }
+
+    /// Stamp the Rabin fingerprint of the avro schema of the manifest entries into the
+    /// manifest metadata, under [`MANIFEST_SCHEMA_FINGERPRINT_KEY`]. Off by default.
     ///
-    /// File path, partition tuple, metrics, …
-    data_file: DataFile,
-}
+    /// The fingerprint is the hex encoded CRC-64-AVRO of the schema's parsing canonical
+    /// form, so readers can e.g. cache decoders by it.
+    pub fn with_schema_fingerprint(mut self, schema_fingerprint: bool) -> Self {
+        self.schema_fingerprint = schema_fingerprint;
+        self
+    }
 
-impl ManifestEntry {
-    /// Check if this manifest entry is deleted.
-    pub fn is_alive(&self) -> bool {
-        matches!(
-            self.status,
-            ManifestStatus::Added | ManifestStatus::Existing
-        )
+    /// Fail to add files with a negative sequence number, which is likely a bug of the
+    /// caller. Off by default, in which case a negative sequence number is treated as
+    /// unassigned, see [`ManifestWriter::add_file`].
+    pub fn with_strict_sequence_numbers(mut self, strict_sequence_numbers: bool) -> Self {
+        self.strict_sequence_numbers = strict_sequence_numbers;
+        self
     }
 
-    /// Status of this manifest entry
-    pub fn status(&self) -> ManifestStatus {
-        self.status
+    /// Set the ids of the sort orders of the table, e.g. from the table metadata, to reject
+    /// data files whose `sort_order_id` isn't one of them. Data files without a sort order
+    /// id are always accepted. By default sort order ids aren't checked.
+    pub fn with_known_sort_orders(mut self, sort_order_ids: HashSet<i32>) -> Self {
+        self.known_sort_orders = Some(sort_order_ids);
+        self
     }
 
-    /// Content type of this manifest entry.
-    #[inline]
-    pub fn content_type(&self) -> DataContentType {
-        self.data_file.content
+    /// Reject data files whose `file_path` is empty, or neither a URI with a scheme, like
+    /// `s3://bucket/file.parquet`, nor an absolute path. Off by default since some test
+    /// and local setups use bare paths.
+    pub fn with_path_check(mut self, path_check: bool) -> Self {
+        self.path_check = path_check;
+        self
     }
 
-    /// File format of this manifest entry.
-    #[inline]
-    pub fn file_format(&self) -> DataFileFormat {
-        self.data_file.file_format
+    /// Encrypt the manifest with `encryption_manager` in
+    /// [`ManifestWriter::write_manifest_file`], storing the key metadata it produces in
+    /// the returned [`ManifestFile`]. Manifests are written unencrypted by default.
+    pub fn with_encryption_manager(
+        mut self,
+        encryption_manager: Option<Arc<dyn EncryptionManager>>,
+    ) -> Self {
+        self.encryption_manager = encryption_manager;
+        self
     }
 
-    /// Data file path of this manifest entry.
-    #[inline]
-    pub fn file_path(&self) -> &str {
-        &self.data_file.file_path
+    /// Sort the `equality_ids` of added delete files ascending, so manifests of different
+    /// producers list them in the same order. Off by default since callers may rely on
+    /// the order they wrote.
+    pub fn with_sort_equality_ids(mut self, sort_equality_ids: bool) -> Self {
+        self.sort_equality_ids = sort_equality_ids;
+        self
     }
 
-    /// Data file record count of the manifest entry.
-    #[inline]
-    pub fn record_count(&self) -> u64 {
-        self.data_file.record_count
+    /// Build a [`ManifestWriter`] for format version 1.
+    pub fn build_v1(self) -> ManifestWriter {
+        self.build(FormatVersion::V1, ManifestContentType::Data)
     }
 
-    /// Inherit data from manifest list, such as snapshot id, sequence number.
-    pub(crate) fn inherit_data(&mut self, snapshot_entry: &ManifestFile) {
-        if self.snapshot_id.is_none() {
-            self.snapshot_id = Some(snapshot_entry.added_snapshot_id);
+    /// Build a [`ManifestWriter`] for format version 2, data content.
+    pub fn build_v2_data(self) -> ManifestWriter {
+        self.build(FormatVersion::V2, ManifestContentType::Data)
+    }
+
+    /// Build a [`ManifestWriter`] for format version 2, deletes content.
+    pub fn build_v2_deletes(self) -> ManifestWriter {
+        self.build(FormatVersion::V2, ManifestContentType::Deletes)
+    }
+
+    fn build(self, format_version: FormatVersion, content: ManifestContentType) -> ManifestWriter {
+        let metadata = ManifestMetadata::builder()
+            .schema_id(self.schema.schema_id())
+            .schema(self.schema)
+            .partition_spec(self.partition_spec)
+            .format_version(format_version)
+            .content(content)
+            .extra_metadata(self.user_metadata)
+            .build();
+        let mut writer = ManifestWriter::new(
+            self.output,
+            self.manifest_path,
+            self.snapshot_id,
+            self.key_metadata,
+            metadata,
+        );
+        writer.min_entries = self.min_entries;
+        writer.partition_summaries = self.partition_summaries;
+        writer.default_metrics_mode = self.default_metrics_mode;
+        writer.column_metrics_modes = self.column_metrics_modes;
+        writer.sequence_number = self.sequence_number;
+        writer.schema_fingerprint = self.schema_fingerprint;
+        writer.strict_sequence_numbers = self.strict_sequence_numbers;
+        writer.known_sort_orders = self.known_sort_orders;
+        writer.path_check = self.path_check;
+        writer.encryption_manager = self.encryption_manager;
+        writer.sort_equality_ids = self.sort_equality_ids;
+        writer.bounds_byte_budget = self.bounds_byte_budget;
+        writer
+    }
+}
+
+/// Which column metrics of data files are kept by a [`ManifestWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetricsMode {
+    /// Drop all metrics of the column.
+    None,
+    /// Keep the column size, value count and null value count, but drop the nan value
+    /// count and the lower and upper bounds.
+    Counts,
+    /// Keep all metrics of the column.
+    #[default]
+    Full,
+}
+
+/// A manifest writer.
+pub struct ManifestWriter {
+    output: Option<OutputFile>,
+    manifest_path: String,
+
+    snapshot_id: Option<i64>,
+
+    added_files: u32,
+    added_rows: u64,
+    existing_files: u32,
+    existing_rows: u64,
+    deleted_files: u32,
+    deleted_rows: u64,
+
+    min_seq_num: Option<i64>,
+
+    key_metadata: Vec<u8>,
+
+    manifest_entries: Vec<ManifestEntry>,
+
+    metadata: ManifestMetadata,
+
+    min_entries: Option<usize>,
+
+    partition_summaries: bool,
+    // Updated as entries are added, initialized on the first entry.
+    partition_field_stats: Option<Vec<PartitionFieldStats>>,
+
+    default_metrics_mode: MetricsMode,
+    column_metrics_modes: HashMap<i32, MetricsMode>,
+
+    sequence_number: Option<i64>,
+
+    schema_fingerprint: bool,
+
+    strict_sequence_numbers: bool,
+
+    known_sort_orders: Option<HashSet<i32>>,
+
+    path_check: bool,
+
+    encryption_manager: Option<Arc<dyn EncryptionManager>>,
+
+    sort_equality_ids: bool,
+
+    bounds_byte_budget: Option<usize>,
+    // The file path and the field ids of the columns whose bounds were dropped to fit
+    // `bounds_byte_budget`, for entries of any status.
+    bounds_dropped_by_budget: Vec<(String, Vec<i32>)>,
+}
+
+/// Row counts of the entries added to a [`ManifestWriter`] so far, by entry status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RowCounts {
+    /// Rows in entries with status [`ManifestStatus::Added`].
+    pub added: u64,
+    /// Rows in entries with status [`ManifestStatus::Existing`].
+    pub existing: u64,
+    /// Rows in entries with status [`ManifestStatus::Deleted`].
+    pub deleted: u64,
+}
+
+struct PartitionFieldStats {
+    partition_type: PrimitiveType,
+    summary: FieldSummary,
+}
+
+impl PartitionFieldStats {
+    pub(crate) fn new(partition_type: PrimitiveType) -> Self {
+        Self {
+            partition_type,
+            summary: FieldSummary::default(),
         }
+    }
 
-        if self.sequence_number.is_none()
-            && (self.status == ManifestStatus::Added
-                || snapshot_entry.sequence_number == INITIAL_SEQUENCE_NUMBER)
-        {
-            self.sequence_number = Some(snapshot_entry.sequence_number);
+    /// Fail when `value` can't update the summary, i.e. isn't compatible with the type of
+    /// the partition field.
+    fn check(&self, value: Option<&PrimitiveLiteral>) -> Result<()> {
+        if value.is_some_and(|value| !self.partition_type.compatible(value)) {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                "value is not compatitable with type",
+            ));
         }
+        Ok(())
+    }
 
-        if self.file_sequence_number.is_none()
-            && (self.status == ManifestStatus::Added
-                || snapshot_entry.sequence_number == INITIAL_SEQUENCE_NUMBER)
-        {
-            self.file_sequence_number = Some(snapshot_entry.sequence_number);
+    pub(crate) fn update(&mut self, value: Option<PrimitiveLiteral>) -> Result<()> {
+        self.check(value.as_ref())?;
+        let Some(value) = value else {
+            self.summary.contains_null = true;
+            return Ok(());
+        };
+        let value = Datum::new(self.partition_type.clone(), value);
+
+        if value.is_nan() {
+            self.summary.contains_nan = Some(true);
+            return Ok(());
+        }
+
+        self.summary.lower_bound = Some(self.summary.lower_bound.take().map_or(
+            value.clone(),
+            |original| {
+                if value < original {
+                    value.clone()
+                } else {
+                    original
+                }
+            },
+        ));
+        self.summary.upper_bound = Some(self.summary.upper_bound.take().map_or(
+            value.clone(),
+            |original| {
+                if value > original {
+                    value
+                } else {
+                    original
+                }
+            },
+        ));
+
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> FieldSummary {
+        // Always set contains_nan
+        self.summary.contains_nan = self.summary.contains_nan.or(Some(false));
+        self.summary
+    }
+}
+
+/// Update `field_stats` with the partition of `data_file`, failing instead of panicking
+/// when the partition doesn't have one value per field.
+fn update_partition_field_stats(
+    field_stats: &mut [PartitionFieldStats],
+    data_file: &DataFile,
+) -> Result<()> {
+    let partition = &data_file.partition;
+    if partition.iter().len() != field_stats.len() {
+        return Err(Error::new(
+            ErrorKind::DataInvalid,
+            format!(
+                "Partition of data file {} has {} fields, expected {}",
+                data_file.file_path,
+                partition.iter().len(),
+                field_stats.len()
+            ),
+        ));
+    }
+    let values = partition
+        .iter()
+        .map(|literal| literal.map(|v| v.as_primitive_literal().unwrap()))
+        .collect::<Vec<_>>();
+    // Check every value before updating any summary, so that a rejected data file leaves
+    // them all untouched.
+    for (value, stat) in values.iter().zip_eq(field_stats.iter()) {
+        stat.check(value.as_ref())?;
+    }
+    for (value, stat) in values.into_iter().zip_eq(field_stats.iter_mut()) {
+        stat.update(value)?;
+    }
+    Ok(())
+}
+
+impl ManifestWriter {
+    /// Create a new manifest writer.
+    pub(crate) fn new(
+        output: Option<OutputFile>,
+        manifest_path: String,
+        snapshot_id: Option<i64>,
+        key_metadata: Vec<u8>,
+        metadata: ManifestMetadata,
+    ) -> Self {
+        Self {
+            output,
+            manifest_path,
+            snapshot_id,
+            added_files: 0,
+            added_rows: 0,
+            existing_files: 0,
+            existing_rows: 0,
+            deleted_files: 0,
+            deleted_rows: 0,
+            min_seq_num: None,
+            key_metadata,
+            manifest_entries: Vec::new(),
+            metadata,
+            min_entries: None,
+            partition_summaries: true,
+            partition_field_stats: None,
+            default_metrics_mode: MetricsMode::default(),
+            column_metrics_modes: HashMap::new(),
+            sequence_number: None,
+            schema_fingerprint: false,
+            strict_sequence_numbers: false,
+            known_sort_orders: None,
+            path_check: false,
+            encryption_manager: None,
+            sort_equality_ids: false,
+            bounds_byte_budget: None,
+            bounds_dropped_by_budget: vec![],
         }
     }
 
-    /// Snapshot id
-    #[inline]
+    /// The snapshot id assigned to added and deleted entries.
+    ///
+    /// `None` means the snapshot id is inherited at commit: entries are written with a null
+    /// snapshot id, and readers fill it with the `added_snapshot_id` of the manifest list entry
+    /// that references this manifest. Only format version 2 supports this, v1 manifests always
+    /// store the snapshot id.
     pub fn snapshot_id(&self) -> Option<i64> {
         self.snapshot_id
     }
 
-    /// Data sequence number.
-    #[inline]
-    pub fn sequence_number(&self) -> Option<i64> {
-        self.sequence_number
+    /// Returns true if a minimum entry count was configured with
+    /// [`ManifestWriterBuilder::with_min_entries`] and fewer entries have been added so far.
+    pub fn is_below_min_entries(&self) -> bool {
+        self.min_entries
+            .is_some_and(|min_entries| self.manifest_entries.len() < min_entries)
     }
 
-    /// File size in bytes.
-    #[inline]
-    pub fn file_size_in_bytes(&self) -> u64 {
-        self.data_file.file_size_in_bytes
+    /// Row counts of the entries added so far, the same values as the `*_rows_count`
+    /// fields of the [`ManifestFile`] produced when writing.
+    pub fn row_counts(&self) -> RowCounts {
+        RowCounts {
+            added: self.added_rows,
+            existing: self.existing_rows,
+            deleted: self.deleted_rows,
+        }
     }
 
-    /// get a reference to the actual data file
-    #[inline]
-    pub fn data_file(&self) -> &DataFile {
-        &self.data_file
+    /// The file path and the field ids of the columns whose bounds were dropped from the
+    /// data file of each entry added so far, whatever its status, to fit the budget set with
+    /// [`ManifestWriterBuilder::with_bounds_byte_budget`], in the order the entries were
+    /// added. Files that fit the budget are absent.
+    pub fn bounds_dropped_by_budget(&self) -> &[(String, Vec<i32>)] {
+        &self.bounds_dropped_by_budget
     }
-}
 
-/// Used to track additions and deletions in ManifestEntry.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum ManifestStatus {
-    /// Value: 0
-    Existing = 0,
-    /// Value: 1
-    Added = 1,
-    /// Value: 2
-    ///
-    /// Deletes are informational only and not used in scans.
-    Deleted = 2,
-}
+    fn new_partition_field_stats(partition_type: &StructType) -> Vec<PartitionFieldStats> {
+        partition_type
+            .fields()
+            .iter()
+            .map(|f| PartitionFieldStats::new(f.field_type.as_primitive_type().unwrap().clone()))
+            .collect()
+    }
 
-impl TryFrom<i32> for ManifestStatus {
-    type Error = Error;
+    fn update_partition_summaries(&mut self, data_file: &DataFile) -> Result<()> {
+        if !self.partition_summaries {
+            return Ok(());
+        }
+        if self.partition_field_stats.is_none() {
+            let partition_type = self
+                .metadata
+                .partition_spec
+                .partition_type(&self.metadata.schema)?;
+            self.partition_field_stats = Some(Self::new_partition_field_stats(&partition_type));
+        }
+        if let Some(field_stats) = &mut self.partition_field_stats {
+            update_partition_field_stats(field_stats, data_file)?;
+        }
+        Ok(())
+    }
 
-    fn try_from(v: i32) -> Result<ManifestStatus> {
-        match v {
-            0 => Ok(ManifestStatus::Existing),
-            1 => Ok(ManifestStatus::Added),
-            2 => Ok(ManifestStatus::Deleted),
-            _ => Err(Error::new(
-                ErrorKind::DataInvalid,
-                format!("manifest status {} is invalid", v),
-            )),
+    fn construct_partition_summaries(&mut self, partition_type: &StructType) -> Vec<FieldSummary> {
+        self.partition_field_stats
+            .take()
+            .unwrap_or_else(|| Self::new_partition_field_stats(partition_type))
+            .into_iter()
+            .map(|stat| stat.finish())
+            .collect()
+    }
+
+    fn apply_bounds_byte_budget(&mut self, data_file: &mut DataFile) {
+        let Some(budget) = self.bounds_byte_budget else {
+            return;
+        };
+        let bound_len = |datum: Option<&Datum>| {
+            datum
+                .and_then(|datum| datum.serialized_len().ok())
+                .unwrap_or(0)
+        };
+        let mut columns = data_file
+            .lower_bounds
+            .keys()
+            .chain(data_file.upper_bounds.keys())
+            .copied()
+            .sorted()
+            .dedup()
+            .map(|field_id| {
+                let len = bound_len(data_file.lower_bounds.get(&field_id))
+                    + bound_len(data_file.upper_bounds.get(&field_id));
+                (field_id, len)
+            })
+            .collect::<Vec<_>>();
+        let mut total: usize = columns.iter().map(|(_, len)| len).sum();
+        if total <= budget {
+            return;
+        }
+        // Drop the largest bounds first, ties in field id order.
+        columns.sort_by_key(|(field_id, len)| (std::cmp::Reverse(*len), *field_id));
+        let mut dropped = vec![];
+        for (field_id, len) in columns {
+            if total <= budget {
+                break;
+            }
+            data_file.lower_bounds.remove(&field_id);
+            data_file.upper_bounds.remove(&field_id);
+            total -= len;
+            dropped.push(field_id);
         }
+        self.bounds_dropped_by_budget
+            .push((data_file.file_path.clone(), dropped));
     }
-}
 
-/// Data file carries data file path, partition tuple, metrics, …
-#[derive(Debug, PartialEq, Clone, Eq, Builder)]
-pub struct DataFile {
-    /// field id: 134
-    ///
-    /// Type of content stored by the data file: data, equality deletes,
-    /// or position deletes (all v1 files are data files)
-    pub(crate) content: DataContentType,
-    /// field id: 100
-    ///
-    /// Full URI for the file with FS scheme
-    pub(crate) file_path: String,
-    /// field id: 101
-    ///
-    /// String file format name, avro, orc or parquet
-    pub(crate) file_format: DataFileFormat,
-    /// field id: 102
-    ///
-    /// Partition data tuple, schema based on the partition spec output using
-    /// partition field ids for the struct field ids
-    pub(crate) partition: Struct,
-    /// field id: 103
-    ///
-    /// Number of records in this file
-    pub(crate) record_count: u64,
-    /// field id: 104
-    ///
-    /// Total file size in bytes
-    pub(crate) file_size_in_bytes: u64,
-    /// field id: 108
-    /// key field id: 117
-    /// value field id: 118
-    ///
-    /// Map from column id to the total size on disk of all regions that
-    /// store the column. Does not include bytes necessary to read other
-    /// columns, like footers. Leave null for row-oriented formats (Avro)
-    #[builder(default)]
-    pub(crate) column_sizes: HashMap<i32, u64>,
-    /// field id: 109
-    /// key field id: 119
-    /// value field id: 120
-    ///
-    /// Map from column id to number of values in the column (including null
-    /// and NaN values)
-    #[builder(default)]
-    pub(crate) value_counts: HashMap<i32, u64>,
-    /// field id: 110
-    /// key field id: 121
-    /// value field id: 122
-    ///
-    /// Map from column id to number of null values in the column
-    #[builder(default)]
-    pub(crate) null_value_counts: HashMap<i32, u64>,
-    /// field id: 137
-    /// key field id: 138
-    /// value field id: 139
-    ///
-    /// Map from column id to number of NaN values in the column
-    #[builder(default)]
-    pub(crate) nan_value_counts: HashMap<i32, u64>,
-    /// field id: 125
-    /// key field id: 126
-    /// value field id: 127
-    ///
-    /// Map from column id to lower bound in the column serialized as binary.
-    /// Each value must be less than or equal to all non-null, non-NaN values
-    /// in the column for the file.
-    ///
-    /// Reference:
-    ///
-    /// - [Binary single-value serialization](https://iceberg.apache.org/spec/#binary-single-value-serialization)
-    #[builder(default)]
-    pub(crate) lower_bounds: HashMap<i32, Datum>,
-    /// field id: 128
-    /// key field id: 129
-    /// value field id: 130
-    ///
-    /// Map from column id to upper bound in the column serialized as binary.
-    /// Each value must be greater than or equal to all non-null, non-Nan
-    /// values in the column for the file.
-    ///
-    /// Reference:
-    ///
-    /// - [Binary single-value serialization](https://iceberg.apache.org/spec/#binary-single-value-serialization)
-    #[builder(default)]
-    pub(crate) upper_bounds: HashMap<i32, Datum>,
-    /// field id: 131
-    ///
-    /// Implementation-specific key metadata for encryption
-    #[builder(default)]
-    pub(crate) key_metadata: Option<Vec<u8>>,
-    /// field id: 132
-    /// element field id: 133
-    ///
-    /// Split offsets for the data file. For example, all row group offsets
-    /// in a Parquet file. Must be sorted ascending
-    #[builder(default)]
-    pub(crate) split_offsets: Vec<i64>,
-    /// field id: 135
-    /// element field id: 136
-    ///
-    /// Field ids used to determine row equality in equality delete files.
-    /// Required when content is EqualityDeletes and should be null
-    /// otherwise. Fields with ids listed in this column must be present
-    /// in the delete file
-    #[builder(default)]
-    pub(crate) equality_ids: Vec<i32>,
-    /// field id: 140
-    ///
-    /// ID representing sort order for this file.
-    ///
-    /// If sort order ID is missing or unknown, then the order is assumed to
-    /// be unsorted. Only data files and equality delete files should be
-    /// written with a non-null order id. Position deletes are required to be
-    /// sorted by file and position, not a table order, and should set sort
-    /// order id to null. Readers must ignore sort order id for position
-    /// delete files.
-    #[builder(default, setter(strip_option))]
-    pub(crate) sort_order_id: Option<i32>,
-    /// This field is not included in spec. It is just store in memory representation used
-    /// in process.
-    pub(crate) partition_spec_id: i32,
-}
-
-impl DataFile {
-    /// Get the content type of the data file (data, equality deletes, or position deletes)
-    pub fn content_type(&self) -> DataContentType {
-        self.content
-    }
-    /// Get the file path as full URI with FS scheme
-    pub fn file_path(&self) -> &str {
-        &self.file_path
-    }
-    /// Get the file format of the file (avro, orc or parquet).
-    pub fn file_format(&self) -> DataFileFormat {
-        self.file_format
+    fn apply_metrics_modes(&self, data_file: &mut DataFile) {
+        if self.default_metrics_mode == MetricsMode::Full && self.column_metrics_modes.is_empty() {
+            return;
+        }
+        let mode = |field_id: &i32| {
+            self.column_metrics_modes
+                .get(field_id)
+                .copied()
+                .unwrap_or(self.default_metrics_mode)
+        };
+        let keep_counts = |field_id: &i32| mode(field_id) != MetricsMode::None;
+        let keep_all = |field_id: &i32| mode(field_id) == MetricsMode::Full;
+        data_file.column_sizes.retain(|k, _| keep_counts(k));
+        data_file.value_counts.retain(|k, _| keep_counts(k));
+        data_file.null_value_counts.retain(|k, _| keep_counts(k));
+        data_file.nan_value_counts.retain(|k, _| keep_all(k));
+        data_file.lower_bounds.retain(|k, _| keep_all(k));
+        data_file.upper_bounds.retain(|k, _| keep_all(k));
     }
-    /// Get the partition values of the file.
-    pub fn partition(&self) -> &Struct {
-        &self.partition
+
+    fn check_data_file(&self, data_file: &DataFile) -> Result<()> {
+        if self.path_check
+            && !data_file.file_path.contains("://")
+            && !data_file.file_path.starts_with('/')
+        {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "File path {:?} of data file is neither a URI with a scheme nor an absolute path",
+                    data_file.file_path
+                ),
+            ));
+        }
+        if data_file.partition_spec_id != self.metadata.partition_spec.spec_id() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Partition spec id {} of data file {} doesn't match the manifest partition spec id {}",
+                    data_file.partition_spec_id,
+                    data_file.file_path,
+                    self.metadata.partition_spec.spec_id()
+                ),
+            ));
+        }
+        if self.metadata.format_version == FormatVersion::V1
+            && data_file.content != DataContentType::Data
+        {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Format version 1 doesn't support delete files, got entry with content type {:?}",
+                    data_file.content
+                ),
+            ));
+        }
+        if let (Some(known_sort_orders), Some(sort_order_id)) =
+            (&self.known_sort_orders, data_file.sort_order_id)
+        {
+            if !known_sort_orders.contains(&sort_order_id) {
+                return Err(Error::new(
+                    ErrorKind::DataInvalid,
+                    format!(
+                        "Sort order id {sort_order_id} of data file {} is not a known sort order",
+                        data_file.file_path
+                    ),
+                ));
+            }
+        }
+        if data_file.content == DataContentType::Data && data_file.referenced_data_file.is_some() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Data file {} must not have a referenced data file, it's only valid for delete files",
+                    data_file.file_path
+                ),
+            ));
+        }
+        match self.metadata.content {
+            ManifestContentType::Data => {
+                if data_file.content != DataContentType::Data {
+                    return Err(Error::new(
+                        ErrorKind::DataInvalid,
+                        format!(
+                            "Content type of entry {:?} should have DataContentType::Data",
+                            data_file.content
+                        ),
+                    ));
+                }
+            }
+            ManifestContentType::Deletes => {
+                if data_file.content != DataContentType::EqualityDeletes
+                    && data_file.content != DataContentType::PositionDeletes
+                {
+                    return Err(Error::new(
+                    ErrorKind::DataInvalid,
+                    format!("Content type of entry {:?} should have DataContentType::EqualityDeletes or DataContentType::PositionDeletes", data_file.content),
+                ));
+                }
+            }
+        }
+        Ok(())
     }
-    /// Get the record count in the data file.
-    pub fn record_count(&self) -> u64 {
-        self.record_count
+
+    /// Add a new manifest entry. This method will update following status of the entry:
+    /// - Update the entry status to `Added`
+    /// - Set the snapshot id to the current snapshot id
+    /// - Set the sequence number to `None` if it is invalid(smaller than 0)
+    /// - Set the file sequence number to `None`
+    pub(crate) fn add_entry(&mut self, mut entry: ManifestEntry) -> Result<()> {
+        self.check_data_file(&entry.data_file)?;
+        if entry.sequence_number().is_some_and(|n| n >= 0) {
+            entry.status = ManifestStatus::Added;
+            entry.snapshot_id = self.snapshot_id;
+            entry.file_sequence_number = None;
+        } else {
+            entry.status = ManifestStatus::Added;
+            entry.snapshot_id = self.snapshot_id;
+            entry.sequence_number = None;
+            entry.file_sequence_number = None;
+        };
+        self.add_entry_inner(entry)?;
+        Ok(())
     }
-    /// Get the file size in bytes.
-    pub fn file_size_in_bytes(&self) -> u64 {
-        self.file_size_in_bytes
+
+    /// Add file as an added entry with a specific sequence number. The entry's snapshot ID will be this manifest's snapshot ID. The entry's data sequence
+    /// number will be the provided data sequence number. The entry's file sequence number will be
+    /// assigned at commit.
+    ///
+    /// A negative sequence number is treated as unassigned, or rejected when the writer was
+    /// built with [`ManifestWriterBuilder::with_strict_sequence_numbers`].
+    pub fn add_file(&mut self, data_file: DataFile, sequence_number: i64) -> Result<()> {
+        self.add_added_entry(data_file, sequence_number, self.snapshot_id)
     }
-    /// Get the column sizes.
-    /// Map from column id to the total size on disk of all regions that
-    /// store the column. Does not include bytes necessary to read other
-    /// columns, like footers. Null for row-oriented formats (Avro)
-    pub fn column_sizes(&self) -> &HashMap<i32, u64> {
-        &self.column_sizes
+
+    /// Add file as an added entry like [`ManifestWriter::add_file`], but with the given
+    /// snapshot ID instead of this manifest's snapshot ID, e.g. to preserve the snapshot
+    /// that originally added the file when cherry-picking it.
+    pub fn add_file_with_snapshot_id(
+        &mut self,
+        data_file: DataFile,
+        sequence_number: i64,
+        snapshot_id: i64,
+    ) -> Result<()> {
+        self.add_added_entry(data_file, sequence_number, Some(snapshot_id))
     }
-    /// Get the columns value counts for the data file.
-    /// Map from column id to number of values in the column (including null
-    /// and NaN values)
-    pub fn value_counts(&self) -> &HashMap<i32, u64> {
-        &self.value_counts
+
+    /// Add a delete file, i.e. a file with position or equality deletes, as an added entry
+    /// of this delete manifest. The entry's snapshot ID will be this manifest's snapshot ID
+    /// and its file sequence number will be assigned at commit, like for
+    /// [`ManifestWriter::add_file`].
+    ///
+    /// Unlike [`ManifestWriter::add_file`], the content type of `data_file` is checked
+    /// up front, a data file is rejected with an error pointing to `add_file`. To mark a
+    /// file as removed, use [`ManifestWriter::add_delete_file`] instead.
+    pub fn add_delete(&mut self, data_file: DataFile, sequence_number: i64) -> Result<()> {
+        if data_file.content == DataContentType::Data {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Expected a position or equality delete file, got data file {}, use add_file to add data files",
+                    data_file.file_path
+                ),
+            ));
+        }
+        self.add_added_entry(data_file, sequence_number, self.snapshot_id)
     }
-    /// Get the null value counts of the data file.
-    /// Map from column id to number of null values in the column
-    pub fn null_value_counts(&self) -> &HashMap<i32, u64> {
-        &self.null_value_counts
+
+    fn add_added_entry(
+        &mut self,
+        data_file: DataFile,
+        sequence_number: i64,
+        snapshot_id: Option<i64>,
+    ) -> Result<()> {
+        self.check_data_file(&data_file)?;
+        if self.strict_sequence_numbers && sequence_number < 0 {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Negative sequence number {sequence_number} for data file {}",
+                    data_file.file_path
+                ),
+            ));
+        }
+        let entry = ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id,
+            sequence_number: (sequence_number >= 0).then_some(sequence_number),
+            file_sequence_number: None,
+            data_file,
+        };
+        self.add_entry_inner(entry)?;
+        Ok(())
     }
-    /// Get the nan value counts of the data file.
-    /// Map from column id to number of NaN values in the column
-    pub fn nan_value_counts(&self) -> &HashMap<i32, u64> {
-        &self.nan_value_counts
+
+    /// Add a delete manifest entry. This method will update following status of the entry:
+    /// - Update the entry status to `Deleted`
+    /// - Set the snapshot id to the current snapshot id
+    ///
+    /// # TODO
+    /// Remove this allow later
+    #[allow(dead_code)]
+    pub(crate) fn add_delete_entry(&mut self, mut entry: ManifestEntry) -> Result<()> {
+        self.check_data_file(&entry.data_file)?;
+        entry.status = ManifestStatus::Deleted;
+        entry.snapshot_id = self.snapshot_id;
+        self.add_entry_inner(entry)?;
+        Ok(())
+    }
+
+    /// Add a file as delete manifest entry. The entry's snapshot ID will be this manifest's snapshot ID.
+    /// However, the original data and file sequence numbers of the file must be preserved when
+    /// the file is marked as deleted.
+    pub fn add_delete_file(
+        &mut self,
+        data_file: DataFile,
+        sequence_number: i64,
+        file_sequence_number: Option<i64>,
+    ) -> Result<()> {
+        self.check_data_file(&data_file)?;
+        if file_sequence_number.is_none() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Delete files must preserve file_sequence_number, got none for {}",
+                    data_file.file_path
+                ),
+            ));
+        }
+        let entry = ManifestEntry {
+            status: ManifestStatus::Deleted,
+            snapshot_id: self.snapshot_id,
+            sequence_number: Some(sequence_number),
+            file_sequence_number,
+            data_file,
+        };
+        self.add_entry_inner(entry)?;
+        Ok(())
+    }
+
+    /// Add an existing manifest entry. This method will update following status of the entry:
+    /// - Update the entry status to `Existing`
+    ///
+    /// # TODO
+    /// Remove this allow later
+    #[allow(dead_code)]
+    pub(crate) fn add_existing_entry(&mut self, mut entry: ManifestEntry) -> Result<()> {
+        self.check_data_file(&entry.data_file)?;
+        entry.status = ManifestStatus::Existing;
+        self.add_entry_inner(entry)?;
+        Ok(())
+    }
+
+    /// Add an file as existing manifest entry. The original data and file sequence numbers, snapshot ID,
+    /// which were assigned at commit, must be preserved when adding an existing entry.
+    pub fn add_existing_file(
+        &mut self,
+        data_file: DataFile,
+        snapshot_id: i64,
+        sequence_number: i64,
+        file_sequence_number: Option<i64>,
+    ) -> Result<()> {
+        self.check_data_file(&data_file)?;
+        let entry = ManifestEntry {
+            status: ManifestStatus::Existing,
+            snapshot_id: Some(snapshot_id),
+            sequence_number: Some(sequence_number),
+            file_sequence_number,
+            data_file,
+        };
+        self.add_entry_inner(entry)?;
+        Ok(())
+    }
+
+    /// Add a file superseded by this manifest's snapshot, e.g. a file replaced by compaction,
+    /// as a delete manifest entry. This is [`ManifestWriter::add_delete_file`]: the entry's
+    /// snapshot ID will be this manifest's snapshot ID, and the original data and file
+    /// sequence numbers of the file must be preserved.
+    pub fn add_superseded(
+        &mut self,
+        data_file: DataFile,
+        sequence_number: i64,
+        file_sequence_number: Option<i64>,
+    ) -> Result<()> {
+        self.add_delete_file(data_file, sequence_number, file_sequence_number)
+    }
+
+    /// Copy the live entries of a rewritten manifest, marking the files whose path is in
+    /// `superseded` as deleted with [`ManifestWriter::add_superseded`], and adding the other
+    /// files as existing with [`ManifestWriter::add_existing_file`].
+    ///
+    /// Entries with status [`ManifestStatus::Deleted`] are dropped. The entries must have
+    /// their snapshot ID and sequence numbers, e.g. inherited when loading the manifest from
+    /// its manifest list entry, otherwise this fails.
+    pub fn add_rewritten_entries(
+        &mut self,
+        entries: impl IntoIterator<Item = impl AsRef<ManifestEntry>>,
+        superseded: &HashSet<&str>,
+    ) -> Result<()> {
+        for entry in entries {
+            let entry = entry.as_ref();
+            if !entry.is_alive() {
+                continue;
+            }
+            let sequence_number = entry.sequence_number.ok_or_else(|| {
+                Error::new(
+                    ErrorKind::DataInvalid,
+                    format!("Entry for {} has no sequence number", entry.file_path()),
+                )
+            })?;
+            if superseded.contains(entry.file_path()) {
+                self.add_superseded(
+                    entry.data_file.clone(),
+                    sequence_number,
+                    entry.file_sequence_number,
+                )?;
+            } else {
+                let snapshot_id = entry.snapshot_id.ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::DataInvalid,
+                        format!("Entry for {} has no snapshot id", entry.file_path()),
+                    )
+                })?;
+                self.add_existing_file(
+                    entry.data_file.clone(),
+                    snapshot_id,
+                    sequence_number,
+                    entry.file_sequence_number,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_entry_inner(&mut self, mut entry: ManifestEntry) -> Result<()> {
+        // Check if the entry has sequence number
+        if (entry.status == ManifestStatus::Deleted || entry.status == ManifestStatus::Existing)
+            && (entry.sequence_number.is_none() || entry.file_sequence_number.is_none())
+        {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                "Manifest entry with status Existing or Deleted should have sequence number",
+            ));
+        }
+
+        self.update_partition_summaries(&entry.data_file)?;
+
+        // Update the statistics
+        match entry.status {
+            ManifestStatus::Added => {
+                self.added_files += 1;
+                self.added_rows += entry.data_file.record_count;
+            }
+            ManifestStatus::Deleted => {
+                self.deleted_files += 1;
+                self.deleted_rows += entry.data_file.record_count;
+            }
+            ManifestStatus::Existing => {
+                self.existing_files += 1;
+                self.existing_rows += entry.data_file.record_count;
+            }
+        }
+        if entry.is_alive() {
+            if let Some(seq_num) = entry.sequence_number {
+                self.min_seq_num = Some(self.min_seq_num.map_or(seq_num, |v| min(v, seq_num)));
+            }
+        }
+        self.apply_metrics_modes(&mut entry.data_file);
+        self.apply_bounds_byte_budget(&mut entry.data_file);
+        if self.sort_equality_ids {
+            entry.data_file.equality_ids.sort_unstable();
+        }
+        self.manifest_entries.push(entry);
+        Ok(())
+    }
+
+    /// Write manifest file and return it.
+    ///
+    /// With an encryption manager, see [`ManifestWriterBuilder::with_encryption_manager`],
+    /// the encrypted bytes are written and the returned [`ManifestFile`] has their length
+    /// and the key metadata to decrypt them.
+    ///
+    /// Fails for a writer built with [`ManifestWriterBuilder::new_in_memory`], which has
+    /// no output file to write to.
+    pub async fn write_manifest_file(mut self) -> Result<ManifestFile> {
+        let Some(output) = self.output.take() else {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "Manifest writer has no output file, use write_to_bytes to encode the manifest in memory",
+            ));
+        };
+        let (mut content, mut manifest_file) = self.encode()?;
+        if let Some(encryption_manager) = &self.encryption_manager {
+            content = encryption_manager
+                .encrypt(content, &mut manifest_file.key_metadata)
+                .await?;
+            manifest_file.manifest_length = content.len() as i64;
+        }
+        output.write(content).await?;
+        Ok(manifest_file)
+    }
+
+    /// Encode the manifest in memory instead of writing it to the output file, and
+    /// return the avro bytes together with the [`ManifestFile`] describing them.
+    ///
+    /// Nothing is written to the output file, its location, or the path given to
+    /// [`ManifestWriterBuilder::new_in_memory`], is only used as the `manifest_path` of
+    /// the returned [`ManifestFile`]. The bytes aren't encrypted, even when an encryption
+    /// manager is set.
+    pub fn write_to_bytes(mut self) -> Result<(Bytes, ManifestFile)> {
+        self.encode()
+    }
+
+    fn encode(&mut self) -> Result<(Bytes, ManifestFile)> {
+        if self.is_below_min_entries() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Manifest has {} entries, fewer than the configured minimum of {}",
+                    self.manifest_entries.len(),
+                    self.min_entries.unwrap_or_default()
+                ),
+            ));
+        }
+
+        // Create the avro writer
+        let partition_type = self
+            .metadata
+            .partition_spec
+            .partition_type(&self.metadata.schema)?;
+        let table_schema = &self.metadata.schema;
+        let avro_schema = match self.metadata.format_version {
+            FormatVersion::V1 => manifest_schema_v1(&partition_type)?,
+            FormatVersion::V2 => manifest_schema_v2(&partition_type)?,
+        };
+        let mut avro_writer = AvroWriter::new(&avro_schema, Vec::new());
+        avro_writer.add_user_metadata(
+            "schema".to_string(),
+            to_vec(table_schema).map_err(|err| {
+                Error::new(ErrorKind::DataInvalid, "Fail to serialize table schema")
+                    .with_source(err)
+            })?,
+        )?;
+        avro_writer.add_user_metadata(
+            "schema-id".to_string(),
+            table_schema.schema_id().to_string(),
+        )?;
+        avro_writer.add_user_metadata(
+            "partition-spec".to_string(),
+            to_vec(&self.metadata.partition_spec.fields()).map_err(|err| {
+                Error::new(ErrorKind::DataInvalid, "Fail to serialize partition spec")
+                    .with_source(err)
+            })?,
+        )?;
+        avro_writer.add_user_metadata(
+            "partition-spec-id".to_string(),
+            self.metadata.partition_spec.spec_id().to_string(),
+        )?;
+        avro_writer.add_user_metadata(
+            "format-version".to_string(),
+            (self.metadata.format_version as u8).to_string(),
+        )?;
+        if self.metadata.format_version == FormatVersion::V2 {
+            avro_writer
+                .add_user_metadata("content".to_string(), self.metadata.content.to_string())?;
+        }
+        for (key, value) in self
+            .metadata
+            .extra_metadata
+            .iter()
+            .sorted_by_key(|(key, _)| *key)
+        {
+            avro_writer.add_user_metadata(key.clone(), value)?;
+        }
+        if self.schema_fingerprint {
+            avro_writer.add_user_metadata(
+                MANIFEST_SCHEMA_FINGERPRINT_KEY.to_string(),
+                avro_schema.fingerprint::<Rabin>().to_string(),
+            )?;
+        }
+
+        let partition_summary = if self.partition_summaries {
+            self.construct_partition_summaries(&partition_type)
+        } else {
+            vec![]
+        };
+        // Write manifest entries
+        for entry in std::mem::take(&mut self.manifest_entries) {
+            let value = match self.metadata.format_version {
+                FormatVersion::V1 => {
+                    to_value(_serde::ManifestEntryV1::try_from(entry, &partition_type)?)?
+                        .resolve(&avro_schema)?
+                }
+                FormatVersion::V2 => {
+                    to_value(_serde::ManifestEntryV2::try_from(entry, &partition_type)?)?
+                        .resolve(&avro_schema)?
+                }
+            };
+
+            avro_writer.append(value)?;
+        }
+
+        let content = avro_writer.into_inner()?;
+        let length = content.len();
+
+        let manifest_file = ManifestFile {
+            manifest_path: self.manifest_path.clone(),
+            manifest_length: length as i64,
+            partition_spec_id: self.metadata.partition_spec.spec_id(),
+            content: self.metadata.content,
+            // sequence_number and min_sequence_number with UNASSIGNED_SEQUENCE_NUMBER will be replace with
+            // real sequence number in `ManifestListWriter`.
+            sequence_number: self.sequence_number.unwrap_or(UNASSIGNED_SEQUENCE_NUMBER),
+            min_sequence_number: self
+                .min_seq_num
+                .or(self.sequence_number)
+                .unwrap_or(UNASSIGNED_SEQUENCE_NUMBER),
+            added_snapshot_id: self.snapshot_id.unwrap_or(UNASSIGNED_SNAPSHOT_ID),
+            added_files_count: Some(self.added_files),
+            existing_files_count: Some(self.existing_files),
+            deleted_files_count: Some(self.deleted_files),
+            added_rows_count: Some(self.added_rows),
+            existing_rows_count: Some(self.existing_rows),
+            deleted_rows_count: Some(self.deleted_rows),
+            partitions: partition_summary,
+            key_metadata: std::mem::take(&mut self.key_metadata),
+        };
+        Ok((Bytes::from(content), manifest_file))
+    }
+}
+
+/// Write manifests concurrently, with at most `concurrency` writes in flight, and return
+/// their [`ManifestFile`]s in the order of `writers`.
+pub async fn write_manifests(
+    writers: Vec<ManifestWriter>,
+    concurrency: usize,
+) -> Result<Vec<ManifestFile>> {
+    stream::iter(
+        writers
+            .into_iter()
+            .map(|writer| writer.write_manifest_file()),
+    )
+    .buffered(concurrency.max(1))
+    .try_collect()
+    .await
+}
+
+/// This is a helper module that defines the schema field of the manifest list entry.
+mod _const_schema {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use apache_avro::schema::Name;
+    use apache_avro::Schema as AvroSchema;
+    use once_cell::sync::Lazy;
+
+    use crate::avro::schema_to_avro_schema;
+    use crate::spec::{
+        FormatVersion, ListType, MapType, NestedField, NestedFieldRef, PrimitiveType, Schema,
+        StructType, Type,
+    };
+    use crate::Error;
+
+    static STATUS: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::required(
+                0,
+                "status",
+                Type::Primitive(PrimitiveType::Int),
+            ))
+        })
+    };
+
+    static SNAPSHOT_ID_V1: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::required(
+                1,
+                "snapshot_id",
+                Type::Primitive(PrimitiveType::Long),
+            ))
+        })
+    };
+
+    static SNAPSHOT_ID_V2: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                1,
+                "snapshot_id",
+                Type::Primitive(PrimitiveType::Long),
+            ))
+        })
+    };
+
+    static SEQUENCE_NUMBER: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                3,
+                "sequence_number",
+                Type::Primitive(PrimitiveType::Long),
+            ))
+        })
+    };
+
+    static FILE_SEQUENCE_NUMBER: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                4,
+                "file_sequence_number",
+                Type::Primitive(PrimitiveType::Long),
+            ))
+        })
+    };
+
+    static CONTENT: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::required(
+                134,
+                "content",
+                Type::Primitive(PrimitiveType::Int),
+            ))
+        })
+    };
+
+    static FILE_PATH: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::required(
+                100,
+                "file_path",
+                Type::Primitive(PrimitiveType::String),
+            ))
+        })
+    };
+
+    static FILE_FORMAT: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::required(
+                101,
+                "file_format",
+                Type::Primitive(PrimitiveType::String),
+            ))
+        })
+    };
+
+    static RECORD_COUNT: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::required(
+                103,
+                "record_count",
+                Type::Primitive(PrimitiveType::Long),
+            ))
+        })
+    };
+
+    static FILE_SIZE_IN_BYTES: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::required(
+                104,
+                "file_size_in_bytes",
+                Type::Primitive(PrimitiveType::Long),
+            ))
+        })
+    };
+
+    // Deprecated. Always write a default in v1. Do not write in v2.
+    static BLOCK_SIZE_IN_BYTES: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::required(
+                105,
+                "block_size_in_bytes",
+                Type::Primitive(PrimitiveType::Long),
+            ))
+        })
+    };
+
+    static COLUMN_SIZES: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                108,
+                "column_sizes",
+                Type::Map(MapType {
+                    key_field: Arc::new(NestedField::required(
+                        117,
+                        "key",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    value_field: Arc::new(NestedField::required(
+                        118,
+                        "value",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                }),
+            ))
+        })
+    };
+
+    static VALUE_COUNTS: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                109,
+                "value_counts",
+                Type::Map(MapType {
+                    key_field: Arc::new(NestedField::required(
+                        119,
+                        "key",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    value_field: Arc::new(NestedField::required(
+                        120,
+                        "value",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                }),
+            ))
+        })
+    };
+
+    static NULL_VALUE_COUNTS: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                110,
+                "null_value_counts",
+                Type::Map(MapType {
+                    key_field: Arc::new(NestedField::required(
+                        121,
+                        "key",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    value_field: Arc::new(NestedField::required(
+                        122,
+                        "value",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                }),
+            ))
+        })
+    };
+
+    static NAN_VALUE_COUNTS: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                137,
+                "nan_value_counts",
+                Type::Map(MapType {
+                    key_field: Arc::new(NestedField::required(
+                        138,
+                        "key",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    value_field: Arc::new(NestedField::required(
+                        139,
+                        "value",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                }),
+            ))
+        })
+    };
+
+    static LOWER_BOUNDS: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                125,
+                "lower_bounds",
+                Type::Map(MapType {
+                    key_field: Arc::new(NestedField::required(
+                        126,
+                        "key",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    value_field: Arc::new(NestedField::required(
+                        127,
+                        "value",
+                        Type::Primitive(PrimitiveType::Binary),
+                    )),
+                }),
+            ))
+        })
+    };
+
+    static UPPER_BOUNDS: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                128,
+                "upper_bounds",
+                Type::Map(MapType {
+                    key_field: Arc::new(NestedField::required(
+                        129,
+                        "key",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    value_field: Arc::new(NestedField::required(
+                        130,
+                        "value",
+                        Type::Primitive(PrimitiveType::Binary),
+                    )),
+                }),
+            ))
+        })
+    };
+
+    static KEY_METADATA: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                131,
+                "key_metadata",
+                Type::Primitive(PrimitiveType::Binary),
+            ))
+        })
+    };
+
+    static SPLIT_OFFSETS: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                132,
+                "split_offsets",
+                Type::List(ListType {
+                    element_field: Arc::new(NestedField::required(
+                        133,
+                        "element",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                }),
+            ))
+        })
+    };
+
+    static EQUALITY_IDS: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                135,
+                "equality_ids",
+                Type::List(ListType {
+                    element_field: Arc::new(NestedField::required(
+                        136,
+                        "element",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                }),
+            ))
+        })
+    };
+
+    static SORT_ORDER_ID: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                140,
+                "sort_order_id",
+                Type::Primitive(PrimitiveType::Int),
+            ))
+        })
+    };
+
+    static REFERENCED_DATA_FILE: Lazy<NestedFieldRef> = {
+        Lazy::new(|| {
+            Arc::new(NestedField::optional(
+                143,
+                "referenced_data_file",
+                Type::Primitive(PrimitiveType::String),
+            ))
+        })
+    };
+
+    fn data_file_fields_v2(partition_type: &StructType) -> Vec<NestedFieldRef> {
+        vec![
+            CONTENT.clone(),
+            FILE_PATH.clone(),
+            FILE_FORMAT.clone(),
+            Arc::new(NestedField::required(
+                102,
+                "partition",
+                Type::Struct(partition_type.clone()),
+            )),
+            RECORD_COUNT.clone(),
+            FILE_SIZE_IN_BYTES.clone(),
+            COLUMN_SIZES.clone(),
+            VALUE_COUNTS.clone(),
+            NULL_VALUE_COUNTS.clone(),
+            NAN_VALUE_COUNTS.clone(),
+            LOWER_BOUNDS.clone(),
+            UPPER_BOUNDS.clone(),
+            KEY_METADATA.clone(),
+            SPLIT_OFFSETS.clone(),
+            EQUALITY_IDS.clone(),
+            SORT_ORDER_ID.clone(),
+            REFERENCED_DATA_FILE.clone(),
+        ]
+    }
+
+    pub(super) fn data_file_schema_v2(partition_type: &StructType) -> Result<AvroSchema, Error> {
+        let schema = Schema::builder()
+            .with_fields(data_file_fields_v2(partition_type))
+            .build()?;
+        schema_to_avro_schema("data_file", &schema)
+    }
+
+    pub(super) fn manifest_schema_v2(partition_type: &StructType) -> Result<AvroSchema, Error> {
+        let fields = vec![
+            STATUS.clone(),
+            SNAPSHOT_ID_V2.clone(),
+            SEQUENCE_NUMBER.clone(),
+            FILE_SEQUENCE_NUMBER.clone(),
+            Arc::new(NestedField::required(
+                2,
+                "data_file",
+                Type::Struct(StructType::new(data_file_fields_v2(partition_type))),
+            )),
+        ];
+        let schema = Schema::builder().with_fields(fields).build()?;
+        schema_to_avro_schema("manifest_entry", &schema)
+    }
+
+    fn data_file_fields_v1(partition_type: &StructType) -> Vec<NestedFieldRef> {
+        vec![
+            FILE_PATH.clone(),
+            FILE_FORMAT.clone(),
+            Arc::new(NestedField::required(
+                102,
+                "partition",
+                Type::Struct(partition_type.clone()),
+            )),
+            RECORD_COUNT.clone(),
+            FILE_SIZE_IN_BYTES.clone(),
+            BLOCK_SIZE_IN_BYTES.clone(),
+            COLUMN_SIZES.clone(),
+            VALUE_COUNTS.clone(),
+            NULL_VALUE_COUNTS.clone(),
+            NAN_VALUE_COUNTS.clone(),
+            LOWER_BOUNDS.clone(),
+            UPPER_BOUNDS.clone(),
+            KEY_METADATA.clone(),
+            SPLIT_OFFSETS.clone(),
+            SORT_ORDER_ID.clone(),
+        ]
+    }
+
+    pub(super) fn data_file_schema_v1(partition_type: &StructType) -> Result<AvroSchema, Error> {
+        let schema = Schema::builder()
+            .with_fields(data_file_fields_v1(partition_type))
+            .build()?;
+        schema_to_avro_schema("data_file", &schema)
+    }
+
+    /// Data file schema with the record named `record_name` instead of `data_file`.
+    pub(super) fn data_file_schema_with_name(
+        partition_type: &StructType,
+        version: FormatVersion,
+        record_name: &str,
+    ) -> Result<AvroSchema, Error> {
+        let mut schema = match version {
+            FormatVersion::V1 => data_file_schema_v1(partition_type)?,
+            FormatVersion::V2 => data_file_schema_v2(partition_type)?,
+        };
+        if let AvroSchema::Record(record) = &mut schema {
+            record.name = Name::new(record_name)?;
+        }
+        Ok(schema)
+    }
+
+    /// Data file schema with only the required fields and the optional fields in `field_ids`.
+    pub(super) fn data_file_schema_projected(
+        partition_type: &StructType,
+        version: FormatVersion,
+        field_ids: &HashSet<i32>,
+    ) -> Result<AvroSchema, Error> {
+        let fields = match version {
+            FormatVersion::V1 => data_file_fields_v1(partition_type),
+            FormatVersion::V2 => data_file_fields_v2(partition_type),
+        };
+        let schema = Schema::builder()
+            .with_fields(
+                fields
+                    .into_iter()
+                    .filter(|field| field.required || field_ids.contains(&field.id))
+                    .collect::<Vec<_>>(),
+            )
+            .build()?;
+        schema_to_avro_schema("data_file", &schema)
+    }
+
+    pub(super) fn manifest_schema_v1(partition_type: &StructType) -> Result<AvroSchema, Error> {
+        let fields = vec![
+            STATUS.clone(),
+            SNAPSHOT_ID_V1.clone(),
+            Arc::new(NestedField::required(
+                2,
+                "data_file",
+                Type::Struct(StructType::new(data_file_fields_v1(partition_type))),
+            )),
+        ];
+        let schema = Schema::builder().with_fields(fields).build()?;
+        schema_to_avro_schema("manifest_entry", &schema)
+    }
+}
+
+/// Meta data of a manifest that is stored in the key-value metadata of the Avro file
+#[derive(Debug, PartialEq, Clone, Eq, TypedBuilder)]
+pub struct ManifestMetadata {
+    /// The table schema at the time the manifest
+    /// was written
+    schema: SchemaRef,
+    /// ID of the schema used to write the manifest as a string
+    schema_id: SchemaId,
+    /// The partition spec used to write the manifest
+    partition_spec: PartitionSpec,
+    /// Table format version number of the manifest as a string
+    format_version: FormatVersion,
+    /// Type of content files tracked by the manifest: “data” or “deletes”
+    content: ManifestContentType,
+    /// Custom key-value metadata, i.e. all keys that are not reserved by the spec
+    #[builder(default)]
+    extra_metadata: HashMap<String, Vec<u8>>,
+}
+
+/// Key of the manifest metadata holding the avro schema fingerprint, see
+/// [`ManifestWriterBuilder::with_schema_fingerprint`].
+///
+/// It's not `avro.schema.fingerprint` since keys starting with `avro.` are reserved by avro.
+pub const MANIFEST_SCHEMA_FINGERPRINT_KEY: &str = "avro-schema-fingerprint";
+
+/// Keys of the manifest avro metadata that are reserved by the spec.
+const RESERVED_METADATA_KEYS: [&str; 6] = [
+    "schema",
+    "schema-id",
+    "partition-spec",
+    "partition-spec-id",
+    "format-version",
+    "content",
+];
+
+impl ManifestMetadata {
+    /// Create manifest metadata from its parts.
+    pub fn new(
+        schema: SchemaRef,
+        schema_id: SchemaId,
+        partition_spec: PartitionSpec,
+        format_version: FormatVersion,
+        content: ManifestContentType,
+    ) -> Self {
+        Self {
+            schema,
+            schema_id,
+            partition_spec,
+            format_version,
+            content,
+            extra_metadata: HashMap::new(),
+        }
+    }
+
+    /// Parse from metadata in avro file.
+    pub fn parse(meta: &HashMap<String, Vec<u8>>) -> Result<Self> {
+        Self::parse_with_schema(meta, None, false)
+    }
+
+    /// Parse from metadata in avro file, using `schema` instead of the embedded
+    /// `schema` key when given. The embedded schema isn't read at all in that case.
+    ///
+    /// With `assign_missing_partition_field_ids`, partition fields without a field id
+    /// are accepted, and assigned ids by position starting at 1000.
+    fn parse_with_schema(
+        meta: &HashMap<String, Vec<u8>>,
+        schema: Option<SchemaRef>,
+        assign_missing_partition_field_ids: bool,
+    ) -> Result<Self> {
+        let schema = match schema {
+            Some(schema) => schema,
+            None => Arc::new({
+                let bs = meta.get("schema").ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::DataInvalid,
+                        "schema is required in manifest metadata but not found",
+                    )
+                })?;
+                serde_json::from_slice::<Schema>(bs).map_err(|err| {
+                    Error::new(
+                        ErrorKind::DataInvalid,
+                        "Fail to parse schema in manifest metadata",
+                    )
+                    .with_source(err)
+                })?
+            }),
+        };
+        let schema_id: i32 = meta
+            .get("schema-id")
+            .map(|bs| {
+                String::from_utf8_lossy(bs).parse().map_err(|err| {
+                    Error::new(
+                        ErrorKind::DataInvalid,
+                        "Fail to parse schema id in manifest metadata",
+                    )
+                    .with_source(err)
+                })
+            })
+            .transpose()?
+            .unwrap_or(0);
+        let partition_spec = {
+            let fields = {
+                let bs = meta.get("partition-spec").ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::DataInvalid,
+                        "partition-spec is required in manifest metadata but not found",
+                    )
+                })?;
+                let parse_error = |err| {
+                    Error::new(
+                        ErrorKind::DataInvalid,
+                        "Fail to parse partition spec in manifest metadata",
+                    )
+                    .with_source(err)
+                };
+                if assign_missing_partition_field_ids {
+                    serde_json::from_slice::<Vec<UnboundPartitionField>>(bs).map_err(parse_error)?
+                } else {
+                    serde_json::from_slice::<Vec<PartitionField>>(bs)
+                        .map_err(parse_error)?
+                        .into_iter()
+                        .map(|f| f.into_unbound())
+                        .collect()
+                }
+            };
+            let spec_id = meta
+                .get("partition-spec-id")
+                .map(|bs| {
+                    String::from_utf8_lossy(bs).parse().map_err(|err| {
+                        Error::new(
+                            ErrorKind::DataInvalid,
+                            "Fail to parse partition spec id in manifest metadata",
+                        )
+                        .with_source(err)
+                    })
+                })
+                .transpose()?
+                .unwrap_or(0);
+            PartitionSpec::builder(schema.clone())
+                .with_spec_id(spec_id)
+                .add_unbound_fields(fields)?
+                .build()?
+        };
+        let format_version = if let Some(bs) = meta.get("format-version") {
+            parse_format_version(bs)?
+        } else {
+            FormatVersion::V1
+        };
+        let content = if let Some(bs) = meta.get("content") {
+            parse_content(bs)?
+        } else {
+            ManifestContentType::Data
+        };
+        let extra_metadata = meta
+            .iter()
+            .filter(|(key, _)| !RESERVED_METADATA_KEYS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Ok(ManifestMetadata {
+            schema,
+            schema_id,
+            partition_spec,
+            format_version,
+            content,
+            extra_metadata,
+        })
+    }
+
+    /// Check that the recorded `schema-id` matches the id of the embedded schema.
+    ///
+    /// A mismatch indicates a producer bug or corruption, but it's not checked by
+    /// [`ManifestMetadata::parse`] since the `schema-id` key is sometimes stale.
+    pub fn validate_schema_id(&self) -> Result<()> {
+        if self.schema_id != self.schema.schema_id() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Schema id {} in manifest metadata doesn't match the id {} of the embedded schema",
+                    self.schema_id,
+                    self.schema.schema_id()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the schema of table at the time manifest was written
+    pub fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    /// Get the ID of schema used to write the manifest
+    pub fn schema_id(&self) -> SchemaId {
+        self.schema_id
+    }
+
+    /// Get the partition spec used to write manifest
+    pub fn partition_spec(&self) -> &PartitionSpec {
+        &self.partition_spec
+    }
+
+    /// Get the table format version
+    pub fn format_version(&self) -> &FormatVersion {
+        &self.format_version
+    }
+
+    /// Get the type of content files tracked by manifest
+    pub fn content(&self) -> &ManifestContentType {
+        &self.content
+    }
+
+    /// Get the custom key-value metadata, i.e. the keys not reserved by the spec
+    pub fn extra_metadata(&self) -> &HashMap<String, Vec<u8>> {
+        &self.extra_metadata
+    }
+}
+
+/// Parse the `format-version` value of the manifest metadata.
+///
+/// The value is normally a JSON number such as `2`, but some writers store it as a JSON
+/// string (`"2"`) or as a single raw byte, so fall back to those forms.
+fn parse_format_version(bs: &[u8]) -> Result<FormatVersion> {
+    if let Ok(format_version) = serde_json::from_slice::<FormatVersion>(bs) {
+        return Ok(format_version);
+    }
+
+    let version = std::str::from_utf8(bs)
+        .ok()
+        .and_then(|v| v.trim().trim_matches('"').parse::<u8>().ok())
+        .or(match bs {
+            [v] => Some(*v),
+            _ => None,
+        });
+    match version {
+        Some(1) => Ok(FormatVersion::V1),
+        Some(2) => Ok(FormatVersion::V2),
+        _ => Err(Error::new(
+            ErrorKind::DataInvalid,
+            format!(
+                "Fail to parse format version in manifest metadata: {}",
+                String::from_utf8_lossy(bs)
+            ),
+        )),
+    }
+}
+
+/// Parse the `content` value of the manifest metadata.
+///
+/// The value is normally the content name, `data` or `deletes`, but some writers store
+/// the numeric content code, `0` or `1`, instead.
+fn parse_content(bs: &[u8]) -> Result<ManifestContentType> {
+    let v = String::from_utf8_lossy(bs);
+    match v.trim().parse::<i32>() {
+        Ok(code) => ManifestContentType::try_from(code),
+        Err(_) => v.parse(),
+    }
+}
+
+/// Writer of the canonical encoding hashed by [`Manifest::content_hash`].
+#[derive(Default)]
+struct CanonicalEncoder(Vec<u8>);
+
+impl CanonicalEncoder {
+    fn u64(&mut self, v: u64) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn i64(&mut self, v: i64) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn i32(&mut self, v: i32) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn bytes(&mut self, v: &[u8]) {
+        self.u64(v.len() as u64);
+        self.0.extend_from_slice(v);
+    }
+
+    fn option<T>(&mut self, v: Option<T>, f: impl FnOnce(&mut Self, T)) {
+        match v {
+            None => self.0.push(0),
+            Some(v) => {
+                self.0.push(1);
+                f(self, v);
+            }
+        }
+    }
+
+    fn list<T>(&mut self, v: impl IntoIterator<Item = T>, mut f: impl FnMut(&mut Self, T)) {
+        let v = v.into_iter().collect_vec();
+        self.u64(v.len() as u64);
+        for item in v {
+            f(self, item);
+        }
+    }
+
+    fn literal(&mut self, literal: &PrimitiveLiteral) {
+        match literal {
+            PrimitiveLiteral::Boolean(v) => {
+                self.0.push(0);
+                self.0.push(*v as u8);
+            }
+            PrimitiveLiteral::Int(v) => {
+                self.0.push(1);
+                self.i32(*v);
+            }
+            PrimitiveLiteral::Long(v) => {
+                self.0.push(2);
+                self.i64(*v);
+            }
+            PrimitiveLiteral::Float(v) => {
+                self.0.push(3);
+                self.0.extend_from_slice(&v.to_le_bytes());
+            }
+            PrimitiveLiteral::Double(v) => {
+                self.0.push(4);
+                self.0.extend_from_slice(&v.to_le_bytes());
+            }
+            PrimitiveLiteral::String(v) => {
+                self.0.push(5);
+                self.bytes(v.as_bytes());
+            }
+            PrimitiveLiteral::Binary(v) => {
+                self.0.push(6);
+                self.bytes(v);
+            }
+            PrimitiveLiteral::Int128(v) => {
+                self.0.push(7);
+                self.0.extend_from_slice(&v.to_le_bytes());
+            }
+            PrimitiveLiteral::UInt128(v) => {
+                self.0.push(8);
+                self.0.extend_from_slice(&v.to_le_bytes());
+            }
+            PrimitiveLiteral::AboveMax => self.0.push(9),
+            PrimitiveLiteral::BelowMin => self.0.push(10),
+        }
+    }
+
+    fn value(&mut self, value: &Literal) {
+        match value {
+            Literal::Primitive(literal) => self.literal(literal),
+            Literal::Struct(fields) => {
+                self.0.push(11);
+                self.list(fields.iter(), |e, v| e.option(v, Self::value));
+            }
+            Literal::List(items) => {
+                self.0.push(12);
+                self.list(items, |e, v| e.option(v.as_ref(), Self::value));
+            }
+            Literal::Map(map) => {
+                self.0.push(13);
+                let pairs = map.clone().into_iter().map(|(key, value)| {
+                    let mut e = Self::default();
+                    e.value(&key);
+                    e.option(value.as_ref(), Self::value);
+                    e.0
+                });
+                self.list(pairs.sorted(), |e, pair| e.0.extend_from_slice(&pair));
+            }
+        }
+    }
+}
+
+/// Reference to [`ManifestEntry`].
+pub type ManifestEntryRef = Arc<ManifestEntry>;
+
+/// A manifest is an immutable Avro file that lists data files or delete
+/// files, along with each file’s partition data tuple, metrics, and tracking
+/// information.
+#[derive(Debug, PartialEq, Eq, Clone, TypedBuilder)]
+pub struct ManifestEntry {
+    /// field: 0
+    ///
+    /// Used to track additions and deletions.
+    status: ManifestStatus,
+    /// field id: 1
+    ///
+    /// Snapshot id where the file was added, or deleted if status is 2.
+    /// Inherited when null.
+    #[builder(default, setter(strip_option(fallback = snapshot_id_opt)))]
+    snapshot_id: Option<i64>,
+    /// field id: 3
+    ///
+    /// Data sequence number of the file.
+    /// Inherited when null and status is 1 (added).
+    #[builder(default, setter(strip_option(fallback = sequence_number_opt)))]
+    sequence_number: Option<i64>,
+    /// field id: 4
+    ///
+    /// File sequence number indicating when the file was added.
+    /// Inherited when null and status is 1 (added).
+    #[builder(default, setter(strip_option(fallback = file_sequence_number_opt)))]
+    file_sequence_number: Option<i64>,
+    /// field id: 2
+    ///
+    /// File path, partition tuple, metrics, …
+    data_file: DataFile,
+}
+
+impl ManifestEntry {
+    /// Check if this manifest entry is deleted.
+    pub fn is_alive(&self) -> bool {
+        matches!(
+            self.status,
+            ManifestStatus::Added | ManifestStatus::Existing
+        )
+    }
+
+    /// Status of this manifest entry
+    pub fn status(&self) -> ManifestStatus {
+        self.status
+    }
+
+    /// Content type of this manifest entry.
+    #[inline]
+    pub fn content_type(&self) -> DataContentType {
+        self.data_file.content
+    }
+
+    /// File format of this manifest entry.
+    #[inline]
+    pub fn file_format(&self) -> DataFileFormat {
+        self.data_file.file_format
+    }
+
+    /// Data file path of this manifest entry.
+    #[inline]
+    pub fn file_path(&self) -> &str {
+        &self.data_file.file_path
+    }
+
+    /// Data file record count of the manifest entry.
+    #[inline]
+    pub fn record_count(&self) -> u64 {
+        self.data_file.record_count
+    }
+
+    /// Partition tuple of the data file of this manifest entry.
+    #[inline]
+    pub fn partition(&self) -> &Struct {
+        &self.data_file.partition
+    }
+
+    /// Path of the data file the delete file of this entry applies to, if it's scoped to
+    /// a single data file.
+    #[inline]
+    pub fn referenced_data_file(&self) -> Option<&str> {
+        self.data_file.referenced_data_file()
+    }
+
+    /// Inherit data from manifest list, such as snapshot id, sequence number.
+    pub(crate) fn inherit_data(&mut self, snapshot_entry: &ManifestFile) {
+        if self.snapshot_id.is_none() {
+            self.snapshot_id = Some(snapshot_entry.added_snapshot_id);
+        }
+
+        if self.sequence_number.is_none()
+            && (self.status == ManifestStatus::Added
+                || snapshot_entry.sequence_number == INITIAL_SEQUENCE_NUMBER)
+        {
+            self.sequence_number = Some(snapshot_entry.sequence_number);
+        }
+
+        if self.file_sequence_number.is_none()
+            && (self.status == ManifestStatus::Added
+                || snapshot_entry.sequence_number == INITIAL_SEQUENCE_NUMBER)
+        {
+            self.file_sequence_number = Some(snapshot_entry.sequence_number);
+        }
+    }
+
+    /// Snapshot id
+    #[inline]
+    pub fn snapshot_id(&self) -> Option<i64> {
+        self.snapshot_id
+    }
+
+    /// Data sequence number.
+    #[inline]
+    pub fn sequence_number(&self) -> Option<i64> {
+        self.sequence_number
+    }
+
+    /// File sequence number, i.e. the sequence number of the snapshot that added the file.
+    #[inline]
+    pub fn file_sequence_number(&self) -> Option<i64> {
+        self.file_sequence_number
+    }
+
+    /// File size in bytes.
+    #[inline]
+    pub fn file_size_in_bytes(&self) -> u64 {
+        self.data_file.file_size_in_bytes
+    }
+
+    /// get a reference to the actual data file
+    #[inline]
+    pub fn data_file(&self) -> &DataFile {
+        &self.data_file
+    }
+
+    /// Canonical encoding of this entry, see [`Manifest::content_hash`].
+    fn canonical_encoding(&self) -> Vec<u8> {
+        let data_file = &self.data_file;
+        let mut e = CanonicalEncoder::default();
+        e.bytes(data_file.file_path.as_bytes());
+        e.i32(self.status as i32);
+        e.option(self.snapshot_id, |e, v| e.i64(v));
+        e.option(self.sequence_number, |e, v| e.i64(v));
+        e.option(self.file_sequence_number, |e, v| e.i64(v));
+        e.i32(data_file.content as i32);
+        e.bytes(data_file.file_format.to_string().as_bytes());
+        e.u64(data_file.partition.iter().len() as u64);
+        for value in data_file.partition.iter() {
+            e.option(value, CanonicalEncoder::value);
+        }
+        e.u64(data_file.record_count);
+        e.u64(data_file.file_size_in_bytes);
+        e.option(data_file.block_size_in_bytes, |e, v| e.i64(v));
+        for counts in [
+            &data_file.column_sizes,
+            &data_file.value_counts,
+            &data_file.null_value_counts,
+            &data_file.nan_value_counts,
+        ] {
+            e.list(counts.iter().sorted(), |e, (k, v)| {
+                e.i32(*k);
+                e.u64(*v);
+            });
+        }
+        for bounds in [&data_file.lower_bounds, &data_file.upper_bounds] {
+            e.list(sorted_bounds(bounds), |e, (k, v)| {
+                e.i32(k);
+                e.literal(v.literal());
+            });
+        }
+        e.option(data_file.key_metadata.as_deref(), |e, v| e.bytes(v));
+        e.list(&data_file.split_offsets, |e, v| e.i64(*v));
+        e.list(&data_file.equality_ids, |e, v| e.i32(*v));
+        e.option(data_file.sort_order_id, |e, v| e.i32(v));
+        e.option(data_file.referenced_data_file.as_deref(), |e, v| {
+            e.bytes(v.as_bytes())
+        });
+        e.i32(data_file.partition_spec_id);
+        e.0
+    }
+
+    /// Returns a copy of this entry whose data file carries no column level
+    /// statistics. See [`DataFile::without_stats`] for the fields dropped.
+    pub fn without_stats(&self) -> ManifestEntry {
+        ManifestEntry {
+            status: self.status,
+            snapshot_id: self.snapshot_id,
+            sequence_number: self.sequence_number,
+            file_sequence_number: self.file_sequence_number,
+            data_file: self.data_file.without_stats(),
+        }
+    }
+}
+
+/// Used to track additions and deletions in ManifestEntry.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ManifestStatus {
+    /// Value: 0
+    Existing = 0,
+    /// Value: 1
+    Added = 1,
+    /// Value: 2
+    ///
+    /// Deletes are informational only and not used in scans.
+    Deleted = 2,
+}
+
+impl ManifestStatus {
+    /// Lowercase name of this status, e.g. `"added"`, as a static label for
+    /// structured logging.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ManifestStatus::Existing => "existing",
+            ManifestStatus::Added => "added",
+            ManifestStatus::Deleted => "deleted",
+        }
+    }
+}
+
+impl TryFrom<i32> for ManifestStatus {
+    type Error = Error;
+
+    fn try_from(v: i32) -> Result<ManifestStatus> {
+        match v {
+            0 => Ok(ManifestStatus::Existing),
+            1 => Ok(ManifestStatus::Added),
+            2 => Ok(ManifestStatus::Deleted),
+            _ => Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!("manifest status {} is invalid", v),
+            )),
+        }
+    }
+}
+
+/// Data file carries data file path, partition tuple, metrics, …
+#[derive(Debug, PartialEq, Clone, Eq, Builder)]
+pub struct DataFile {
+    /// field id: 134
+    ///
+    /// Type of content stored by the data file: data, equality deletes,
+    /// or position deletes (all v1 files are data files)
+    pub(crate) content: DataContentType,
+    /// field id: 100
+    ///
+    /// Full URI for the file with FS scheme
+    pub(crate) file_path: String,
+    /// field id: 101
+    ///
+    /// String file format name, avro, orc or parquet
+    pub(crate) file_format: DataFileFormat,
+    /// field id: 102
+    ///
+    /// Partition data tuple, schema based on the partition spec output using
+    /// partition field ids for the struct field ids
+    pub(crate) partition: Struct,
+    /// field id: 103
+    ///
+    /// Number of records in this file
+    pub(crate) record_count: u64,
+    /// field id: 104
+    ///
+    /// Total file size in bytes
+    pub(crate) file_size_in_bytes: u64,
+    /// field id: 105
+    ///
+    /// Deprecated block size of the file, only present in format version 1. It's
+    /// populated when reading v1 manifests and never written to v2 manifests.
+    #[builder(default)]
+    pub(crate) block_size_in_bytes: Option<i64>,
+    /// field id: 108
+    /// key field id: 117
+    /// value field id: 118
+    ///
+    /// Map from column id to the total size on disk of all regions that
+    /// store the column. Does not include bytes necessary to read other
+    /// columns, like footers. Leave null for row-oriented formats (Avro)
+    #[builder(default)]
+    pub(crate) column_sizes: HashMap<i32, u64>,
+    /// field id: 109
+    /// key field id: 119
+    /// value field id: 120
+    ///
+    /// Map from column id to number of values in the column (including null
+    /// and NaN values)
+    #[builder(default)]
+    pub(crate) value_counts: HashMap<i32, u64>,
+    /// field id: 110
+    /// key field id: 121
+    /// value field id: 122
+    ///
+    /// Map from column id to number of null values in the column
+    #[builder(default)]
+    pub(crate) null_value_counts: HashMap<i32, u64>,
+    /// field id: 137
+    /// key field id: 138
+    /// value field id: 139
+    ///
+    /// Map from column id to number of NaN values in the column
+    #[builder(default)]
+    pub(crate) nan_value_counts: HashMap<i32, u64>,
+    /// field id: 125
+    /// key field id: 126
+    /// value field id: 127
+    ///
+    /// Map from column id to lower bound in the column serialized as binary.
+    /// Each value must be less than or equal to all non-null, non-NaN values
+    /// in the column for the file.
+    ///
+    /// Reference:
+    ///
+    /// - [Binary single-value serialization](https://iceberg.apache.org/spec/#binary-single-value-serialization)
+    #[builder(default)]
+    pub(crate) lower_bounds: HashMap<i32, Datum>,
+    /// field id: 128
+    /// key field id: 129
+    /// value field id: 130
+    ///
+    /// Map from column id to upper bound in the column serialized as binary.
+    /// Each value must be greater than or equal to all non-null, non-Nan
+    /// values in the column for the file.
+    ///
+    /// Reference:
+    ///
+    /// - [Binary single-value serialization](https://iceberg.apache.org/spec/#binary-single-value-serialization)
+    #[builder(default)]
+    pub(crate) upper_bounds: HashMap<i32, Datum>,
+    /// field id: 131
+    ///
+    /// Implementation-specific key metadata for encryption
+    #[builder(default)]
+    pub(crate) key_metadata: Option<Vec<u8>>,
+    /// field id: 132
+    /// element field id: 133
+    ///
+    /// Split offsets for the data file. For example, all row group offsets
+    /// in a Parquet file. Must be sorted ascending
+    #[builder(default)]
+    pub(crate) split_offsets: Vec<i64>,
+    /// field id: 135
+    /// element field id: 136
+    ///
+    /// Field ids used to determine row equality in equality delete files.
+    /// Required when content is EqualityDeletes and should be null
+    /// otherwise. Fields with ids listed in this column must be present
+    /// in the delete file
+    #[builder(default)]
+    pub(crate) equality_ids: Vec<i32>,
+    /// field id: 140
+    ///
+    /// ID representing sort order for this file.
+    ///
+    /// If sort order ID is missing or unknown, then the order is assumed to
+    /// be unsorted. Only data files and equality delete files should be
+    /// written with a non-null order id. Position deletes are required to be
+    /// sorted by file and position, not a table order, and should set sort
+    /// order id to null. Readers must ignore sort order id for position
+    /// delete files.
+    #[builder(default, setter(strip_option))]
+    pub(crate) sort_order_id: Option<i32>,
+    /// field id: 143
+    ///
+    /// Fully qualified location (URI with FS scheme) of a data file that all
+    /// deletes reference. Position delete metadata can use it to skip delete
+    /// files that don't apply to a data file. Should be null for data files.
+    #[builder(default)]
+    pub(crate) referenced_data_file: Option<String>,
+    /// This field is not included in spec. It is just store in memory representation used
+    /// in process.
+    pub(crate) partition_spec_id: i32,
+}
+
+impl DataFile {
+    /// Create a data file builder.
+    pub fn builder() -> DataFileBuilder {
+        DataFileBuilder::default()
+    }
+    /// Get the content type of the data file (data, equality deletes, or position deletes)
+    pub fn content_type(&self) -> DataContentType {
+        self.content
+    }
+    /// Get the file path as full URI with FS scheme
+    pub fn file_path(&self) -> &str {
+        &self.file_path
+    }
+    /// Get the file format of the file (avro, orc or parquet).
+    pub fn file_format(&self) -> DataFileFormat {
+        self.file_format
+    }
+    /// Get the partition values of the file.
+    pub fn partition(&self) -> &Struct {
+        &self.partition
+    }
+    /// Get the record count in the data file.
+    pub fn record_count(&self) -> u64 {
+        self.record_count
+    }
+    /// Get the file size in bytes.
+    pub fn file_size_in_bytes(&self) -> u64 {
+        self.file_size_in_bytes
+    }
+    /// Get the deprecated block size recorded by format version 1 manifests, `None` for v2.
+    pub fn block_size_in_bytes(&self) -> Option<i64> {
+        self.block_size_in_bytes
+    }
+    /// Get the column sizes.
+    /// Map from column id to the total size on disk of all regions that
+    /// store the column. Does not include bytes necessary to read other
+    /// columns, like footers. Null for row-oriented formats (Avro)
+    pub fn column_sizes(&self) -> &HashMap<i32, u64> {
+        &self.column_sizes
+    }
+    /// Get the columns value counts for the data file.
+    /// Map from column id to number of values in the column (including null
+    /// and NaN values)
+    pub fn value_counts(&self) -> &HashMap<i32, u64> {
+        &self.value_counts
+    }
+    /// Get the null value counts of the data file.
+    /// Map from column id to number of null values in the column
+    pub fn null_value_counts(&self) -> &HashMap<i32, u64> {
+        &self.null_value_counts
+    }
+    /// Get the nan value counts of the data file.
+    /// Map from column id to number of NaN values in the column
+    pub fn nan_value_counts(&self) -> &HashMap<i32, u64> {
+        &self.nan_value_counts
     }
     /// Get the lower bounds of the data file values per column.
     /// Map from column id to lower bound in the column serialized as binary.
     pub fn lower_bounds(&self) -> &HashMap<i32, Datum> {
         &self.lower_bounds
     }
-    /// Get the upper bounds of the data file values per column.
-    /// Map from column id to upper bound in the column serialized as binary.
-    pub fn upper_bounds(&self) -> &HashMap<i32, Datum> {
-        &self.upper_bounds
+    /// Get the upper bounds of the data file values per column.
+    /// Map from column id to upper bound in the column serialized as binary.
+    pub fn upper_bounds(&self) -> &HashMap<i32, Datum> {
+        &self.upper_bounds
+    }
+    /// Get the lower and upper bound of the column with given field id.
+    /// Returns `None` unless both bounds are present.
+    pub fn bounds(&self, field_id: i32) -> Option<(&Datum, &Datum)> {
+        Some((
+            self.lower_bounds.get(&field_id)?,
+            self.upper_bounds.get(&field_id)?,
+        ))
+    }
+    /// Get the lower bound of the column with given field id as `i64`.
+    ///
+    /// Only covers bounds of `int`, `long`, `date`, `time`, `timestamp`, `timestamptz`,
+    /// `timestamp_ns` and `timestamptz_ns` columns, using their physical value (e.g. days
+    /// from epoch for `date`). Returns `None` for other types or a missing bound.
+    pub fn lower_bound_i64(&self, field_id: i32) -> Option<i64> {
+        self.lower_bounds.get(&field_id).and_then(datum_as_i64)
+    }
+    /// Get the upper bound of the column with given field id as `i64`.
+    ///
+    /// Covers the same types as [`DataFile::lower_bound_i64`].
+    pub fn upper_bound_i64(&self, field_id: i32) -> Option<i64> {
+        self.upper_bounds.get(&field_id).and_then(datum_as_i64)
+    }
+    /// Validate the metrics of this data file against the given table schema.
+    ///
+    /// Every column in `nan_value_counts` must be a `float` or `double` field of
+    /// `schema`, otherwise the metrics were likely mapped to the wrong field ids.
+    ///
+    /// Suspicious but not invalid metrics are returned as diagnostics instead of
+    /// failing: columns with a lower or upper bound but no value count, and column
+    /// sizes of row-oriented Avro files.
+    pub fn validate(&self, schema: &Schema) -> Result<Vec<String>> {
+        for field_id in self.nan_value_counts.keys().copied().sorted() {
+            let field = schema.field_by_id(field_id).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::DataInvalid,
+                    format!("Nan value count for field id {field_id} not found in schema"),
+                )
+            })?;
+            if !matches!(
+                field.field_type.as_ref(),
+                Type::Primitive(PrimitiveType::Float | PrimitiveType::Double)
+            ) {
+                return Err(Error::new(
+                    ErrorKind::DataInvalid,
+                    format!(
+                        "Nan value count for field {} with id {field_id} of type {}, only float and double fields have nan values",
+                        field.name, field.field_type
+                    ),
+                ));
+            }
+        }
+
+        let mut diagnostics: Vec<_> = self
+            .lower_bounds
+            .keys()
+            .chain(self.upper_bounds.keys())
+            .filter(|field_id| !self.value_counts.contains_key(field_id))
+            .copied()
+            .sorted()
+            .dedup()
+            .map(|field_id| format!("Field id {field_id} has bounds but no value count"))
+            .collect();
+        if self.file_format == DataFileFormat::Avro && !self.column_sizes.is_empty() {
+            diagnostics.push(format!(
+                "Avro file has column sizes for {} columns, they should be null for row-oriented files",
+                self.column_sizes.len()
+            ));
+        }
+        Ok(diagnostics)
+    }
+    /// Validate the bounds of identity partition source columns against `partition`, the
+    /// partition tuple of this file under `partition_spec`.
+    ///
+    /// Every row of the file has the partition value in an identity partition source column,
+    /// so the column's lower and upper bounds must both contain that value, and a null
+    /// partition value means the column has no bounds at all. Inconsistencies are returned
+    /// as diagnostics, fields with other transforms are skipped.
+    pub fn validate_bounds_against_partition(
+        &self,
+        partition_spec: &PartitionSpec,
+        partition: &Struct,
+    ) -> Result<Vec<String>> {
+        if partition.fields().len() != partition_spec.fields().len() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Partition has {} values but partition spec {} has {} fields",
+                    partition.fields().len(),
+                    partition_spec.spec_id(),
+                    partition_spec.fields().len()
+                ),
+            ));
+        }
+
+        let mut diagnostics = vec![];
+        for (field, value) in partition_spec.fields().iter().zip(partition.iter()) {
+            if field.transform != Transform::Identity {
+                continue;
+            }
+            let source_id = field.source_id;
+            let lower = self.lower_bounds.get(&source_id);
+            let upper = self.upper_bounds.get(&source_id);
+            let Some(value) = value.and_then(|value| value.as_primitive_literal()) else {
+                if lower.is_some() || upper.is_some() {
+                    diagnostics.push(format!(
+                        "Field id {source_id} has bounds but partition {} is null",
+                        field.name
+                    ));
+                }
+                continue;
+            };
+            if let Some(lower) = lower {
+                let value = Datum::new(lower.data_type().clone(), value.clone());
+                if lower.partial_cmp(&value) == Some(std::cmp::Ordering::Greater) {
+                    diagnostics.push(format!(
+                        "Field id {source_id} has lower bound {lower} above partition {} value {value}",
+                        field.name
+                    ));
+                }
+            }
+            if let Some(upper) = upper {
+                let value = Datum::new(upper.data_type().clone(), value);
+                if upper.partial_cmp(&value) == Some(std::cmp::Ordering::Less) {
+                    diagnostics.push(format!(
+                        "Field id {source_id} has upper bound {upper} below partition {} value {value}",
+                        field.name
+                    ));
+                }
+            }
+        }
+        Ok(diagnostics)
+    }
+    /// The partition values of this data file by partition field name, with `None` for
+    /// null values.
+    ///
+    /// `schema` is needed to resolve the result types of the partition fields of
+    /// `partition_spec`. Fails if the partition tuple doesn't have a value for every
+    /// field of the spec.
+    pub fn partition_values(
+        &self,
+        partition_spec: &PartitionSpec,
+        schema: &Schema,
+    ) -> Result<BTreeMap<String, Option<Datum>>> {
+        let partition_type = partition_spec.partition_type(schema)?;
+        if self.partition.fields().len() != partition_type.fields().len() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Partition of data file {} has {} values but partition spec {} has {} fields",
+                    self.file_path,
+                    self.partition.fields().len(),
+                    partition_spec.spec_id(),
+                    partition_type.fields().len()
+                ),
+            ));
+        }
+
+        partition_type
+            .fields()
+            .iter()
+            .zip(self.partition.iter())
+            .map(|(field, value)| {
+                let value = value
+                    .map(|value| {
+                        let (Some(r#type), Some(literal)) = (
+                            field.field_type.as_primitive_type(),
+                            value.as_primitive_literal(),
+                        ) else {
+                            return Err(Error::new(
+                                ErrorKind::DataInvalid,
+                                format!("Partition field {} is not a primitive value", field.name),
+                            ));
+                        };
+                        Ok(Datum::new(r#type.clone(), literal))
+                    })
+                    .transpose()?;
+                Ok((field.name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Get the Implementation-specific key metadata for the data file.
+    pub fn key_metadata(&self) -> Option<&[u8]> {
+        self.key_metadata.as_deref()
+    }
+    /// Get the split offsets of the data file.
+    /// For example, all row group offsets in a Parquet file.
+    pub fn split_offsets(&self) -> &[i64] {
+        &self.split_offsets
+    }
+    /// Get the equality ids of the data file.
+    /// Field ids used to determine row equality in equality delete files.
+    /// null when content is not EqualityDeletes.
+    pub fn equality_ids(&self) -> &[i32] {
+        &self.equality_ids
+    }
+    /// Get the full names of the equality fields in `schema`, in the order of
+    /// [`DataFile::equality_ids`]. Fails if an equality id isn't a field of `schema`.
+    pub fn equality_field_names(&self, schema: &Schema) -> Result<Vec<String>> {
+        self.equality_ids
+            .iter()
+            .map(|field_id| {
+                schema
+                    .name_by_field_id(*field_id)
+                    .map(str::to_string)
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::DataInvalid,
+                            format!(
+                                "Equality field id {field_id} of {} not found in schema",
+                                self.file_path
+                            ),
+                        )
+                    })
+            })
+            .collect()
+    }
+    /// Get the sort order id of the data file.
+    /// Only data files and equality delete files should be
+    /// written with a non-null order id. Position deletes are required to be
+    /// sorted by file and position, not a table order, and should set sort
+    /// order id to null. Readers must ignore sort order id for position
+    /// delete files.
+    pub fn sort_order_id(&self) -> Option<i32> {
+        self.sort_order_id
+    }
+    /// Get the location of the data file referenced by this delete file, if any.
+    pub fn referenced_data_file(&self) -> Option<&str> {
+        self.referenced_data_file.as_deref()
+    }
+    /// Get the column sizes sorted by field id.
+    pub fn column_sizes_sorted(&self) -> Vec<(i32, u64)> {
+        sorted_counts(&self.column_sizes)
+    }
+    /// Get the value counts sorted by field id.
+    pub fn value_counts_sorted(&self) -> Vec<(i32, u64)> {
+        sorted_counts(&self.value_counts)
+    }
+    /// Get the null value counts sorted by field id.
+    pub fn null_value_counts_sorted(&self) -> Vec<(i32, u64)> {
+        sorted_counts(&self.null_value_counts)
+    }
+    /// Get the nan value counts sorted by field id.
+    pub fn nan_value_counts_sorted(&self) -> Vec<(i32, u64)> {
+        sorted_counts(&self.nan_value_counts)
+    }
+    /// Get the lower bounds sorted by field id.
+    pub fn lower_bounds_sorted(&self) -> Vec<(i32, &Datum)> {
+        sorted_bounds(&self.lower_bounds)
+    }
+    /// Get the upper bounds sorted by field id.
+    pub fn upper_bounds_sorted(&self) -> Vec<(i32, &Datum)> {
+        sorted_bounds(&self.upper_bounds)
+    }
+    /// Ids of the columns with any metric, i.e. the union of the keys of the column
+    /// sizes, value counts, null value counts, NaN value counts and bounds.
+    pub fn stat_column_ids(&self) -> BTreeSet<i32> {
+        self.column_sizes
+            .keys()
+            .chain(self.value_counts.keys())
+            .chain(self.null_value_counts.keys())
+            .chain(self.nan_value_counts.keys())
+            .chain(self.lower_bounds.keys())
+            .chain(self.upper_bounds.keys())
+            .copied()
+            .collect()
+    }
+    /// Returns a copy of this data file with `column_sizes`, `value_counts`,
+    /// `null_value_counts`, `nan_value_counts`, `lower_bounds` and
+    /// `upper_bounds` cleared, which shrinks the payload when per-column
+    /// stats are not needed. All other fields are kept as is.
+    pub fn without_stats(&self) -> DataFile {
+        DataFile {
+            content: self.content,
+            file_path: self.file_path.clone(),
+            file_format: self.file_format,
+            partition: self.partition.clone(),
+            record_count: self.record_count,
+            file_size_in_bytes: self.file_size_in_bytes,
+            block_size_in_bytes: self.block_size_in_bytes,
+            column_sizes: HashMap::new(),
+            value_counts: HashMap::new(),
+            null_value_counts: HashMap::new(),
+            nan_value_counts: HashMap::new(),
+            lower_bounds: HashMap::new(),
+            upper_bounds: HashMap::new(),
+            key_metadata: self.key_metadata.clone(),
+            split_offsets: self.split_offsets.clone(),
+            equality_ids: self.equality_ids.clone(),
+            sort_order_id: self.sort_order_id,
+            referenced_data_file: self.referenced_data_file.clone(),
+            partition_spec_id: self.partition_spec_id,
+        }
+    }
+    /// Returns this data file with its `column_sizes`, `value_counts`, `null_value_counts`,
+    /// `nan_value_counts`, `lower_bounds` and `upper_bounds` replaced, e.g. by metrics
+    /// recomputed for an existing file. All other fields are kept as is.
+    pub fn with_metrics(
+        self,
+        column_sizes: HashMap<i32, u64>,
+        value_counts: HashMap<i32, u64>,
+        null_value_counts: HashMap<i32, u64>,
+        nan_value_counts: HashMap<i32, u64>,
+        lower_bounds: HashMap<i32, Datum>,
+        upper_bounds: HashMap<i32, Datum>,
+    ) -> DataFile {
+        DataFile {
+            column_sizes,
+            value_counts,
+            null_value_counts,
+            nan_value_counts,
+            lower_bounds,
+            upper_bounds,
+            ..self
+        }
+    }
+
+    /// Decode a data file from an avro `data_file` record, e.g. as read by a custom avro
+    /// reader from a file written with [`DataFileAvroSchema`] or from the `data_file` field
+    /// of a manifest entry.
+    ///
+    /// Bounds are resolved against the field types of `schema`, and the partition tuple
+    /// against `partition_type`, the partition type of the spec with id `partition_spec_id`.
+    pub fn from_avro_value(
+        value: &apache_avro::types::Value,
+        schema: &Schema,
+        partition_type: &StructType,
+        partition_spec_id: i32,
+    ) -> Result<DataFile> {
+        from_value::<_serde::DataFile>(value)?.try_into(partition_spec_id, partition_type, schema)
+    }
+}
+
+fn sorted_counts(counts: &HashMap<i32, u64>) -> Vec<(i32, u64)> {
+    counts
+        .iter()
+        .map(|(k, v)| (*k, *v))
+        .sorted_by_key(|(k, _)| *k)
+        .collect()
+}
+
+fn sorted_bounds(bounds: &HashMap<i32, Datum>) -> Vec<(i32, &Datum)> {
+    bounds
+        .iter()
+        .map(|(k, v)| (*k, v))
+        .sorted_by_key(|(k, _)| *k)
+        .collect()
+}
+
+fn datum_as_i64(datum: &Datum) -> Option<i64> {
+    match (datum.data_type(), datum.literal()) {
+        (PrimitiveType::Int | PrimitiveType::Date, PrimitiveLiteral::Int(v)) => Some(i64::from(*v)),
+        (
+            PrimitiveType::Long
+            | PrimitiveType::Time
+            | PrimitiveType::Timestamp
+            | PrimitiveType::Timestamptz
+            | PrimitiveType::TimestampNs
+            | PrimitiveType::TimestamptzNs,
+            PrimitiveLiteral::Long(v),
+        ) => Some(*v),
+        _ => None,
+    }
+}
+
+/// Convert data files to avro bytes and write to writer.
+/// Return the bytes written.
+pub fn write_data_files_to_avro<W: Write>(
+    writer: &mut W,
+    data_files: impl IntoIterator<Item = DataFile>,
+    partition_type: &StructType,
+    version: FormatVersion,
+) -> Result<usize> {
+    write_data_files_to_avro_with_record_name(
+        writer,
+        data_files,
+        partition_type,
+        version,
+        "data_file",
+    )
+}
+
+/// Like [`write_data_files_to_avro`], naming the avro record `record_name`.
+///
+/// This is for consumers that expect a specific record name instead of `data_file`. A
+/// name containing dots, e.g. `com.example.DataFile`, also sets the namespace.
+///
+/// Readers resolve the record by its fields, so the result can still be read with
+/// [`read_data_files_from_avro`].
+pub fn write_data_files_to_avro_with_record_name<W: Write>(
+    writer: &mut W,
+    data_files: impl IntoIterator<Item = DataFile>,
+    partition_type: &StructType,
+    version: FormatVersion,
+    record_name: &str,
+) -> Result<usize> {
+    let avro_schema =
+        _const_schema::data_file_schema_with_name(partition_type, version, record_name)?;
+    let mut writer = AvroWriter::new(&avro_schema, writer);
+
+    for data_file in data_files {
+        let value = to_value(_serde::DataFile::try_from(data_file, partition_type, true)?)?
+            .resolve(&avro_schema)?;
+        writer.append(value)?;
+    }
+
+    Ok(writer.flush()?)
+}
+
+/// Avro schema of data files, used by [`DataFileAvroWriter`].
+#[derive(Debug, Clone)]
+pub struct DataFileAvroSchema {
+    avro_schema: AvroSchema,
+    partition_type: StructType,
+}
+
+impl DataFileAvroSchema {
+    /// Create the avro schema of data files with the given partition type and format version.
+    pub fn try_new(partition_type: &StructType, version: FormatVersion) -> Result<Self> {
+        let avro_schema = match version {
+            FormatVersion::V1 => _const_schema::data_file_schema_v1(partition_type)?,
+            FormatVersion::V2 => _const_schema::data_file_schema_v2(partition_type)?,
+        };
+        Ok(Self {
+            avro_schema,
+            partition_type: partition_type.clone(),
+        })
+    }
+}
+
+/// Streaming writer of data files to avro, for exports too large to build at once
+/// with [`write_data_files_to_avro`].
+///
+/// Appended data files are buffered in avro blocks, use [`DataFileAvroWriter::flush`]
+/// or [`DataFileAvroWriter::with_flush_interval`] to bound the buffered data.
+pub struct DataFileAvroWriter<'a, W: Write> {
+    schema: &'a DataFileAvroSchema,
+    writer: AvroWriter<'a, W>,
+    flush_interval: Option<usize>,
+    progress: Option<Box<dyn FnMut(usize) + 'a>>,
+    record_count: usize,
+    bytes_written: usize,
+}
+
+impl<'a, W: Write> DataFileAvroWriter<'a, W> {
+    /// Create a writer of data files with the given schema to `writer`.
+    pub fn new(schema: &'a DataFileAvroSchema, writer: W) -> Self {
+        Self {
+            schema,
+            writer: AvroWriter::new(&schema.avro_schema, writer),
+            flush_interval: None,
+            progress: None,
+            record_count: 0,
+            bytes_written: 0,
+        }
+    }
+
+    /// Flush after every `flush_interval` appended data files.
+    pub fn with_flush_interval(mut self, flush_interval: usize) -> Self {
+        self.flush_interval = Some(flush_interval);
+        self
+    }
+
+    /// Call `progress` with the number of data files written to the underlying writer so
+    /// far after every flush, whether done by [`DataFileAvroWriter::append`] every flush
+    /// interval, by [`DataFileAvroWriter::flush`] or by [`DataFileAvroWriter::finish`].
+    pub fn with_progress(mut self, progress: impl FnMut(usize) + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Append a data file.
+    pub fn append(&mut self, data_file: DataFile) -> Result<()> {
+        let value = to_value(_serde::DataFile::try_from(
+            data_file,
+            &self.schema.partition_type,
+            true,
+        )?)?
+        .resolve(&self.schema.avro_schema)?;
+        self.bytes_written += self.writer.append(value)?;
+        self.record_count += 1;
+        if self
+            .flush_interval
+            .is_some_and(|interval| interval > 0 && self.record_count % interval == 0)
+        {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flush the buffered data files to the underlying writer.
+    /// Return the bytes written by this flush.
+    pub fn flush(&mut self) -> Result<usize> {
+        let n = self.writer.flush()?;
+        self.bytes_written += n;
+        if let Some(progress) = &mut self.progress {
+            progress(self.record_count);
+        }
+        Ok(n)
+    }
+
+    /// Number of data files appended so far, e.g. for progress reporting.
+    pub fn record_count(&self) -> usize {
+        self.record_count
+    }
+
+    /// Flush the remaining data files and return the total bytes written.
+    pub fn finish(mut self) -> Result<usize> {
+        self.flush()?;
+        Ok(self.bytes_written)
+    }
+}
+
+/// Parse data files from avro bytes.
+pub fn read_data_files_from_avro<R: Read>(
+    reader: &mut R,
+    schema: &Schema,
+    partition_spec_id: i32,
+    partition_type: &StructType,
+    version: FormatVersion,
+) -> Result<Vec<DataFile>> {
+    let avro_schema = match version {
+        FormatVersion::V1 => _const_schema::data_file_schema_v1(partition_type).unwrap(),
+        FormatVersion::V2 => _const_schema::data_file_schema_v2(partition_type).unwrap(),
+    };
+
+    let reader = AvroReader::with_schema(&avro_schema, reader)?;
+    reader
+        .into_iter()
+        .map(|value| DataFile::from_avro_value(&value?, schema, partition_type, partition_spec_id))
+        .collect::<Result<Vec<_>>>()
+}
+
+/// Parse data files from avro bytes, only decoding the optional fields whose field ids
+/// are in `field_ids`.
+///
+/// Required fields, like `file_path` and `record_count`, are always decoded. Skipped
+/// fields are left empty in the returned data files, e.g. leaving out the stats maps
+/// and bounds makes metadata-only scans cheaper.
+pub fn read_data_files_from_avro_projected<R: Read>(
+    reader: &mut R,
+    schema: &Schema,
+    partition_spec_id: i32,
+    partition_type: &StructType,
+    version: FormatVersion,
+    field_ids: &HashSet<i32>,
+) -> Result<Vec<DataFile>> {
+    let avro_schema =
+        _const_schema::data_file_schema_projected(partition_type, version, field_ids)?;
+
+    let reader = AvroReader::with_schema(&avro_schema, reader)?;
+    reader
+        .into_iter()
+        .map(|value| {
+            from_value::<_serde::DataFile>(&value?)?.try_into(
+                partition_spec_id,
+                partition_type,
+                schema,
+            )
+        })
+        .collect::<Result<Vec<_>>>()
+}
+
+/// Type of content stored by the data file: data, equality deletes, or
+/// position deletes (all v1 files are data files)
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum DataContentType {
+    /// value: 0
+    Data = 0,
+    /// value: 1
+    PositionDeletes = 1,
+    /// value: 2
+    EqualityDeletes = 2,
+}
+
+impl DataContentType {
+    /// Kebab-case name of this content type, e.g. `"position-deletes"`, as a static
+    /// label for structured logging.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataContentType::Data => "data",
+            DataContentType::PositionDeletes => "position-deletes",
+            DataContentType::EqualityDeletes => "equality-deletes",
+        }
+    }
+}
+
+impl TryFrom<i32> for DataContentType {
+    type Error = Error;
+
+    fn try_from(v: i32) -> Result<DataContentType> {
+        match v {
+            0 => Ok(DataContentType::Data),
+            1 => Ok(DataContentType::PositionDeletes),
+            2 => Ok(DataContentType::EqualityDeletes),
+            _ => Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!("data content type {} is invalid", v),
+            )),
+        }
+    }
+}
+
+/// Format of this data.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, SerializeDisplay, DeserializeFromStr)]
+pub enum DataFileFormat {
+    /// Avro file format: <https://avro.apache.org/>
+    Avro,
+    /// Orc file format: <https://orc.apache.org/>
+    Orc,
+    /// Parquet file format: <https://parquet.apache.org/>
+    Parquet,
+}
+
+impl FromStr for DataFileFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "avro" => Ok(Self::Avro),
+            "orc" => Ok(Self::Orc),
+            "parquet" => Ok(Self::Parquet),
+            _ => Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!("Unsupported data file format: {}", s),
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for DataFileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataFileFormat::Avro => write!(f, "avro"),
+            DataFileFormat::Orc => write!(f, "orc"),
+            DataFileFormat::Parquet => write!(f, "parquet"),
+        }
+    }
+}
+
+mod _serde {
+    use std::collections::HashMap;
+
+    use serde_derive::{Deserialize, Serialize};
+    use serde_with::serde_as;
+
+    use super::ManifestEntry;
+    use crate::spec::{Datum, Literal, RawLiteral, Schema, Struct, StructType, Type};
+    use crate::{Error, ErrorKind};
+
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ManifestEntryV2 {
+        status: i32,
+        snapshot_id: Option<i64>,
+        sequence_number: Option<i64>,
+        file_sequence_number: Option<i64>,
+        data_file: DataFile,
+    }
+
+    impl ManifestEntryV2 {
+        pub fn try_from(value: ManifestEntry, partition_type: &StructType) -> Result<Self, Error> {
+            Ok(Self {
+                status: value.status as i32,
+                snapshot_id: value.snapshot_id,
+                sequence_number: value.sequence_number,
+                file_sequence_number: value.file_sequence_number,
+                data_file: DataFile::try_from(value.data_file, partition_type, false)?,
+            })
+        }
+
+        pub fn take_raw_stats(&mut self) -> RawStats {
+            self.data_file.take_raw_stats()
+        }
+
+        pub fn clamp_negative_record_count(&mut self) -> Option<(String, i64)> {
+            self.data_file.clamp_negative_record_count()
+        }
+
+        pub fn try_into(
+            self,
+            partition_spec_id: i32,
+            partition_type: &StructType,
+            schema: &Schema,
+        ) -> Result<ManifestEntry, Error> {
+            Ok(ManifestEntry {
+                status: self.status.try_into()?,
+                snapshot_id: self.snapshot_id,
+                sequence_number: self.sequence_number,
+                file_sequence_number: self.file_sequence_number,
+                data_file: self
+                    .data_file
+                    .try_into(partition_spec_id, partition_type, schema)?,
+            })
+        }
+    }
+
+    /// A manifest entry as read from a manifest of either format version.
+    pub(super) enum VersionedManifestEntry {
+        V1(ManifestEntryV1),
+        V2(ManifestEntryV2),
+    }
+
+    impl VersionedManifestEntry {
+        pub fn take_raw_stats(&mut self) -> RawStats {
+            match self {
+                Self::V1(entry) => entry.take_raw_stats(),
+                Self::V2(entry) => entry.take_raw_stats(),
+            }
+        }
+
+        pub fn clamp_negative_record_count(&mut self) -> Option<(String, i64)> {
+            match self {
+                Self::V1(entry) => entry.clamp_negative_record_count(),
+                Self::V2(entry) => entry.clamp_negative_record_count(),
+            }
+        }
+
+        pub fn try_into(
+            self,
+            partition_spec_id: i32,
+            partition_type: &StructType,
+            schema: &Schema,
+        ) -> Result<ManifestEntry, Error> {
+            match self {
+                Self::V1(entry) => entry.try_into(partition_spec_id, partition_type, schema),
+                Self::V2(entry) => entry.try_into(partition_spec_id, partition_type, schema),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct ManifestEntryV1 {
+        status: i32,
+        pub snapshot_id: i64,
+        data_file: DataFile,
+    }
+
+    impl ManifestEntryV1 {
+        pub fn try_from(value: ManifestEntry, partition_type: &StructType) -> Result<Self, Error> {
+            Ok(Self {
+                status: value.status as i32,
+                snapshot_id: value.snapshot_id.unwrap_or_default(),
+                data_file: DataFile::try_from(value.data_file, partition_type, true)?,
+            })
+        }
+
+        pub fn take_raw_stats(&mut self) -> RawStats {
+            self.data_file.take_raw_stats()
+        }
+
+        pub fn clamp_negative_record_count(&mut self) -> Option<(String, i64)> {
+            self.data_file.clamp_negative_record_count()
+        }
+
+        pub fn try_into(
+            self,
+            partition_spec_id: i32,
+            partition_type: &StructType,
+            schema: &Schema,
+        ) -> Result<ManifestEntry, Error> {
+            Ok(ManifestEntry {
+                status: self.status.try_into()?,
+                snapshot_id: Some(self.snapshot_id),
+                sequence_number: Some(0),
+                file_sequence_number: Some(0),
+                data_file: self
+                    .data_file
+                    .try_into(partition_spec_id, partition_type, schema)?,
+            })
+        }
+    }
+
+    #[serde_as]
+    #[derive(Serialize, Deserialize)]
+    pub(super) struct DataFile {
+        #[serde(default)]
+        content: i32,
+        file_path: String,
+        file_format: String,
+        partition: RawLiteral,
+        record_count: i64,
+        file_size_in_bytes: i64,
+        #[serde(
+            default,
+            deserialize_with = "deserialize_block_size_in_bytes",
+            skip_serializing_if = "Option::is_none"
+        )]
+        block_size_in_bytes: Option<i64>,
+        column_sizes: Option<Vec<I64Entry>>,
+        value_counts: Option<Vec<I64Entry>>,
+        null_value_counts: Option<Vec<I64Entry>>,
+        nan_value_counts: Option<Vec<I64Entry>>,
+        lower_bounds: Option<Vec<BytesEntry>>,
+        upper_bounds: Option<Vec<BytesEntry>>,
+        key_metadata: Option<serde_bytes::ByteBuf>,
+        split_offsets: Option<Vec<i64>>,
+        #[serde(default)]
+        equality_ids: Option<Vec<i32>>,
+        sort_order_id: Option<i32>,
+        #[serde(default)]
+        referenced_data_file: Option<String>,
+    }
+
+    impl DataFile {
+        pub fn try_from(
+            value: super::DataFile,
+            partition_type: &StructType,
+            is_version_1: bool,
+        ) -> Result<Self, Error> {
+            // Keep the block size read from a v1 manifest, it's required by the v1 schema.
+            let block_size_in_bytes = if is_version_1 {
+                Some(value.block_size_in_bytes.unwrap_or(0))
+            } else {
+                None
+            };
+            Ok(Self {
+                content: value.content as i32,
+                file_path: value.file_path,
+                file_format: value.file_format.to_string().to_ascii_uppercase(),
+                partition: RawLiteral::try_from(
+                    Literal::Struct(value.partition),
+                    &Type::Struct(partition_type.clone()),
+                )?,
+                record_count: value.record_count.try_into()?,
+                file_size_in_bytes: value.file_size_in_bytes.try_into()?,
+                block_size_in_bytes,
+                column_sizes: Some(to_i64_entry(value.column_sizes)?),
+                value_counts: Some(to_i64_entry(value.value_counts)?),
+                null_value_counts: Some(to_i64_entry(value.null_value_counts)?),
+                nan_value_counts: Some(to_i64_entry(value.nan_value_counts)?),
+                lower_bounds: Some(to_bytes_entry(value.lower_bounds)?),
+                upper_bounds: Some(to_bytes_entry(value.upper_bounds)?),
+                key_metadata: value.key_metadata.map(serde_bytes::ByteBuf::from),
+                split_offsets: Some(value.split_offsets),
+                equality_ids: Some(value.equality_ids),
+                sort_order_id: value.sort_order_id,
+                referenced_data_file: value.referenced_data_file,
+            })
+        }
+
+        pub fn try_into(
+            self,
+            partition_spec_id: i32,
+            partition_type: &StructType,
+            schema: &Schema,
+        ) -> Result<super::DataFile, Error> {
+            let partition = self
+                .partition
+                .try_into(&Type::Struct(partition_type.clone()))?
+                .map(|v| {
+                    if let Literal::Struct(v) = v {
+                        Ok(v)
+                    } else {
+                        Err(Error::new(
+                            ErrorKind::DataInvalid,
+                            "partition value is not a struct",
+                        ))
+                    }
+                })
+                .transpose()?
+                .unwrap_or(Struct::empty());
+            Ok(super::DataFile {
+                content: self.content.try_into()?,
+                file_path: self.file_path,
+                file_format: self.file_format.parse()?,
+                partition,
+                record_count: self.record_count.try_into()?,
+                file_size_in_bytes: self.file_size_in_bytes.try_into()?,
+                block_size_in_bytes: self.block_size_in_bytes,
+                column_sizes: self
+                    .column_sizes
+                    .map(|v| parse_i64_entry(&v))
+                    .transpose()?
+                    .unwrap_or_default(),
+                value_counts: self
+                    .value_counts
+                    .map(|v| parse_i64_entry(&v))
+                    .transpose()?
+                    .unwrap_or_default(),
+                null_value_counts: self
+                    .null_value_counts
+                    .map(|v| parse_i64_entry(&v))
+                    .transpose()?
+                    .unwrap_or_default(),
+                nan_value_counts: self
+                    .nan_value_counts
+                    .map(|v| parse_i64_entry(&v))
+                    .transpose()?
+                    .unwrap_or_default(),
+                lower_bounds: self
+                    .lower_bounds
+                    .map(|v| parse_bytes_entry(&v, schema))
+                    .transpose()?
+                    .unwrap_or_default(),
+                upper_bounds: self
+                    .upper_bounds
+                    .map(|v| parse_bytes_entry(&v, schema))
+                    .transpose()?
+                    .unwrap_or_default(),
+                key_metadata: self.key_metadata.map(|v| v.to_vec()),
+                split_offsets: self.split_offsets.unwrap_or_default(),
+                equality_ids: self.equality_ids.unwrap_or_default(),
+                sort_order_id: self.sort_order_id,
+                referenced_data_file: self.referenced_data_file,
+                partition_spec_id,
+            })
+        }
+
+        /// Set a negative record count to 0, returning the file path and the original
+        /// record count when it was negative.
+        fn clamp_negative_record_count(&mut self) -> Option<(String, i64)> {
+            if self.record_count >= 0 {
+                return None;
+            }
+            let record_count = std::mem::take(&mut self.record_count);
+            Some((self.file_path.clone(), record_count))
+        }
+
+        /// Move the column level statistics out, leaving them unset so that
+        /// `try_into` produces a data file without stats.
+        fn take_raw_stats(&mut self) -> RawStats {
+            RawStats {
+                column_sizes: self.column_sizes.take().unwrap_or_default(),
+                value_counts: self.value_counts.take().unwrap_or_default(),
+                null_value_counts: self.null_value_counts.take().unwrap_or_default(),
+                nan_value_counts: self.nan_value_counts.take().unwrap_or_default(),
+                lower_bounds: self.lower_bounds.take().unwrap_or_default(),
+                upper_bounds: self.upper_bounds.take().unwrap_or_default(),
+            }
+        }
+    }
+
+    /// Column level statistics of a data file, kept as read from avro.
+    #[derive(Debug)]
+    pub(super) struct RawStats {
+        column_sizes: Vec<I64Entry>,
+        value_counts: Vec<I64Entry>,
+        null_value_counts: Vec<I64Entry>,
+        nan_value_counts: Vec<I64Entry>,
+        lower_bounds: Vec<BytesEntry>,
+        upper_bounds: Vec<BytesEntry>,
+    }
+
+    impl RawStats {
+        /// Decode the statistics into `data_file`.
+        pub fn decode_into(
+            &self,
+            data_file: &mut super::DataFile,
+            schema: &Schema,
+        ) -> Result<(), Error> {
+            data_file.column_sizes = parse_i64_entry(&self.column_sizes)?;
+            data_file.value_counts = parse_i64_entry(&self.value_counts)?;
+            data_file.null_value_counts = parse_i64_entry(&self.null_value_counts)?;
+            data_file.nan_value_counts = parse_i64_entry(&self.nan_value_counts)?;
+            data_file.lower_bounds = parse_bytes_entry(&self.lower_bounds, schema)?;
+            data_file.upper_bounds = parse_bytes_entry(&self.upper_bounds, schema)?;
+            Ok(())
+        }
+    }
+
+    #[serde_as]
+    #[derive(Debug, Serialize, Deserialize)]
+    #[cfg_attr(test, derive(PartialEq, Eq))]
+    struct BytesEntry {
+        key: i32,
+        value: serde_bytes::ByteBuf,
+    }
+
+    /// The v1 schema declares `block_size_in_bytes` as a required `long`, which the avro
+    /// deserializer refuses to read into an `Option`, so accept a bare long as well as a
+    /// union. The field is absent from the v2 schema, where it's read as `None`.
+    fn deserialize_block_size_in_bytes<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+    where D: serde::Deserializer<'de> {
+        struct BlockSizeVisitor;
+
+        impl serde::de::Visitor<'_> for BlockSizeVisitor {
+            type Value = Option<i64>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a long or an optional long")
+            }
+
+            fn visit_i32<E>(self, v: i32) -> Result<Self::Value, E> {
+                Ok(Some(v.into()))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Some(v))
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+
+            fn visit_none<E>(self) -> Result<Self::Value, E> {
+                Ok(None)
+            }
+        }
+
+        deserializer.deserialize_any(BlockSizeVisitor)
+    }
+
+    fn parse_bytes_entry(v: &[BytesEntry], schema: &Schema) -> Result<HashMap<i32, Datum>, Error> {
+        let mut m = HashMap::with_capacity(v.len());
+        for entry in v {
+            // We ignore the entry if the field is not found in the schema, due to schema evolution.
+            if let Some(field) = schema.field_by_id(entry.key) {
+                let data_type = field
+                    .field_type
+                    .as_primitive_type()
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::DataInvalid,
+                            format!("field {} is not a primitive type", field.name),
+                        )
+                    })?
+                    .clone();
+                m.insert(entry.key, Datum::try_from_bytes(&entry.value, data_type)?);
+            }
+        }
+        Ok(m)
+    }
+
+    fn to_bytes_entry(v: impl IntoIterator<Item = (i32, Datum)>) -> Result<Vec<BytesEntry>, Error> {
+        let iter = v.into_iter();
+        // Reserve the capacity to the lower bound.
+        let mut bs = Vec::with_capacity(iter.size_hint().0);
+        for (k, d) in iter {
+            bs.push(BytesEntry {
+                key: k,
+                value: d.to_bytes()?,
+            });
+        }
+        Ok(bs)
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[cfg_attr(test, derive(PartialEq, Eq))]
+    struct I64Entry {
+        key: i32,
+        value: i64,
+    }
+
+    fn parse_i64_entry(v: &[I64Entry]) -> Result<HashMap<i32, u64>, Error> {
+        let mut m = HashMap::with_capacity(v.len());
+        for entry in v {
+            // We ignore the entry if it's value is negative since these entries are supposed to be used for
+            // counting, which should never be negative.
+            if let Ok(v) = entry.value.try_into() {
+                m.insert(entry.key, v);
+            }
+        }
+        Ok(m)
+    }
+
+    fn to_i64_entry(entries: HashMap<i32, u64>) -> Result<Vec<I64Entry>, Error> {
+        entries
+            .iter()
+            .map(|e| {
+                Ok(I64Entry {
+                    key: *e.0,
+                    value: (*e.1).try_into()?,
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::collections::HashMap;
+
+        use crate::spec::manifest::_serde::{parse_i64_entry, I64Entry};
+
+        #[test]
+        fn test_parse_negative_manifest_entry() {
+            let entries = vec![I64Entry { key: 1, value: -1 }, I64Entry {
+                key: 2,
+                value: 3,
+            }];
+
+            let ret = parse_i64_entry(&entries).unwrap();
+
+            let expected_ret = HashMap::from([(2, 3)]);
+            assert_eq!(ret, expected_ret, "Negative i64 entry should be ignored!");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::io::FileIOBuilder;
+    use crate::spec::{
+        ListType, Literal, MapType, NestedField, PrimitiveType, Struct, Transform, Type,
+    };
+
+    #[tokio::test]
+    async fn test_parse_manifest_v2_unpartition() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    // id v_int v_long v_float v_double v_varchar v_bool v_date v_timestamp v_decimal v_ts_ntz
+                    Arc::new(NestedField::optional(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "v_int",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    Arc::new(NestedField::optional(
+                        3,
+                        "v_long",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                    Arc::new(NestedField::optional(
+                        4,
+                        "v_float",
+                        Type::Primitive(PrimitiveType::Float),
+                    )),
+                    Arc::new(NestedField::optional(
+                        5,
+                        "v_double",
+                        Type::Primitive(PrimitiveType::Double),
+                    )),
+                    Arc::new(NestedField::optional(
+                        6,
+                        "v_varchar",
+                        Type::Primitive(PrimitiveType::String),
+                    )),
+                    Arc::new(NestedField::optional(
+                        7,
+                        "v_bool",
+                        Type::Primitive(PrimitiveType::Boolean),
+                    )),
+                    Arc::new(NestedField::optional(
+                        8,
+                        "v_date",
+                        Type::Primitive(PrimitiveType::Date),
+                    )),
+                    Arc::new(NestedField::optional(
+                        9,
+                        "v_timestamp",
+                        Type::Primitive(PrimitiveType::Timestamptz),
+                    )),
+                    Arc::new(NestedField::optional(
+                        10,
+                        "v_decimal",
+                        Type::Primitive(PrimitiveType::Decimal {
+                            precision: 36,
+                            scale: 10,
+                        }),
+                    )),
+                    Arc::new(NestedField::optional(
+                        11,
+                        "v_ts_ntz",
+                        Type::Primitive(PrimitiveType::Timestamp),
+                    )),
+                    Arc::new(NestedField::optional(
+                        12,
+                        "v_ts_ns_ntz",
+                        Type::Primitive(PrimitiveType::TimestampNs),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let metadata = ManifestMetadata {
+            schema_id: 0,
+            schema: schema.clone(),
+            partition_spec: PartitionSpec::builder(schema)
+                .with_spec_id(0)
+                .build()
+                .unwrap(),
+            content: ManifestContentType::Data,
+            format_version: FormatVersion::V2,
+            extra_metadata: HashMap::new(),
+        };
+        let mut entries = vec![
+                ManifestEntry {
+                    status: ManifestStatus::Added,
+                    snapshot_id: None,
+                    sequence_number: None,
+                    file_sequence_number: None,
+                    data_file: DataFile {content:DataContentType::Data,file_path:"s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),file_format:DataFileFormat::Parquet,partition:Struct::empty(),record_count:1,file_size_in_bytes:5442,block_size_in_bytes:None,column_sizes:HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),value_counts:HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),null_value_counts:HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),nan_value_counts:HashMap::new(),lower_bounds:HashMap::new(),upper_bounds:HashMap::new(),key_metadata:None,split_offsets:vec![4],equality_ids:Vec::new(),sort_order_id:None, referenced_data_file: None, partition_spec_id: 0 }
+                }
+            ];
+
+        // write manifest to file
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer = ManifestWriterBuilder::new(
+            output_file,
+            Some(1),
+            vec![],
+            metadata.schema.clone(),
+            metadata.partition_spec.clone(),
+        )
+        .build_v2_data();
+        for entry in &entries {
+            writer.add_entry(entry.clone()).unwrap();
+        }
+        writer.write_manifest_file().await.unwrap();
+
+        // read back the manifest file and check the content
+        let actual_manifest =
+            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                .unwrap();
+        // The snapshot id is assigned when the entry is added to the manifest.
+        entries[0].snapshot_id = Some(1);
+        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+    }
+
+    #[tokio::test]
+    async fn test_parse_manifest_v2_partition() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "v_int",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    Arc::new(NestedField::optional(
+                        3,
+                        "v_long",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                    Arc::new(NestedField::optional(
+                        4,
+                        "v_float",
+                        Type::Primitive(PrimitiveType::Float),
+                    )),
+                    Arc::new(NestedField::optional(
+                        5,
+                        "v_double",
+                        Type::Primitive(PrimitiveType::Double),
+                    )),
+                    Arc::new(NestedField::optional(
+                        6,
+                        "v_varchar",
+                        Type::Primitive(PrimitiveType::String),
+                    )),
+                    Arc::new(NestedField::optional(
+                        7,
+                        "v_bool",
+                        Type::Primitive(PrimitiveType::Boolean),
+                    )),
+                    Arc::new(NestedField::optional(
+                        8,
+                        "v_date",
+                        Type::Primitive(PrimitiveType::Date),
+                    )),
+                    Arc::new(NestedField::optional(
+                        9,
+                        "v_timestamp",
+                        Type::Primitive(PrimitiveType::Timestamptz),
+                    )),
+                    Arc::new(NestedField::optional(
+                        10,
+                        "v_decimal",
+                        Type::Primitive(PrimitiveType::Decimal {
+                            precision: 36,
+                            scale: 10,
+                        }),
+                    )),
+                    Arc::new(NestedField::optional(
+                        11,
+                        "v_ts_ntz",
+                        Type::Primitive(PrimitiveType::Timestamp),
+                    )),
+                    Arc::new(NestedField::optional(
+                        12,
+                        "v_ts_ns_ntz",
+                        Type::Primitive(PrimitiveType::TimestampNs),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let metadata = ManifestMetadata {
+            schema_id: 0,
+            schema: schema.clone(),
+            partition_spec: PartitionSpec::builder(schema)
+                .with_spec_id(0)
+                .add_partition_field("v_int", "v_int", Transform::Identity)
+                .unwrap()
+                .add_partition_field("v_long", "v_long", Transform::Identity)
+                .unwrap()
+                .build()
+                .unwrap(),
+            content: ManifestContentType::Data,
+            format_version: FormatVersion::V2,
+            extra_metadata: HashMap::new(),
+        };
+        let mut entries = vec![ManifestEntry {
+                status: ManifestStatus::Added,
+                snapshot_id: None,
+                sequence_number: None,
+                file_sequence_number: None,
+                data_file: DataFile {
+                    content: DataContentType::Data,
+                    file_format: DataFileFormat::Parquet,
+                    file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-378b56f5-5c52-4102-a2c2-f05f8a7cbe4a-00000.parquet".to_string(),
+                    partition: Struct::from_iter(
+                        vec![
+                            Some(Literal::int(1)),
+                            Some(Literal::long(1000)),
+                        ]
+                            .into_iter()
+                    ),
+                    record_count: 1,
+                    file_size_in_bytes: 5442,
+                    block_size_in_bytes: None,
+                    column_sizes: HashMap::from([
+                        (0, 73),
+                        (6, 34),
+                        (2, 73),
+                        (7, 61),
+                        (3, 61),
+                        (5, 62),
+                        (9, 79),
+                        (10, 73),
+                        (1, 61),
+                        (4, 73),
+                        (8, 73)
+                    ]),
+                    value_counts: HashMap::from([
+                        (4, 1),
+                        (5, 1),
+                        (2, 1),
+                        (0, 1),
+                        (3, 1),
+                        (6, 1),
+                        (8, 1),
+                        (1, 1),
+                        (10, 1),
+                        (7, 1),
+                        (9, 1)
+                    ]),
+                    null_value_counts: HashMap::from([
+                        (1, 0),
+                        (6, 0),
+                        (2, 0),
+                        (8, 0),
+                        (0, 0),
+                        (3, 0),
+                        (5, 0),
+                        (9, 0),
+                        (7, 0),
+                        (4, 0),
+                        (10, 0)
+                    ]),
+                    nan_value_counts: HashMap::new(),
+                    lower_bounds: HashMap::new(),
+                    upper_bounds: HashMap::new(),
+                    key_metadata: None,
+                    split_offsets: vec![4],
+                    equality_ids: vec![],
+                    sort_order_id: None,
+                    referenced_data_file: None,
+                    partition_spec_id: 0
+                },
+            }];
+
+        // write manifest to file and check the return manifest file.
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer = ManifestWriterBuilder::new(
+            output_file,
+            Some(2),
+            vec![],
+            metadata.schema.clone(),
+            metadata.partition_spec.clone(),
+        )
+        .build_v2_data();
+        for entry in &entries {
+            writer.add_entry(entry.clone()).unwrap();
+        }
+        let manifest_file = writer.write_manifest_file().await.unwrap();
+        assert_eq!(manifest_file.sequence_number, UNASSIGNED_SEQUENCE_NUMBER);
+        assert_eq!(
+            manifest_file.min_sequence_number,
+            UNASSIGNED_SEQUENCE_NUMBER
+        );
+
+        // read back the manifest file and check the content
+        let actual_manifest =
+            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                .unwrap();
+        // The snapshot id is assigned when the entry is added to the manifest.
+        entries[0].snapshot_id = Some(2);
+        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+    }
+
+    #[tokio::test]
+    async fn test_parse_manifest_v1_unpartition() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_schema_id(1)
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "data",
+                        Type::Primitive(PrimitiveType::String),
+                    )),
+                    Arc::new(NestedField::optional(
+                        3,
+                        "comment",
+                        Type::Primitive(PrimitiveType::String),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let metadata = ManifestMetadata {
+            schema_id: 1,
+            schema: schema.clone(),
+            partition_spec: PartitionSpec::builder(schema)
+                .with_spec_id(0)
+                .build()
+                .unwrap(),
+            content: ManifestContentType::Data,
+            format_version: FormatVersion::V1,
+            extra_metadata: HashMap::new(),
+        };
+        let mut entries = vec![ManifestEntry {
+                status: ManifestStatus::Added,
+                snapshot_id: Some(0),
+                sequence_number: Some(0),
+                file_sequence_number: Some(0),
+                data_file: DataFile {
+                    content: DataContentType::Data,
+                    file_path: "s3://testbucket/iceberg_data/iceberg_ctl/iceberg_db/iceberg_tbl/data/00000-7-45268d71-54eb-476c-b42c-942d880c04a1-00001.parquet".to_string(),
+                    file_format: DataFileFormat::Parquet,
+                    partition: Struct::empty(),
+                    record_count: 1,
+                    file_size_in_bytes: 875,
+                    block_size_in_bytes: None,
+                    column_sizes: HashMap::from([(1,47),(2,48),(3,52)]),
+                    value_counts: HashMap::from([(1,1),(2,1),(3,1)]),
+                    null_value_counts: HashMap::from([(1,0),(2,0),(3,0)]),
+                    nan_value_counts: HashMap::new(),
+                    lower_bounds: HashMap::from([(1,Datum::int(1)),(2,Datum::string("a")),(3,Datum::string("AC/DC"))]),
+                    upper_bounds: HashMap::from([(1,Datum::int(1)),(2,Datum::string("a")),(3,Datum::string("AC/DC"))]),
+                    key_metadata: None,
+                    split_offsets: vec![4],
+                    equality_ids: vec![],
+                    sort_order_id: Some(0),
+                    referenced_data_file: None,
+                    partition_spec_id: 0
+                }
+            }];
+
+        // write manifest to file
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer = ManifestWriterBuilder::new(
+            output_file,
+            Some(3),
+            vec![],
+            metadata.schema.clone(),
+            metadata.partition_spec.clone(),
+        )
+        .build_v1();
+        for entry in &entries {
+            writer.add_entry(entry.clone()).unwrap();
+        }
+        writer.write_manifest_file().await.unwrap();
+
+        // read back the manifest file and check the content
+        let actual_manifest =
+            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                .unwrap();
+        // The snapshot id is assigned when the entry is added to the manifest.
+        entries[0].snapshot_id = Some(3);
+        // v1 manifests record a block size of 0 when the data file has none.
+        entries[0].data_file.block_size_in_bytes = Some(0);
+        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+    }
+
+    #[tokio::test]
+    async fn test_parse_manifest_v1_partition() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "data",
+                        Type::Primitive(PrimitiveType::String),
+                    )),
+                    Arc::new(NestedField::optional(
+                        3,
+                        "category",
+                        Type::Primitive(PrimitiveType::String),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let metadata = ManifestMetadata {
+            schema_id: 0,
+            schema: schema.clone(),
+            partition_spec: PartitionSpec::builder(schema)
+                .add_partition_field("category", "category", Transform::Identity)
+                .unwrap()
+                .build()
+                .unwrap(),
+            content: ManifestContentType::Data,
+            format_version: FormatVersion::V1,
+            extra_metadata: HashMap::new(),
+        };
+        let mut entries = vec![
+                ManifestEntry {
+                    status: ManifestStatus::Added,
+                    snapshot_id: Some(0),
+                    sequence_number: Some(0),
+                    file_sequence_number: Some(0),
+                    data_file: DataFile {
+                        content: DataContentType::Data,
+                        file_path: "s3://testbucket/prod/db/sample/data/category=x/00010-1-d5c93668-1e52-41ac-92a6-bba590cbf249-00001.parquet".to_string(),
+                        file_format: DataFileFormat::Parquet,
+                        partition: Struct::from_iter(
+                            vec![
+                                Some(
+                                    Literal::string("x"),
+                                ),
+                            ]
+                                .into_iter()
+                        ),
+                        record_count: 1,
+                        file_size_in_bytes: 874,
+                        block_size_in_bytes: None,
+                        column_sizes: HashMap::from([(1, 46), (2, 48), (3, 48)]),
+                        value_counts: HashMap::from([(1, 1), (2, 1), (3, 1)]),
+                        null_value_counts: HashMap::from([(1, 0), (2, 0), (3, 0)]),
+                        nan_value_counts: HashMap::new(),
+                        lower_bounds: HashMap::from([
+                        (1, Datum::long(1)),
+                        (2, Datum::string("a")),
+                        (3, Datum::string("x"))
+                        ]),
+                        upper_bounds: HashMap::from([
+                        (1, Datum::long(1)),
+                        (2, Datum::string("a")),
+                        (3, Datum::string("x"))
+                        ]),
+                        key_metadata: None,
+                        split_offsets: vec![4],
+                        equality_ids: vec![],
+                        sort_order_id: Some(0),
+                        referenced_data_file: None,
+                        partition_spec_id: 0
+                    },
+                }
+            ];
+
+        // write manifest to file
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer = ManifestWriterBuilder::new(
+            output_file,
+            Some(2),
+            vec![],
+            metadata.schema.clone(),
+            metadata.partition_spec.clone(),
+        )
+        .build_v1();
+        for entry in &entries {
+            writer.add_entry(entry.clone()).unwrap();
+        }
+        let manifest_file = writer.write_manifest_file().await.unwrap();
+        assert_eq!(manifest_file.partitions.len(), 1);
+        assert_eq!(
+            manifest_file.partitions[0].lower_bound,
+            Some(Datum::string("x"))
+        );
+        assert_eq!(
+            manifest_file.partitions[0].upper_bound,
+            Some(Datum::string("x"))
+        );
+
+        // read back the manifest file and check the content
+        let actual_manifest =
+            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                .unwrap();
+        // The snapshot id is assigned when the entry is added to the manifest.
+        entries[0].snapshot_id = Some(2);
+        // v1 manifests record a block size of 0 when the data file has none.
+        entries[0].data_file.block_size_in_bytes = Some(0);
+        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+    }
+
+    #[tokio::test]
+    async fn test_parse_manifest_with_schema_evolution() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "v_int",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let metadata = ManifestMetadata {
+            schema_id: 0,
+            schema: schema.clone(),
+            partition_spec: PartitionSpec::builder(schema)
+                .with_spec_id(0)
+                .build()
+                .unwrap(),
+            content: ManifestContentType::Data,
+            format_version: FormatVersion::V2,
+            extra_metadata: HashMap::new(),
+        };
+        let entries = vec![ManifestEntry {
+                status: ManifestStatus::Added,
+                snapshot_id: None,
+                sequence_number: None,
+                file_sequence_number: None,
+                data_file: DataFile {
+                    content: DataContentType::Data,
+                    file_format: DataFileFormat::Parquet,
+                    file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-378b56f5-5c52-4102-a2c2-f05f8a7cbe4a-00000.parquet".to_string(),
+                    partition: Struct::empty(),
+                    record_count: 1,
+                    file_size_in_bytes: 5442,
+                    block_size_in_bytes: None,
+                    column_sizes: HashMap::from([
+                        (1, 61),
+                        (2, 73),
+                        (3, 61),
+                    ]),
+                    value_counts: HashMap::default(),
+                    null_value_counts: HashMap::default(),
+                    nan_value_counts: HashMap::new(),
+                    lower_bounds: HashMap::from([
+                        (1, Datum::long(1)),
+                        (2, Datum::int(2)),
+                        (3, Datum::string("x"))
+                    ]),
+                    upper_bounds: HashMap::from([
+                        (1, Datum::long(1)),
+                        (2, Datum::int(2)),
+                        (3, Datum::string("x"))
+                    ]),
+                    key_metadata: None,
+                    split_offsets: vec![4],
+                    equality_ids: vec![],
+                    sort_order_id: None,
+                    referenced_data_file: None,
+                    partition_spec_id: 0
+                },
+            }];
+
+        // write manifest to file
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer = ManifestWriterBuilder::new(
+            output_file,
+            Some(2),
+            vec![],
+            metadata.schema.clone(),
+            metadata.partition_spec.clone(),
+        )
+        .build_v2_data();
+        for entry in &entries {
+            writer.add_entry(entry.clone()).unwrap();
+        }
+        writer.write_manifest_file().await.unwrap();
+
+        // read back the manifest file and check the content
+        let actual_manifest =
+            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                .unwrap();
+
+        // Compared with original manifest, the lower_bounds and upper_bounds no longer has data for field 3, and
+        // other parts should be same.
+        // The snapshot id is assigned when the entry is added to the manifest.
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "v_int",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let expected_manifest = Manifest {
+            metadata: ManifestMetadata {
+                schema_id: 0,
+                schema: schema.clone(),
+                partition_spec: PartitionSpec::builder(schema).with_spec_id(0).build().unwrap(),
+                content: ManifestContentType::Data,
+                format_version: FormatVersion::V2,
+                extra_metadata: HashMap::new(),
+            },
+            entries: vec![Arc::new(ManifestEntry {
+                status: ManifestStatus::Added,
+                snapshot_id: Some(2),
+                sequence_number: None,
+                file_sequence_number: None,
+                data_file: DataFile {
+                    content: DataContentType::Data,
+                    file_format: DataFileFormat::Parquet,
+                    file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-378b56f5-5c52-4102-a2c2-f05f8a7cbe4a-00000.parquet".to_string(),
+                    partition: Struct::empty(),
+                    record_count: 1,
+                    file_size_in_bytes: 5442,
+                    block_size_in_bytes: None,
+                    column_sizes: HashMap::from([
+                        (1, 61),
+                        (2, 73),
+                        (3, 61),
+                    ]),
+                    value_counts: HashMap::default(),
+                    null_value_counts: HashMap::default(),
+                    nan_value_counts: HashMap::new(),
+                    lower_bounds: HashMap::from([
+                        (1, Datum::long(1)),
+                        (2, Datum::int(2)),
+                    ]),
+                    upper_bounds: HashMap::from([
+                        (1, Datum::long(1)),
+                        (2, Datum::int(2)),
+                    ]),
+                    key_metadata: None,
+                    split_offsets: vec![4],
+                    equality_ids: vec![],
+                    sort_order_id: None,
+                    referenced_data_file: None,
+                    partition_spec_id: 0
+                },
+            })],
+        };
+
+        assert_eq!(actual_manifest, expected_manifest);
+    }
+
+    #[tokio::test]
+    async fn test_manifest_summary() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "time",
+                        Type::Primitive(PrimitiveType::Date),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "v_float",
+                        Type::Primitive(PrimitiveType::Float),
+                    )),
+                    Arc::new(NestedField::optional(
+                        3,
+                        "v_double",
+                        Type::Primitive(PrimitiveType::Double),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .with_spec_id(0)
+            .add_partition_field("time", "year_of_time", Transform::Year)
+            .unwrap()
+            .add_partition_field("v_float", "f", Transform::Identity)
+            .unwrap()
+            .add_partition_field("v_double", "d", Transform::Identity)
+            .unwrap()
+            .build()
+            .unwrap();
+        let metadata = ManifestMetadata {
+            schema_id: 0,
+            schema,
+            partition_spec,
+            content: ManifestContentType::Data,
+            format_version: FormatVersion::V2,
+            extra_metadata: HashMap::new(),
+        };
+        let entries = vec![
+                ManifestEntry {
+                    status: ManifestStatus::Added,
+                    snapshot_id: None,
+                    sequence_number: None,
+                    file_sequence_number: None,
+                    data_file: DataFile {
+                        content: DataContentType::Data,
+                        file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
+                        file_format: DataFileFormat::Parquet,
+                        partition: Struct::from_iter(
+                            vec![
+                                Some(Literal::int(2021)),
+                                Some(Literal::float(1.0)),
+                                Some(Literal::double(2.0)),
+                            ]
+                        ),
+                        record_count: 1,
+                        file_size_in_bytes: 5442,
+                        block_size_in_bytes: None,
+                        column_sizes: HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),
+                        value_counts: HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),
+                        null_value_counts: HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),
+                        nan_value_counts: HashMap::new(),
+                        lower_bounds: HashMap::new(),
+                        upper_bounds: HashMap::new(),
+                        key_metadata: None,
+                        split_offsets: vec![4],
+                        equality_ids: Vec::new(),
+                        sort_order_id: None,
+                        referenced_data_file: None,
+                        partition_spec_id: 0
+                    }
+                },
+                    ManifestEntry {
+                        status: ManifestStatus::Added,
+                        snapshot_id: None,
+                        sequence_number: None,
+                        file_sequence_number: None,
+                        data_file: DataFile {
+                            content: DataContentType::Data,
+                            file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
+                            file_format: DataFileFormat::Parquet,
+                            partition: Struct::from_iter(
+                                vec![
+                                    Some(Literal::int(1111)),
+                                    Some(Literal::float(15.5)),
+                                    Some(Literal::double(25.5)),
+                                ]
+                            ),
+                            record_count: 1,
+                            file_size_in_bytes: 5442,
+                            block_size_in_bytes: None,
+                            column_sizes: HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),
+                            value_counts: HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),
+                            null_value_counts: HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),
+                            nan_value_counts: HashMap::new(),
+                            lower_bounds: HashMap::new(),
+                            upper_bounds: HashMap::new(),
+                            key_metadata: None,
+                            split_offsets: vec![4],
+                            equality_ids: Vec::new(),
+                            sort_order_id: None,
+                            referenced_data_file: None,
+                            partition_spec_id: 0
+                        }
+                    },
+                    ManifestEntry {
+                        status: ManifestStatus::Added,
+                        snapshot_id: None,
+                        sequence_number: None,
+                        file_sequence_number: None,
+                        data_file: DataFile {
+                            content: DataContentType::Data,
+                            file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
+                            file_format: DataFileFormat::Parquet,
+                            partition: Struct::from_iter(
+                                vec![
+                                    Some(Literal::int(1211)),
+                                    Some(Literal::float(f32::NAN)),
+                                    Some(Literal::double(1.0)),
+                                ]
+                            ),
+                            record_count: 1,
+                            file_size_in_bytes: 5442,
+                            block_size_in_bytes: None,
+                            column_sizes: HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),
+                            value_counts: HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),
+                            null_value_counts: HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),
+                            nan_value_counts: HashMap::new(),
+                            lower_bounds: HashMap::new(),
+                            upper_bounds: HashMap::new(),
+                            key_metadata: None,
+                            split_offsets: vec![4],
+                            equality_ids: Vec::new(),
+                            sort_order_id: None,
+                            referenced_data_file: None,
+                            partition_spec_id: 0
+                        }
+                    },
+                    ManifestEntry {
+                        status: ManifestStatus::Added,
+                        snapshot_id: None,
+                        sequence_number: None,
+                        file_sequence_number: None,
+                        data_file: DataFile {
+                            content: DataContentType::Data,
+                            file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
+                            file_format: DataFileFormat::Parquet,
+                            partition: Struct::from_iter(
+                                vec![
+                                    Some(Literal::int(1111)),
+                                    None,
+                                    Some(Literal::double(11.0)),
+                                ]
+                            ),
+                            record_count: 1,
+                            file_size_in_bytes: 5442,
+                            block_size_in_bytes: None,
+                            column_sizes: HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),
+                            value_counts: HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),
+                            null_value_counts: HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),
+                            nan_value_counts: HashMap::new(),
+                            lower_bounds: HashMap::new(),
+                            upper_bounds: HashMap::new(),
+                            key_metadata: None,
+                            split_offsets: vec![4],
+                            equality_ids: Vec::new(),
+                            sort_order_id: None,
+                            referenced_data_file: None,
+                            partition_spec_id: 0
+                        }
+                    },
+            ];
+
+        // write manifest to file
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer = ManifestWriterBuilder::new(
+            output_file,
+            Some(1),
+            vec![],
+            metadata.schema.clone(),
+            metadata.partition_spec.clone(),
+        )
+        .build_v2_data();
+        for entry in &entries {
+            writer.add_entry(entry.clone()).unwrap();
+        }
+        let res = writer.write_manifest_file().await.unwrap();
+
+        assert_eq!(res.partitions.len(), 3);
+        assert_eq!(res.partitions[0].lower_bound, Some(Datum::int(1111)));
+        assert_eq!(res.partitions[0].upper_bound, Some(Datum::int(2021)));
+        assert!(!res.partitions[0].contains_null);
+        assert_eq!(res.partitions[0].contains_nan, Some(false));
+
+        assert_eq!(res.partitions[1].lower_bound, Some(Datum::float(1.0)));
+        assert_eq!(res.partitions[1].upper_bound, Some(Datum::float(15.5)));
+        assert!(res.partitions[1].contains_null);
+        assert_eq!(res.partitions[1].contains_nan, Some(true));
+
+        assert_eq!(res.partitions[2].lower_bound, Some(Datum::double(1.0)));
+        assert_eq!(res.partitions[2].upper_bound, Some(Datum::double(25.5)));
+        assert!(!res.partitions[2].contains_null);
+        assert_eq!(res.partitions[2].contains_nan, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_add_delete_existing() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "name",
+                        Type::Primitive(PrimitiveType::String),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let metadata = ManifestMetadata {
+            schema_id: 0,
+            schema: schema.clone(),
+            partition_spec: PartitionSpec::builder(schema)
+                .with_spec_id(0)
+                .build()
+                .unwrap(),
+            content: ManifestContentType::Data,
+            format_version: FormatVersion::V2,
+            extra_metadata: HashMap::new(),
+        };
+        let mut entries = vec![
+                ManifestEntry {
+                    status: ManifestStatus::Added,
+                    snapshot_id: None,
+                    sequence_number: Some(1),
+                    file_sequence_number: Some(1),
+                    data_file: DataFile {
+                        content: DataContentType::Data,
+                        file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
+                        file_format: DataFileFormat::Parquet,
+                        partition: Struct::empty(),
+                        record_count: 1,
+                        file_size_in_bytes: 5442,
+                        block_size_in_bytes: None,
+                        column_sizes: HashMap::from([(1, 61), (2, 73)]),
+                        value_counts: HashMap::from([(1, 1), (2, 1)]),
+                        null_value_counts: HashMap::from([(1, 0), (2, 0)]),
+                        nan_value_counts: HashMap::new(),
+                        lower_bounds: HashMap::new(),
+                        upper_bounds: HashMap::new(),
+                        key_metadata: Some(Vec::new()),
+                        split_offsets: vec![4],
+                        equality_ids: Vec::new(),
+                        sort_order_id: None,
+                        referenced_data_file: None,
+                        partition_spec_id: 0
+                    },
+                },
+                ManifestEntry {
+                    status: ManifestStatus::Deleted,
+                    snapshot_id: Some(1),
+                    sequence_number: Some(1),
+                    file_sequence_number: Some(1),
+                    data_file: DataFile {
+                        content: DataContentType::Data,
+                        file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
+                        file_format: DataFileFormat::Parquet,
+                        partition: Struct::empty(),
+                        record_count: 1,
+                        file_size_in_bytes: 5442,
+                        block_size_in_bytes: None,
+                        column_sizes: HashMap::from([(1, 61), (2, 73)]),
+                        value_counts: HashMap::from([(1, 1), (2, 1)]),
+                        null_value_counts: HashMap::from([(1, 0), (2, 0)]),
+                        nan_value_counts: HashMap::new(),
+                        lower_bounds: HashMap::new(),
+                        upper_bounds: HashMap::new(),
+                        key_metadata: Some(Vec::new()),
+                        split_offsets: vec![4],
+                        equality_ids: Vec::new(),
+                        sort_order_id: None,
+                        referenced_data_file: None,
+                        partition_spec_id: 0
+                    },
+                },
+                ManifestEntry {
+                    status: ManifestStatus::Existing,
+                    snapshot_id: Some(1),
+                    sequence_number: Some(1),
+                    file_sequence_number: Some(1),
+                    data_file: DataFile {
+                        content: DataContentType::Data,
+                        file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
+                        file_format: DataFileFormat::Parquet,
+                        partition: Struct::empty(),
+                        record_count: 1,
+                        file_size_in_bytes: 5442,
+                        block_size_in_bytes: None,
+                        column_sizes: HashMap::from([(1, 61), (2, 73)]),
+                        value_counts: HashMap::from([(1, 1), (2, 1)]),
+                        null_value_counts: HashMap::from([(1, 0), (2, 0)]),
+                        nan_value_counts: HashMap::new(),
+                        lower_bounds: HashMap::new(),
+                        upper_bounds: HashMap::new(),
+                        key_metadata: Some(Vec::new()),
+                        split_offsets: vec![4],
+                        equality_ids: Vec::new(),
+                        sort_order_id: None,
+                        referenced_data_file: None,
+                        partition_spec_id: 0
+                    },
+                },
+            ];
+
+        // write manifest to file
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer = ManifestWriterBuilder::new(
+            output_file,
+            Some(3),
+            vec![],
+            metadata.schema.clone(),
+            metadata.partition_spec.clone(),
+        )
+        .build_v2_data();
+        writer.add_entry(entries[0].clone()).unwrap();
+        writer.add_delete_entry(entries[1].clone()).unwrap();
+        writer.add_existing_entry(entries[2].clone()).unwrap();
+        writer.write_manifest_file().await.unwrap();
+
+        // read back the manifest file and check the content
+        let actual_manifest =
+            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                .unwrap();
+
+        // The snapshot id is assigned when the entry is added and delete to the manifest. Existing entries are keep original.
+        entries[0].snapshot_id = Some(3);
+        entries[1].snapshot_id = Some(3);
+        // file sequence number is assigned to None when the entry is added and delete to the manifest.
+        entries[0].file_sequence_number = None;
+        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+    }
+
+    #[tokio::test]
+    async fn test_data_file_serialize_deserialize() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "v1",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "v2",
+                        Type::Primitive(PrimitiveType::String),
+                    )),
+                    Arc::new(NestedField::optional(
+                        3,
+                        "v3",
+                        Type::Primitive(PrimitiveType::String),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let data_files = vec![DataFile {
+            content: DataContentType::Data,
+            file_path: "s3://testbucket/iceberg_data/iceberg_ctl/iceberg_db/iceberg_tbl/data/00000-7-45268d71-54eb-476c-b42c-942d880c04a1-00001.parquet".to_string(),
+            file_format: DataFileFormat::Parquet,
+            partition: Struct::empty(),
+            record_count: 1,
+            file_size_in_bytes: 875,
+            block_size_in_bytes: None,
+            column_sizes: HashMap::from([(1,47),(2,48),(3,52)]),
+            value_counts: HashMap::from([(1,1),(2,1),(3,1)]),
+            null_value_counts: HashMap::from([(1,0),(2,0),(3,0)]),
+            nan_value_counts: HashMap::new(),
+            lower_bounds: HashMap::from([(1,Datum::int(1)),(2,Datum::string("a")),(3,Datum::string("AC/DC"))]),
+            upper_bounds: HashMap::from([(1,Datum::int(1)),(2,Datum::string("a")),(3,Datum::string("AC/DC"))]),
+            key_metadata: None,
+            split_offsets: vec![4],
+            equality_ids: vec![],
+            sort_order_id: Some(0),
+            referenced_data_file: None,
+            partition_spec_id: 0
+        }];
+
+        let mut buffer = Vec::new();
+        let _ = write_data_files_to_avro(
+            &mut buffer,
+            data_files.clone().into_iter(),
+            &StructType::new(vec![]),
+            FormatVersion::V2,
+        )
+        .unwrap();
+
+        let actual_data_file = read_data_files_from_avro(
+            &mut Cursor::new(buffer),
+            &schema,
+            0,
+            &StructType::new(vec![]),
+            FormatVersion::V2,
+        )
+        .unwrap();
+
+        assert_eq!(data_files, actual_data_file);
     }
-    /// Get the Implementation-specific key metadata for the data file.
-    pub fn key_metadata(&self) -> Option<&[u8]> {
-        self.key_metadata.as_deref()
+
+    #[test]
+    fn test_data_file_avro_writer() {
+        let schema = Schema::builder()
+            .with_fields(vec![Arc::new(NestedField::optional(
+                1,
+                "id",
+                Type::Primitive(PrimitiveType::Int),
+            ))])
+            .build()
+            .unwrap();
+        let data_files = (0..5)
+            .map(|i| {
+                test_data_file_builder(format!("s3://testbucket/data/{i:05}.parquet"))
+                    .lower_bounds(HashMap::from([(1, Datum::int(i))]))
+                    .upper_bounds(HashMap::from([(1, Datum::int(i))]))
+                    .build()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let avro_schema =
+            DataFileAvroSchema::try_new(&StructType::new(vec![]), FormatVersion::V2).unwrap();
+        let mut buffer = Vec::new();
+        let mut progress = vec![];
+        let mut writer = DataFileAvroWriter::new(&avro_schema, &mut buffer)
+            .with_flush_interval(2)
+            .with_progress(|count| progress.push(count));
+        for data_file in data_files.clone() {
+            writer.append(data_file).unwrap();
+        }
+        assert_eq!(writer.record_count(), 5);
+        let bytes_written = writer.finish().unwrap();
+        assert_eq!(bytes_written, buffer.len());
+        assert_eq!(progress, vec![2, 4, 5]);
+
+        let actual_data_files = read_data_files_from_avro(
+            &mut Cursor::new(buffer),
+            &schema,
+            0,
+            &StructType::new(vec![]),
+            FormatVersion::V2,
+        )
+        .unwrap();
+        assert_eq!(actual_data_files, data_files);
     }
-    /// Get the split offsets of the data file.
-    /// For example, all row group offsets in a Parquet file.
-    pub fn split_offsets(&self) -> &[i64] {
-        &self.split_offsets
+
+    #[test]
+    fn test_write_data_files_to_avro_with_record_name() {
+        let schema = Schema::builder()
+            .with_fields(vec![Arc::new(NestedField::optional(
+                1,
+                "id",
+                Type::Primitive(PrimitiveType::Int),
+            ))])
+            .build()
+            .unwrap();
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .lower_bounds(HashMap::from([(1, Datum::int(1))]))
+            .build()
+            .unwrap();
+
+        let mut buffer = Vec::new();
+        write_data_files_to_avro_with_record_name(
+            &mut buffer,
+            vec![data_file.clone()],
+            &StructType::new(vec![]),
+            FormatVersion::V2,
+            "com.example.DataFile",
+        )
+        .unwrap();
+
+        let reader = AvroReader::new(&buffer[..]).unwrap();
+        let AvroSchema::Record(record) = reader.writer_schema() else {
+            panic!("data file schema should be a record");
+        };
+        assert_eq!(record.name.fullname(None), "com.example.DataFile");
+
+        let actual = read_data_files_from_avro(
+            &mut Cursor::new(buffer),
+            &schema,
+            0,
+            &StructType::new(vec![]),
+            FormatVersion::V2,
+        )
+        .unwrap();
+        assert_eq!(actual, vec![data_file]);
+    }
+
+    #[test]
+    fn test_data_file_from_avro_value() {
+        let schema = Schema::builder()
+            .with_fields(vec![Arc::new(NestedField::optional(
+                1,
+                "id",
+                Type::Primitive(PrimitiveType::Int),
+            ))])
+            .build()
+            .unwrap();
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .record_count(3)
+            .value_counts(HashMap::from([(1, 3)]))
+            .lower_bounds(HashMap::from([(1, Datum::int(1))]))
+            .upper_bounds(HashMap::from([(1, Datum::int(9))]))
+            .build()
+            .unwrap();
+
+        let mut buffer = Vec::new();
+        write_data_files_to_avro(
+            &mut buffer,
+            vec![data_file.clone()],
+            &StructType::new(vec![]),
+            FormatVersion::V2,
+        )
+        .unwrap();
+
+        // Decode with the writer schema, as a custom reader would.
+        let values = AvroReader::new(&buffer[..])
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values.len(), 1);
+        let actual =
+            DataFile::from_avro_value(&values[0], &schema, &StructType::new(vec![]), 0).unwrap();
+        assert_eq!(actual, data_file);
+
+        assert!(DataFile::from_avro_value(
+            &apache_avro::types::Value::Null,
+            &schema,
+            &StructType::new(vec![]),
+            0
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_read_data_files_from_avro_projected() {
+        let schema = Schema::builder()
+            .with_fields(vec![Arc::new(NestedField::optional(
+                1,
+                "id",
+                Type::Primitive(PrimitiveType::Int),
+            ))])
+            .build()
+            .unwrap();
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .record_count(3)
+            .column_sizes(HashMap::from([(1, 10)]))
+            .value_counts(HashMap::from([(1, 3)]))
+            .null_value_counts(HashMap::from([(1, 0)]))
+            .lower_bounds(HashMap::from([(1, Datum::int(1))]))
+            .upper_bounds(HashMap::from([(1, Datum::int(9))]))
+            .split_offsets(vec![4])
+            .sort_order_id(0)
+            .build()
+            .unwrap();
+
+        for version in [FormatVersion::V1, FormatVersion::V2] {
+            let mut buffer = Vec::new();
+            write_data_files_to_avro(
+                &mut buffer,
+                vec![data_file.clone()],
+                &StructType::new(vec![]),
+                version,
+            )
+            .unwrap();
+
+            // Keep the split offsets (field id 132) and value counts (field id 109).
+            let actual = read_data_files_from_avro_projected(
+                &mut Cursor::new(buffer),
+                &schema,
+                0,
+                &StructType::new(vec![]),
+                version,
+                &HashSet::from([109, 132]),
+            )
+            .unwrap();
+            assert_eq!(actual.len(), 1);
+            assert_eq!(actual[0].file_path(), data_file.file_path());
+            assert_eq!(actual[0].record_count(), 3);
+            assert_eq!(actual[0].value_counts(), data_file.value_counts());
+            assert_eq!(actual[0].split_offsets(), &[4]);
+            assert!(actual[0].column_sizes().is_empty());
+            assert!(actual[0].null_value_counts().is_empty());
+            assert!(actual[0].lower_bounds().is_empty());
+            assert!(actual[0].upper_bounds().is_empty());
+            assert_eq!(actual[0].sort_order_id(), None);
+        }
     }
-    /// Get the equality ids of the data file.
-    /// Field ids used to determine row equality in equality delete files.
-    /// null when content is not EqualityDeletes.
-    pub fn equality_ids(&self) -> &[i32] {
-        &self.equality_ids
+
+    /// Writes `data_files` as added entries of a v2 data manifest and parses it back.
+    async fn write_and_read_v2_data_manifest(
+        schema: SchemaRef,
+        data_files: Vec<DataFile>,
+    ) -> Manifest {
+        let partition_spec = test_partition_spec(&schema);
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(1), vec![], schema, partition_spec)
+                .build_v2_data();
+        for data_file in data_files {
+            writer.add_file(data_file, 1).unwrap();
+        }
+        writer.write_manifest_file().await.unwrap();
+
+        Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice()).unwrap()
     }
-    /// Get the sort order id of the data file.
-    /// Only data files and equality delete files should be
-    /// written with a non-null order id. Position deletes are required to be
-    /// sorted by file and position, not a table order, and should set sort
-    /// order id to null. Readers must ignore sort order id for position
-    /// delete files.
-    pub fn sort_order_id(&self) -> Option<i32> {
-        self.sort_order_id
+
+    #[tokio::test]
+    async fn test_parse_manifest_timestamp_and_timestamptz_bounds() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "ts",
+                        Type::Primitive(PrimitiveType::Timestamp),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "ts_tz",
+                        Type::Primitive(PrimitiveType::Timestamptz),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let lower_bounds = HashMap::from([
+            (1, Datum::timestamp_micros(1_000)),
+            (2, Datum::timestamptz_micros(2_000)),
+        ]);
+        let upper_bounds = HashMap::from([
+            (1, Datum::timestamp_micros(3_000)),
+            (2, Datum::timestamptz_micros(4_000)),
+        ]);
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .lower_bounds(lower_bounds.clone())
+            .upper_bounds(upper_bounds.clone())
+            .build()
+            .unwrap();
+
+        let manifest = write_and_read_v2_data_manifest(schema, vec![data_file]).await;
+        let data_file = manifest.entries()[0].data_file();
+
+        assert_eq!(data_file.lower_bounds(), &lower_bounds);
+        assert_eq!(data_file.upper_bounds(), &upper_bounds);
+        assert_eq!(
+            data_file.lower_bounds()[&1].data_type(),
+            &PrimitiveType::Timestamp
+        );
+        assert_eq!(
+            data_file.lower_bounds()[&2].data_type(),
+            &PrimitiveType::Timestamptz
+        );
+        assert_eq!(
+            data_file.upper_bounds()[&1].data_type(),
+            &PrimitiveType::Timestamp
+        );
+        assert_eq!(
+            data_file.upper_bounds()[&2].data_type(),
+            &PrimitiveType::Timestamptz
+        );
     }
-}
 
-/// Convert data files to avro bytes and write to writer.
-/// Return the bytes written.
-pub fn write_data_files_to_avro<W: Write>(
-    writer: &mut W,
-    data_files: impl IntoIterator<Item = DataFile>,
-    partition_type: &StructType,
-    version: FormatVersion,
-) -> Result<usize> {
-    let avro_schema = match version {
-        FormatVersion::V1 => _const_schema::data_file_schema_v1(partition_type).unwrap(),
-        FormatVersion::V2 => _const_schema::data_file_schema_v2(partition_type).unwrap(),
-    };
-    let mut writer = AvroWriter::new(&avro_schema, writer);
+    #[test]
+    fn test_manifest_content_predicates() {
+        let schema = Arc::new(Schema::builder().build().unwrap());
+        let metadata = ManifestMetadata {
+            schema_id: 0,
+            schema: schema.clone(),
+            partition_spec: PartitionSpec::builder(schema)
+                .with_spec_id(0)
+                .build()
+                .unwrap(),
+            content: ManifestContentType::Deletes,
+            format_version: FormatVersion::V2,
+            extra_metadata: HashMap::new(),
+        };
+        let manifest = Manifest::new(metadata.clone(), vec![]);
+        assert!(manifest.is_delete_manifest());
+        assert!(!manifest.is_data_manifest());
+        assert_eq!(manifest.metadata(), &metadata);
 
-    for data_file in data_files {
-        let value = to_value(_serde::DataFile::try_from(data_file, partition_type, true)?)?
-            .resolve(&avro_schema)?;
-        writer.append(value)?;
+        let manifest = Manifest::new(
+            ManifestMetadata {
+                content: ManifestContentType::Data,
+                ..metadata
+            },
+            vec![],
+        );
+        assert!(manifest.is_data_manifest());
+        assert!(!manifest.is_delete_manifest());
     }
 
-    Ok(writer.flush()?)
-}
+    #[test]
+    fn test_manifest_metadata_new() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_schema_id(2)
+                .with_fields(vec![Arc::new(NestedField::optional(
+                    1,
+                    "id",
+                    Type::Primitive(PrimitiveType::Int),
+                ))])
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .with_spec_id(1)
+            .add_partition_field("id", "id", Transform::Identity)
+            .unwrap()
+            .build()
+            .unwrap();
 
-/// Parse data files from avro bytes.
-pub fn read_data_files_from_avro<R: Read>(
-    reader: &mut R,
-    schema: &Schema,
-    partition_spec_id: i32,
-    partition_type: &StructType,
-    version: FormatVersion,
-) -> Result<Vec<DataFile>> {
-    let avro_schema = match version {
-        FormatVersion::V1 => _const_schema::data_file_schema_v1(partition_type).unwrap(),
-        FormatVersion::V2 => _const_schema::data_file_schema_v2(partition_type).unwrap(),
-    };
+        let metadata = ManifestMetadata::new(
+            schema.clone(),
+            2,
+            partition_spec.clone(),
+            FormatVersion::V2,
+            ManifestContentType::Deletes,
+        );
+        let expected = ManifestMetadata::builder()
+            .schema_id(2)
+            .schema(schema)
+            .partition_spec(partition_spec)
+            .format_version(FormatVersion::V2)
+            .content(ManifestContentType::Deletes)
+            .build();
+        assert_eq!(metadata, expected);
+    }
 
-    let reader = AvroReader::with_schema(&avro_schema, reader)?;
-    reader
-        .into_iter()
-        .map(|value| {
-            from_value::<_serde::DataFile>(&value?)?.try_into(
-                partition_spec_id,
-                partition_type,
-                schema,
-            )
-        })
-        .collect::<Result<Vec<_>>>()
-}
+    #[test]
+    fn test_parse_format_version() {
+        assert_eq!(parse_format_version(b"2").unwrap(), FormatVersion::V2);
+        assert_eq!(parse_format_version(b"1").unwrap(), FormatVersion::V1);
+        assert_eq!(parse_format_version(b"\"2\"").unwrap(), FormatVersion::V2);
+        assert_eq!(parse_format_version(b" 1 ").unwrap(), FormatVersion::V1);
+        assert_eq!(parse_format_version(&[2u8]).unwrap(), FormatVersion::V2);
+        assert!(parse_format_version(b"3").is_err());
+        assert!(parse_format_version(b"v2").is_err());
+    }
 
-/// Type of content stored by the data file: data, equality deletes, or
-/// position deletes (all v1 files are data files)
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
-pub enum DataContentType {
-    /// value: 0
-    Data = 0,
-    /// value: 1
-    PositionDeletes = 1,
-    /// value: 2
-    EqualityDeletes = 2,
-}
+    #[test]
+    fn test_parse_content() {
+        assert_eq!(parse_content(b"data").unwrap(), ManifestContentType::Data);
+        assert_eq!(
+            parse_content(b"deletes").unwrap(),
+            ManifestContentType::Deletes
+        );
+        assert_eq!(parse_content(b"0").unwrap(), ManifestContentType::Data);
+        assert_eq!(parse_content(b"1").unwrap(), ManifestContentType::Deletes);
+        assert!(parse_content(b"2").is_err());
+        assert!(parse_content(b"delete").is_err());
+    }
 
-impl TryFrom<i32> for DataContentType {
-    type Error = Error;
+    #[test]
+    fn test_validate_schema_id() {
+        let schema = Schema::builder()
+            .with_schema_id(1)
+            .with_fields(vec![Arc::new(NestedField::optional(
+                1,
+                "id",
+                Type::Primitive(PrimitiveType::Int),
+            ))])
+            .build()
+            .unwrap();
+        let mut meta = HashMap::from([
+            ("schema".to_string(), to_vec(&schema).unwrap()),
+            ("schema-id".to_string(), b"2".to_vec()),
+            ("partition-spec".to_string(), b"[]".to_vec()),
+            ("format-version".to_string(), b"2".to_vec()),
+        ]);
+
+        // Lenient by default: the stale schema id is kept as recorded.
+        let metadata = ManifestMetadata::parse(&meta).unwrap();
+        assert_eq!(metadata.schema_id(), 2);
+        assert!(metadata.validate_schema_id().is_err());
+
+        let manifest = Manifest::new(metadata, vec![]);
+        assert_eq!(manifest.schema_id(), 2);
+        assert_eq!(manifest.metadata().schema().schema_id(), 1);
+
+        meta.insert("schema-id".to_string(), b"1".to_vec());
+        let metadata = ManifestMetadata::parse(&meta).unwrap();
+        assert!(metadata.validate_schema_id().is_ok());
+    }
 
-    fn try_from(v: i32) -> Result<DataContentType> {
-        match v {
-            0 => Ok(DataContentType::Data),
-            1 => Ok(DataContentType::PositionDeletes),
-            2 => Ok(DataContentType::EqualityDeletes),
-            _ => Err(Error::new(
-                ErrorKind::DataInvalid,
-                format!("data content type {} is invalid", v),
-            )),
+    #[tokio::test]
+    async fn test_read_manifest_with_stale_schema_id() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_schema_id(1)
+                .with_fields(vec![Arc::new(NestedField::optional(
+                    1,
+                    "id",
+                    Type::Primitive(PrimitiveType::Int),
+                ))])
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(1), vec![], schema, partition_spec)
+                .build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let manifest_file = writer.write_manifest_file().await.unwrap();
+        let bs = io
+            .new_input(&manifest_file.manifest_path)
+            .unwrap()
+            .read()
+            .await
+            .unwrap();
+
+        // Rewrite the manifest with a stale schema id, as some producers do.
+        let reader = AvroReader::new(&bs[..]).unwrap();
+        let mut avro_writer = AvroWriter::new(reader.writer_schema(), Vec::new());
+        for (key, value) in reader.user_metadata() {
+            let value = if key == "schema-id" { b"0" } else { &value[..] };
+            avro_writer.add_user_metadata(key.clone(), value).unwrap();
         }
+        for value in AvroReader::new(&bs[..]).unwrap() {
+            avro_writer.append(value.unwrap()).unwrap();
+        }
+        let bs = avro_writer.into_inner().unwrap();
+
+        let err = ManifestReader::builder()
+            .strict_schema_id(true)
+            .build()
+            .read(&bs)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+
+        let (manifest, diagnostics) = ManifestReader::builder()
+            .strict_schema_id(false)
+            .build()
+            .read_with_diagnostics(&bs)
+            .unwrap();
+        assert_eq!(manifest.metadata().schema_id(), 0);
+        assert_eq!(manifest.entries().len(), 1);
+        assert_eq!(diagnostics, vec![
+            "Schema id 0 in manifest metadata doesn't match the id 1 of the embedded schema, kept it as recorded"
+                .to_string()
+        ]);
     }
-}
 
-/// Format of this data.
-#[derive(Debug, PartialEq, Eq, Clone, Copy, SerializeDisplay, DeserializeFromStr)]
-pub enum DataFileFormat {
-    /// Avro file format: <https://avro.apache.org/>
-    Avro,
-    /// Orc file format: <https://orc.apache.org/>
-    Orc,
-    /// Parquet file format: <https://parquet.apache.org/>
-    Parquet,
-}
+    #[tokio::test]
 
-impl FromStr for DataFileFormat {
-    type Err = Error;
+    async fn test_manifest_writer_inherit_snapshot_id() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
 
-    fn from_str(s: &str) -> Result<Self> {
-        match s.to_lowercase().as_str() {
-            "avro" => Ok(Self::Avro),
-            "orc" => Ok(Self::Orc),
-            "parquet" => Ok(Self::Parquet),
-            _ => Err(Error::new(
-                ErrorKind::DataInvalid,
-                format!("Unsupported data file format: {}", s),
-            )),
-        }
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, None, vec![], schema, partition_spec)
+                .build_v2_data();
+        assert_eq!(writer.snapshot_id(), None);
+        writer
+            .add_entry(
+                ManifestEntry::builder()
+                    .status(ManifestStatus::Added)
+                    .data_file(data_file)
+                    .build(),
+            )
+            .unwrap();
+        let mut manifest_file = writer.write_manifest_file().await.unwrap();
+        assert_eq!(manifest_file.added_snapshot_id, UNASSIGNED_SNAPSHOT_ID);
+
+        // The entry is written without snapshot id.
+        let manifest =
+            Manifest::parse_avro(fs::read(&path).expect("read_file must succeed").as_slice())
+                .unwrap();
+        assert_eq!(manifest.entries()[0].snapshot_id(), None);
+        assert_eq!(manifest.entries()[0].sequence_number(), None);
+
+        // And inherits it from the manifest list entry on load.
+        manifest_file.added_snapshot_id = 10;
+        manifest_file.sequence_number = 5;
+        let manifest = manifest_file.load_manifest(&io).await.unwrap();
+        assert_eq!(manifest.entries()[0].snapshot_id(), Some(10));
+        assert_eq!(manifest.entries()[0].sequence_number(), Some(5));
     }
-}
 
-impl std::fmt::Display for DataFileFormat {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            DataFileFormat::Avro => write!(f, "avro"),
-            DataFileFormat::Orc => write!(f, "orc"),
-            DataFileFormat::Parquet => write!(f, "parquet"),
-        }
+    #[tokio::test]
+    async fn test_manifest_data_files() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = |path: &str| test_data_file(path);
+
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(2), vec![], schema, partition_spec)
+                .build_v2_data();
+        writer
+            .add_file(data_file("s3://testbucket/data/added.parquet"), 2)
+            .unwrap();
+        writer
+            .add_existing_file(
+                data_file("s3://testbucket/data/existing.parquet"),
+                1,
+                1,
+                Some(1),
+            )
+            .unwrap();
+        writer
+            .add_delete_file(
+                data_file("s3://testbucket/data/deleted.parquet"),
+                1,
+                Some(1),
+            )
+            .unwrap();
+        writer.write_manifest_file().await.unwrap();
+
+        let manifest =
+            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                .unwrap();
+        let paths = manifest
+            .data_files()
+            .map(|data_file| data_file.file_path().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec![
+            "s3://testbucket/data/added.parquet",
+            "s3://testbucket/data/existing.parquet"
+        ]);
+
+        let data_files = manifest.into_data_files();
+        assert_eq!(data_files, vec![
+            data_file("s3://testbucket/data/added.parquet"),
+            data_file("s3://testbucket/data/existing.parquet")
+        ]);
     }
-}
 
-mod _serde {
-    use std::collections::HashMap;
+    #[tokio::test]
+    async fn test_parse_manifest_time_and_uuid_bounds() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "time",
+                        Type::Primitive(PrimitiveType::Time),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "uuid",
+                        Type::Primitive(PrimitiveType::Uuid),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let min_uuid = uuid::Uuid::parse_str("0f8fad5b-d9cb-469f-a165-70867728950e").unwrap();
+        let max_uuid = uuid::Uuid::parse_str("7c9e6679-7425-40de-944b-e07fc1f90ae7").unwrap();
+        let lower_bounds = HashMap::from([
+            (1, Datum::time_micros(1_000).unwrap()),
+            (2, Datum::uuid(min_uuid)),
+        ]);
+        let upper_bounds = HashMap::from([
+            (1, Datum::time_micros(86_399_999_999).unwrap()),
+            (2, Datum::uuid(max_uuid)),
+        ]);
+
+        // UUIDs are serialized as 16-byte big-endian values.
+        assert_eq!(
+            lower_bounds[&2].to_bytes().unwrap().as_slice(),
+            min_uuid.as_bytes()
+        );
 
-    use serde_derive::{Deserialize, Serialize};
-    use serde_with::serde_as;
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .lower_bounds(lower_bounds.clone())
+            .upper_bounds(upper_bounds.clone())
+            .build()
+            .unwrap();
 
-    use super::ManifestEntry;
-    use crate::spec::{Datum, Literal, RawLiteral, Schema, Struct, StructType, Type};
-    use crate::{Error, ErrorKind};
+        let manifest = write_and_read_v2_data_manifest(schema, vec![data_file]).await;
+        let data_file = manifest.entries()[0].data_file();
+        assert_eq!(data_file.lower_bounds(), &lower_bounds);
+        assert_eq!(data_file.upper_bounds(), &upper_bounds);
+    }
 
-    #[derive(Serialize, Deserialize)]
-    pub(super) struct ManifestEntryV2 {
-        status: i32,
-        snapshot_id: Option<i64>,
-        sequence_number: Option<i64>,
-        file_sequence_number: Option<i64>,
-        data_file: DataFile,
+    #[test]
+    fn test_empty_unpartitioned_manifest() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let writer = test_writer_builder(schema.clone(), partition_spec.clone()).build_v2_data();
+        let (bs, manifest_file) = writer.write_to_bytes().unwrap();
+        assert_eq!(manifest_file.partitions, vec![]);
+        assert_eq!(manifest_file.added_files_count, Some(0));
+
+        let manifest = Manifest::parse_avro(&bs).unwrap();
+        assert!(manifest.entries().is_empty());
+        assert!(manifest.metadata().partition_spec.is_unpartitioned());
+        assert_eq!(manifest.compute_partition_summaries().unwrap(), vec![]);
+
+        // A partition value for an unpartitioned spec is rejected instead of panicking.
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        let data_file = test_data_file_builder("s3://testbucket/data.parquet")
+            .partition(Struct::from_iter([Some(Literal::int(1))]))
+            .build()
+            .unwrap();
+        let err = writer.add_file(data_file, 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        assert!(err.message().contains("has 1 fields, expected 0"));
     }
 
-    impl ManifestEntryV2 {
-        pub fn try_from(value: ManifestEntry, partition_type: &StructType) -> Result<Self, Error> {
-            Ok(Self {
-                status: value.status as i32,
-                snapshot_id: value.snapshot_id,
-                sequence_number: value.sequence_number,
-                file_sequence_number: value.file_sequence_number,
-                data_file: DataFile::try_from(value.data_file, partition_type, false)?,
-            })
+    #[test]
+    fn test_manifest_writer_incremental_partition_summaries() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "score",
+                        Type::Primitive(PrimitiveType::Double),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .with_spec_id(0)
+            .add_partition_field("id", "id", Transform::Identity)
+            .unwrap()
+            .add_partition_field("score", "score", Transform::Identity)
+            .unwrap()
+            .build()
+            .unwrap();
+        let partition_type = partition_spec.partition_type(&schema).unwrap();
+        let partitions = vec![
+            Struct::from_iter([Some(Literal::int(5)), Some(Literal::double(1.5))]),
+            Struct::from_iter([None, Some(Literal::double(f64::NAN))]),
+            Struct::from_iter([Some(Literal::int(-3)), Some(Literal::double(0.5))]),
+            Struct::from_iter([Some(Literal::int(9)), None]),
+        ];
+
+        // The summaries computed at once over all partitions, as the writer used to.
+        let mut expected: Vec<_> = partition_type
+            .fields()
+            .iter()
+            .map(|f| PartitionFieldStats::new(f.field_type.as_primitive_type().unwrap().clone()))
+            .collect();
+        for partition in &partitions {
+            for (literal, stat) in partition.iter().zip_eq(expected.iter_mut()) {
+                stat.update(literal.map(|v| v.as_primitive_literal().unwrap()))
+                    .unwrap();
+            }
         }
+        let expected: Vec<_> = expected.into_iter().map(|stat| stat.finish()).collect();
 
-        pub fn try_into(
-            self,
-            partition_spec_id: i32,
-            partition_type: &StructType,
-            schema: &Schema,
-        ) -> Result<ManifestEntry, Error> {
-            Ok(ManifestEntry {
-                status: self.status.try_into()?,
-                snapshot_id: self.snapshot_id,
-                sequence_number: self.sequence_number,
-                file_sequence_number: self.file_sequence_number,
-                data_file: self
-                    .data_file
-                    .try_into(partition_spec_id, partition_type, schema)?,
-            })
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        for (i, partition) in partitions.into_iter().enumerate() {
+            let data_file = test_data_file_builder(format!("s3://testbucket/data/{i:05}.parquet"))
+                .partition(partition)
+                .build()
+                .unwrap();
+            writer.add_file(data_file, 1).unwrap();
         }
+        // A file rejected for its second partition value leaves the summary of the first
+        // untouched.
+        let data_file = test_data_file_builder("s3://testbucket/data/invalid.parquet")
+            .partition(Struct::from_iter([
+                Some(Literal::int(100)),
+                Some(Literal::string("1.5")),
+            ]))
+            .build()
+            .unwrap();
+        let err = writer.add_file(data_file, 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        let (bs, manifest_file) = writer.write_to_bytes().unwrap();
+        assert_eq!(manifest_file.partitions, expected);
+        assert_eq!(
+            Manifest::parse_avro(&bs)
+                .unwrap()
+                .compute_partition_summaries()
+                .unwrap(),
+            expected
+        );
+        assert_eq!(manifest_file.partitions[0], FieldSummary {
+            contains_null: true,
+            contains_nan: Some(false),
+            lower_bound: Some(Datum::int(-3)),
+            upper_bound: Some(Datum::int(9)),
+        });
+        assert_eq!(manifest_file.partitions[1], FieldSummary {
+            contains_null: true,
+            contains_nan: Some(true),
+            lower_bound: Some(Datum::double(0.5)),
+            upper_bound: Some(Datum::double(1.5)),
+        });
     }
 
-    #[derive(Serialize, Deserialize)]
-    pub(super) struct ManifestEntryV1 {
-        status: i32,
-        pub snapshot_id: i64,
-        data_file: DataFile,
+    #[tokio::test]
+    async fn test_write_empty_manifest() {
+        let schema = test_schema();
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .with_spec_id(0)
+            .add_partition_field("id", "id", Transform::Identity)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+        let writer =
+            ManifestWriterBuilder::new(output_file, Some(1), vec![], schema, partition_spec)
+                .build_v2_data();
+        let manifest_file = writer.write_manifest_file().await.unwrap();
+        assert_eq!(manifest_file.added_files_count, Some(0));
+        assert_eq!(manifest_file.existing_files_count, Some(0));
+        assert_eq!(manifest_file.deleted_files_count, Some(0));
+        assert_eq!(manifest_file.added_rows_count, Some(0));
+        assert_eq!(manifest_file.existing_rows_count, Some(0));
+        assert_eq!(manifest_file.deleted_rows_count, Some(0));
+        assert_eq!(manifest_file.sequence_number, UNASSIGNED_SEQUENCE_NUMBER);
+        assert_eq!(
+            manifest_file.min_sequence_number,
+            UNASSIGNED_SEQUENCE_NUMBER
+        );
+        assert_eq!(manifest_file.partitions, vec![FieldSummary {
+            contains_null: false,
+            contains_nan: Some(false),
+            lower_bound: None,
+            upper_bound: None,
+        }]);
+
+        let bs = io
+            .new_input(&manifest_file.manifest_path)
+            .unwrap()
+            .read()
+            .await
+            .unwrap();
+        assert_eq!(manifest_file.manifest_length, bs.len() as i64);
+        let manifest = Manifest::parse_avro(&bs).unwrap();
+        assert!(manifest.entries().is_empty());
+        assert_eq!(manifest.metadata().content(), &ManifestContentType::Data);
     }
 
-    impl ManifestEntryV1 {
-        pub fn try_from(value: ManifestEntry, partition_type: &StructType) -> Result<Self, Error> {
-            Ok(Self {
-                status: value.status as i32,
-                snapshot_id: value.snapshot_id.unwrap_or_default(),
-                data_file: DataFile::try_from(value.data_file, partition_type, true)?,
-            })
-        }
+    #[tokio::test]
+    async fn test_manifest_writer_without_partition_summaries() {
+        let schema = test_schema();
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .with_spec_id(0)
+            .add_partition_field("id", "id", Transform::Identity)
+            .unwrap()
+            .build()
+            .unwrap();
+        let delete_file = test_data_file_builder("s3://testbucket/data/delete-00000.parquet")
+            .content(DataContentType::PositionDeletes)
+            .partition(Struct::from_iter([Some(Literal::int(1))]))
+            .build()
+            .unwrap();
 
-        pub fn try_into(
-            self,
-            partition_spec_id: i32,
-            partition_type: &StructType,
-            schema: &Schema,
-        ) -> Result<ManifestEntry, Error> {
-            Ok(ManifestEntry {
-                status: self.status.try_into()?,
-                snapshot_id: Some(self.snapshot_id),
-                sequence_number: Some(0),
-                file_sequence_number: Some(0),
-                data_file: self
-                    .data_file
-                    .try_into(partition_spec_id, partition_type, schema)?,
-            })
+        let tmp_dir = TempDir::new().unwrap();
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        for (partition_summaries, expected_len) in [(true, 1), (false, 0)] {
+            let path = tmp_dir
+                .path()
+                .join(format!("test_manifest_{partition_summaries}.avro"));
+            let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+            let mut writer = ManifestWriterBuilder::new(
+                output_file,
+                Some(1),
+                vec![],
+                schema.clone(),
+                partition_spec.clone(),
+            )
+            .with_partition_summaries(partition_summaries)
+            .build_v2_deletes();
+            writer.add_file(delete_file.clone(), 1).unwrap();
+            let manifest_file = writer.write_manifest_file().await.unwrap();
+            assert_eq!(manifest_file.partitions.len(), expected_len);
+
+            let manifest =
+                Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                    .unwrap();
+            assert_eq!(manifest.entries().len(), 1);
         }
     }
 
-    #[serde_as]
-    #[derive(Serialize, Deserialize)]
-    pub(super) struct DataFile {
-        #[serde(default)]
-        content: i32,
-        file_path: String,
-        file_format: String,
-        partition: RawLiteral,
-        record_count: i64,
-        file_size_in_bytes: i64,
-        #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
-        block_size_in_bytes: Option<i64>,
-        column_sizes: Option<Vec<I64Entry>>,
-        value_counts: Option<Vec<I64Entry>>,
-        null_value_counts: Option<Vec<I64Entry>>,
-        nan_value_counts: Option<Vec<I64Entry>>,
-        lower_bounds: Option<Vec<BytesEntry>>,
-        upper_bounds: Option<Vec<BytesEntry>>,
-        key_metadata: Option<serde_bytes::ByteBuf>,
-        split_offsets: Option<Vec<i64>>,
-        #[serde(default)]
-        equality_ids: Option<Vec<i32>>,
-        sort_order_id: Option<i32>,
-    }
+    #[tokio::test]
+    async fn test_manifest_writer_column_metrics_modes() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(
+                    (1..=200)
+                        .map(|id| {
+                            Arc::new(NestedField::optional(
+                                id,
+                                format!("col_{id}"),
+                                Type::Primitive(PrimitiveType::Int),
+                            ))
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = test_partition_spec(&schema);
+        let counts = HashMap::from_iter((1..=200).map(|id| (id, 1)));
+        let bounds = HashMap::from_iter((1..=200).map(|id| (id, Datum::int(id))));
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .column_sizes(counts.clone())
+            .value_counts(counts.clone())
+            .null_value_counts(counts.clone())
+            .nan_value_counts(counts)
+            .lower_bounds(bounds.clone())
+            .upper_bounds(bounds)
+            .build()
+            .unwrap();
 
-    impl DataFile {
-        pub fn try_from(
-            value: super::DataFile,
-            partition_type: &StructType,
-            is_version_1: bool,
-        ) -> Result<Self, Error> {
-            let block_size_in_bytes = if is_version_1 { Some(0) } else { None };
-            Ok(Self {
-                content: value.content as i32,
-                file_path: value.file_path,
-                file_format: value.file_format.to_string().to_ascii_uppercase(),
-                partition: RawLiteral::try_from(
-                    Literal::Struct(value.partition),
-                    &Type::Struct(partition_type.clone()),
-                )?,
-                record_count: value.record_count.try_into()?,
-                file_size_in_bytes: value.file_size_in_bytes.try_into()?,
-                block_size_in_bytes,
-                column_sizes: Some(to_i64_entry(value.column_sizes)?),
-                value_counts: Some(to_i64_entry(value.value_counts)?),
-                null_value_counts: Some(to_i64_entry(value.null_value_counts)?),
-                nan_value_counts: Some(to_i64_entry(value.nan_value_counts)?),
-                lower_bounds: Some(to_bytes_entry(value.lower_bounds)?),
-                upper_bounds: Some(to_bytes_entry(value.upper_bounds)?),
-                key_metadata: value.key_metadata.map(serde_bytes::ByteBuf::from),
-                split_offsets: Some(value.split_offsets),
-                equality_ids: Some(value.equality_ids),
-                sort_order_id: value.sort_order_id,
-            })
-        }
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(1), vec![], schema, partition_spec)
+                .with_default_metrics_mode(MetricsMode::Counts)
+                .with_column_metrics_mode(1, MetricsMode::Full)
+                .with_column_metrics_mode(50, MetricsMode::Full)
+                .with_column_metrics_mode(200, MetricsMode::Full)
+                .with_column_metrics_mode(100, MetricsMode::None)
+                .build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        writer.write_manifest_file().await.unwrap();
 
-        pub fn try_into(
-            self,
-            partition_spec_id: i32,
-            partition_type: &StructType,
-            schema: &Schema,
-        ) -> Result<super::DataFile, Error> {
-            let partition = self
-                .partition
-                .try_into(&Type::Struct(partition_type.clone()))?
-                .map(|v| {
-                    if let Literal::Struct(v) = v {
-                        Ok(v)
-                    } else {
-                        Err(Error::new(
-                            ErrorKind::DataInvalid,
-                            "partition value is not a struct",
-                        ))
-                    }
-                })
-                .transpose()?
-                .unwrap_or(Struct::empty());
-            Ok(super::DataFile {
-                content: self.content.try_into()?,
-                file_path: self.file_path,
-                file_format: self.file_format.parse()?,
-                partition,
-                record_count: self.record_count.try_into()?,
-                file_size_in_bytes: self.file_size_in_bytes.try_into()?,
-                column_sizes: self
-                    .column_sizes
-                    .map(parse_i64_entry)
-                    .transpose()?
-                    .unwrap_or_default(),
-                value_counts: self
-                    .value_counts
-                    .map(parse_i64_entry)
-                    .transpose()?
-                    .unwrap_or_default(),
-                null_value_counts: self
-                    .null_value_counts
-                    .map(parse_i64_entry)
-                    .transpose()?
-                    .unwrap_or_default(),
-                nan_value_counts: self
-                    .nan_value_counts
-                    .map(parse_i64_entry)
-                    .transpose()?
-                    .unwrap_or_default(),
-                lower_bounds: self
-                    .lower_bounds
-                    .map(|v| parse_bytes_entry(v, schema))
-                    .transpose()?
-                    .unwrap_or_default(),
-                upper_bounds: self
-                    .upper_bounds
-                    .map(|v| parse_bytes_entry(v, schema))
-                    .transpose()?
-                    .unwrap_or_default(),
-                key_metadata: self.key_metadata.map(|v| v.to_vec()),
-                split_offsets: self.split_offsets.unwrap_or_default(),
-                equality_ids: self.equality_ids.unwrap_or_default(),
-                sort_order_id: self.sort_order_id,
-                partition_spec_id,
-            })
+        let manifest =
+            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                .unwrap();
+        let data_file = manifest.entries()[0].data_file();
+        let bounds_columns = vec![1, 50, 200];
+        fn ids<T>(map: &HashMap<i32, T>) -> Vec<i32> {
+            map.keys().copied().sorted().collect()
         }
+        assert_eq!(ids(&data_file.lower_bounds), bounds_columns);
+        assert_eq!(ids(&data_file.upper_bounds), bounds_columns);
+        assert_eq!(ids(&data_file.nan_value_counts), bounds_columns);
+        assert_eq!(data_file.value_counts.len(), 199);
+        assert_eq!(data_file.null_value_counts.len(), 199);
+        assert_eq!(data_file.column_sizes.len(), 199);
+        assert!(!data_file.value_counts.contains_key(&100));
     }
 
-    #[serde_as]
-    #[derive(Serialize, Deserialize)]
-    #[cfg_attr(test, derive(Debug, PartialEq, Eq))]
-    struct BytesEntry {
-        key: i32,
-        value: serde_bytes::ByteBuf,
-    }
+    #[test]
+    fn test_parse_manifest_with_null_stats_maps() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let partition_type = partition_spec.partition_type(&schema).unwrap();
+        let entry = ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file("s3://testbucket/data/00000.parquet"),
+        };
 
-    fn parse_bytes_entry(
-        v: Vec<BytesEntry>,
-        schema: &Schema,
-    ) -> Result<HashMap<i32, Datum>, Error> {
-        let mut m = HashMap::with_capacity(v.len());
-        for entry in v {
-            // We ignore the entry if the field is not found in the schema, due to schema evolution.
-            if let Some(field) = schema.field_by_id(entry.key) {
-                let data_type = field
-                    .field_type
-                    .as_primitive_type()
-                    .ok_or_else(|| {
-                        Error::new(
-                            ErrorKind::DataInvalid,
-                            format!("field {} is not a primitive type", field.name),
-                        )
-                    })?
-                    .clone();
-                m.insert(entry.key, Datum::try_from_bytes(&entry.value, data_type)?);
+        for format_version in [FormatVersion::V1, FormatVersion::V2] {
+            let (avro_schema, mut value) = match format_version {
+                FormatVersion::V1 => (
+                    manifest_schema_v1(&partition_type).unwrap(),
+                    to_value(
+                        _serde::ManifestEntryV1::try_from(entry.clone(), &partition_type).unwrap(),
+                    )
+                    .unwrap(),
+                ),
+                FormatVersion::V2 => (
+                    manifest_schema_v2(&partition_type).unwrap(),
+                    to_value(
+                        _serde::ManifestEntryV2::try_from(entry.clone(), &partition_type).unwrap(),
+                    )
+                    .unwrap(),
+                ),
+            };
+
+            // A minimal-metrics producer writes null instead of empty stats maps.
+            let apache_avro::types::Value::Record(fields) = &mut value else {
+                panic!("manifest entry should be a record");
+            };
+            for (name, field) in fields.iter_mut() {
+                if let ("data_file", apache_avro::types::Value::Record(data_file)) =
+                    (name.as_str(), field)
+                {
+                    for (name, field) in data_file.iter_mut() {
+                        if [
+                            "column_sizes",
+                            "value_counts",
+                            "null_value_counts",
+                            "nan_value_counts",
+                            "lower_bounds",
+                            "upper_bounds",
+                        ]
+                        .contains(&name.as_str())
+                        {
+                            *field = apache_avro::types::Value::Null;
+                        }
+                    }
+                }
             }
+
+            let mut avro_writer = AvroWriter::new(&avro_schema, Vec::new());
+            avro_writer
+                .add_user_metadata("schema".to_string(), to_vec(&schema).unwrap())
+                .unwrap();
+            avro_writer
+                .add_user_metadata("partition-spec".to_string(), b"[]")
+                .unwrap();
+            avro_writer
+                .add_user_metadata(
+                    "format-version".to_string(),
+                    (format_version as u8).to_string(),
+                )
+                .unwrap();
+            avro_writer
+                .append(value.resolve(&avro_schema).unwrap())
+                .unwrap();
+            let bs = avro_writer.into_inner().unwrap();
+
+            let manifest = Manifest::parse_avro(&bs).unwrap();
+            assert_eq!(manifest.entries().len(), 1);
+            let data_file = manifest.entries()[0].data_file();
+            assert_eq!(data_file.file_path(), entry.file_path());
+            assert!(data_file.column_sizes().is_empty());
+            assert!(data_file.value_counts().is_empty());
+            assert!(data_file.null_value_counts().is_empty());
+            assert!(data_file.nan_value_counts().is_empty());
+            assert!(data_file.lower_bounds().is_empty());
+            assert!(data_file.upper_bounds().is_empty());
         }
-        Ok(m)
     }
 
-    fn to_bytes_entry(v: impl IntoIterator<Item = (i32, Datum)>) -> Result<Vec<BytesEntry>, Error> {
-        let iter = v.into_iter();
-        // Reserve the capacity to the lower bound.
-        let mut bs = Vec::with_capacity(iter.size_hint().0);
-        for (k, d) in iter {
-            bs.push(BytesEntry {
-                key: k,
-                value: d.to_bytes()?,
-            });
+    #[test]
+    fn test_parse_manifest_v2_with_block_size_in_bytes() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let partition_type = partition_spec.partition_type(&schema).unwrap();
+        let entry = ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder("s3://testbucket/data/00000.parquet")
+                .value_counts(HashMap::from([(1, 1)]))
+                .build()
+                .unwrap(),
+        };
+
+        // A non-conformant producer writes the deprecated v1 `block_size_in_bytes` field into
+        // v2 data_file records.
+        let mut schema_json =
+            serde_json::to_value(manifest_schema_v2(&partition_type).unwrap()).unwrap();
+        schema_json["fields"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|field| field["name"] == "data_file")
+            .unwrap()["type"]["fields"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({"name": "block_size_in_bytes", "type": "long"}));
+        let writer_schema = apache_avro::Schema::parse(&schema_json).unwrap();
+
+        let mut value =
+            to_value(_serde::ManifestEntryV2::try_from(entry.clone(), &partition_type).unwrap())
+                .unwrap();
+        let apache_avro::types::Value::Record(fields) = &mut value else {
+            panic!("manifest entry should be a record");
+        };
+        for (name, field) in fields.iter_mut() {
+            if let ("data_file", apache_avro::types::Value::Record(data_file)) =
+                (name.as_str(), field)
+            {
+                data_file.push((
+                    "block_size_in_bytes".to_string(),
+                    apache_avro::types::Value::Long(64 * 1024 * 1024),
+                ));
+            }
         }
-        Ok(bs)
+
+        let mut avro_writer = AvroWriter::new(&writer_schema, Vec::new());
+        avro_writer
+            .add_user_metadata("schema".to_string(), to_vec(&schema).unwrap())
+            .unwrap();
+        avro_writer
+            .add_user_metadata("partition-spec".to_string(), b"[]")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("format-version".to_string(), "2")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("content".to_string(), "data")
+            .unwrap();
+        avro_writer
+            .append(value.resolve(&writer_schema).unwrap())
+            .unwrap();
+        let bs = avro_writer.into_inner().unwrap();
+
+        let manifest = Manifest::parse_avro(&bs).unwrap();
+        assert_eq!(manifest.entries().len(), 1);
+        assert_eq!(manifest.entries()[0].as_ref(), &entry);
     }
 
-    #[derive(Serialize, Deserialize)]
-    #[cfg_attr(test, derive(Debug, PartialEq, Eq))]
-    struct I64Entry {
-        key: i32,
-        value: i64,
+    #[test]
+    fn test_block_size_in_bytes_only_kept_for_v1() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .block_size_in_bytes(Some(67108864))
+            .build()
+            .unwrap();
+        let builder = || test_writer_builder(schema.clone(), partition_spec.clone());
+
+        let mut writer = builder().build_v1();
+        writer.add_file(data_file, 1).unwrap();
+        let (bytes, _) = writer.write_to_bytes().unwrap();
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        let data_file = manifest.entries()[0].data_file().clone();
+        assert_eq!(data_file.block_size_in_bytes(), Some(67108864));
+
+        let mut writer = builder().build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bytes, _) = writer.write_to_bytes().unwrap();
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        assert_eq!(
+            manifest.entries()[0].data_file().block_size_in_bytes(),
+            None
+        );
     }
 
-    fn parse_i64_entry(v: Vec<I64Entry>) -> Result<HashMap<i32, u64>, Error> {
-        let mut m = HashMap::with_capacity(v.len());
-        for entry in v {
-            // We ignore the entry if it's value is negative since these entries are supposed to be used for
-            // counting, which should never be negative.
-            if let Ok(v) = entry.value.try_into() {
-                m.insert(entry.key, v);
+    fn read_avro_long(bs: &[u8], pos: &mut usize) -> i64 {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let b = bs[*pos];
+            *pos += 1;
+            value |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return (value >> 1) as i64 ^ -((value & 1) as i64);
             }
+            shift += 7;
         }
-        Ok(m)
     }
 
-    fn to_i64_entry(entries: HashMap<i32, u64>) -> Result<Vec<I64Entry>, Error> {
-        entries
-            .iter()
-            .map(|e| {
-                Ok(I64Entry {
-                    key: *e.0,
-                    value: (*e.1).try_into()?,
+    fn write_avro_long(value: i64, out: &mut Vec<u8>) {
+        let mut value = ((value << 1) ^ (value >> 63)) as u64;
+        while value >= 0x80 {
+            out.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    #[test]
+    fn test_parse_truncated_manifest() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+        assert!(Manifest::parse_avro(&bs).is_ok());
+
+        // Cut in the header, in the entries block, and right before the final sync marker.
+        for len in [0, 2, 64, bs.len() / 2, bs.len() - 1] {
+            let err = Manifest::parse_avro(&bs[..len]).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::DataInvalid);
+            assert_eq!(err.message(), "Manifest file appears truncated");
+        }
+
+        let err = Manifest::parse_avro(b"PAR1 not an avro file").unwrap_err();
+        assert_eq!(
+            err.message(),
+            "Manifest file is not an avro file, the magic header is missing"
+        );
+    }
+
+    #[test]
+    fn test_manifest_schema_json_matches_reference() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_schema_id(1)
+                .with_identifier_field_ids(vec![1])
+                .with_fields(vec![
+                    Arc::new(NestedField::required(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Long),
+                    )),
+                    Arc::new(
+                        NestedField::optional(2, "data", Type::Primitive(PrimitiveType::String))
+                            .with_doc("payload"),
+                    ),
+                    Arc::new(NestedField::optional(
+                        3,
+                        "price",
+                        Type::Primitive(PrimitiveType::Decimal {
+                            precision: 10,
+                            scale: 2,
+                        }),
+                    )),
+                    Arc::new(NestedField::optional(
+                        4,
+                        "location",
+                        Type::Struct(StructType::new(vec![Arc::new(NestedField::required(
+                            7,
+                            "lat",
+                            Type::Primitive(PrimitiveType::Double),
+                        ))])),
+                    )),
+                    Arc::new(NestedField::optional(
+                        5,
+                        "tags",
+                        Type::List(ListType::new(Arc::new(NestedField::list_element(
+                            8,
+                            Type::Primitive(PrimitiveType::String),
+                            false,
+                        )))),
+                    )),
+                    Arc::new(NestedField::optional(
+                        6,
+                        "props",
+                        Type::Map(MapType::new(
+                            Arc::new(NestedField::map_key_element(
+                                9,
+                                Type::Primitive(PrimitiveType::String),
+                            )),
+                            Arc::new(NestedField::map_value_element(
+                                10,
+                                Type::Primitive(PrimitiveType::Int),
+                                true,
+                            )),
+                        )),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = test_partition_spec(&schema);
+        let writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+
+        // The schema JSON is read by other engines, compare it with the layout written by
+        // the Java implementation, ignoring the order of keys.
+        let reader = AvroReader::new(&bs[..]).unwrap();
+        let actual: serde_json::Value =
+            serde_json::from_slice(&reader.user_metadata()["schema"]).unwrap();
+        let expected: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string("testdata/manifest_schema_v2.json").unwrap())
+                .unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[tokio::test]
+    async fn test_parse_avro_mmap() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(1), vec![], schema, partition_spec)
+                .build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        writer.write_manifest_file().await.unwrap();
+
+        let expected = Manifest::parse_avro(&fs::read(&path).unwrap()).unwrap();
+        // SAFETY: the files are only written by this test, before they're parsed.
+        assert_eq!(
+            unsafe { Manifest::parse_avro_mmap(&path) }.unwrap(),
+            expected
+        );
+
+        let empty = tmp_dir.path().join("empty.avro");
+        fs::write(&empty, b"").unwrap();
+        let err = unsafe { Manifest::parse_avro_mmap(&empty) }.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        assert!(err.message().ends_with("is empty"));
+
+        let missing = tmp_dir.path().join("missing.avro");
+        let err = unsafe { Manifest::parse_avro_mmap(&missing) }.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.message().starts_with("Failed to open manifest file"));
+    }
+
+    #[test]
+    fn test_manifest_writer_add_delete() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let file = |path: &str, content: DataContentType| {
+            test_data_file_builder(path)
+                .content(content)
+                .equality_ids(if content == DataContentType::EqualityDeletes {
+                    vec![1]
+                } else {
+                    vec![]
                 })
+                .build()
+                .unwrap()
+        };
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_deletes();
+
+        let err = writer
+            .add_delete(
+                file("s3://testbucket/data.parquet", DataContentType::Data),
+                1,
+            )
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        assert!(err.message().contains("use add_file"));
+
+        writer
+            .add_delete(
+                file(
+                    "s3://testbucket/pos.parquet",
+                    DataContentType::PositionDeletes,
+                ),
+                3,
+            )
+            .unwrap();
+        writer
+            .add_delete(
+                file(
+                    "s3://testbucket/eq.parquet",
+                    DataContentType::EqualityDeletes,
+                ),
+                3,
+            )
+            .unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+
+        let manifest = Manifest::parse_avro(&bs).unwrap();
+        let entries = manifest
+            .entries()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.status(),
+                    entry.content_type(),
+                    entry.sequence_number(),
+                    entry.file_sequence_number(),
+                )
             })
-            .collect()
+            .collect::<Vec<_>>();
+        assert_eq!(entries, vec![
+            (
+                ManifestStatus::Added,
+                DataContentType::PositionDeletes,
+                Some(3),
+                None
+            ),
+            (
+                ManifestStatus::Added,
+                DataContentType::EqualityDeletes,
+                Some(3),
+                None
+            ),
+        ]);
     }
 
-    #[cfg(test)]
-    mod tests {
-        use std::collections::HashMap;
+    #[test]
+    fn test_key_metadata_round_trip() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let key_metadata = vec![0x01, 0x02, 0xAB, 0xCD];
+        let data_file = DataFile::builder()
+            .content(DataContentType::Data)
+            .file_path("s3://testbucket/data/00000.parquet".to_string())
+            .file_format(DataFileFormat::Parquet)
+            .partition(Struct::empty())
+            .record_count(1)
+            .file_size_in_bytes(100)
+            .key_metadata(Some(key_metadata.clone()))
+            .partition_spec_id(0)
+            .build()
+            .unwrap();
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
 
-        use crate::spec::manifest::_serde::{parse_i64_entry, I64Entry};
+        let manifest = Manifest::parse_avro(&bs).unwrap();
+        assert_eq!(
+            manifest.entries()[0].data_file().key_metadata(),
+            Some(key_metadata.as_slice())
+        );
+    }
 
-        #[test]
-        fn test_parse_negative_manifest_entry() {
-            let entries = vec![I64Entry { key: 1, value: -1 }, I64Entry {
-                key: 2,
-                value: 3,
-            }];
+    #[tokio::test]
+    async fn test_manifest_encryption() {
+        #[derive(Debug)]
+        struct XorEncryptionManager;
+
+        #[async_trait::async_trait]
+        impl EncryptionManager for XorEncryptionManager {
+            async fn encrypt(&self, bytes: Bytes, key_metadata: &mut Vec<u8>) -> Result<Bytes> {
+                key_metadata.push(0x5a);
+                Ok(bytes.iter().map(|b| b ^ 0x5a).collect())
+            }
 
-            let ret = parse_i64_entry(entries).unwrap();
+            async fn decrypt(&self, bytes: Bytes, key_metadata: &[u8]) -> Result<Bytes> {
+                let key = *key_metadata
+                    .last()
+                    .ok_or_else(|| Error::new(ErrorKind::DataInvalid, "Missing key metadata"))?;
+                Ok(bytes.iter().map(|b| b ^ key).collect())
+            }
+        }
 
-            let expected_ret = HashMap::from([(2, 3)]);
-            assert_eq!(ret, expected_ret, "Negative i64 entry should be ignored!");
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+        let encryption_manager: Arc<dyn EncryptionManager> = Arc::new(XorEncryptionManager);
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(1), vec![1], schema, partition_spec)
+                .with_encryption_manager(Some(encryption_manager.clone()))
+                .build_v2_data();
+        writer.add_file(data_file.clone(), 1).unwrap();
+        let manifest_file = writer.write_manifest_file().await.unwrap();
+        assert_eq!(manifest_file.key_metadata, vec![1, 0x5a]);
+
+        let bs = io
+            .new_input(&manifest_file.manifest_path)
+            .unwrap()
+            .read()
+            .await
+            .unwrap();
+        assert_eq!(manifest_file.manifest_length, bs.len() as i64);
+        assert!(Manifest::parse_avro(&bs).is_err());
+        assert!(ManifestReader::default()
+            .read_encrypted(bs.clone(), &manifest_file.key_metadata)
+            .await
+            .is_err());
+
+        let manifest = ManifestReader::builder()
+            .encryption_manager(encryption_manager)
+            .build()
+            .read_encrypted(bs, &manifest_file.key_metadata)
+            .await
+            .unwrap();
+        assert_eq!(manifest.entries().len(), 1);
+        assert_eq!(manifest.entries()[0].data_file(), &data_file);
+    }
+
+    #[tokio::test]
+    async fn test_manifest_reader_stream() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let mut files = vec![];
+        for i in 0..3 {
+            let mut writer = ManifestWriterBuilder::new(
+                io.new_output(format!("memory:/test_manifest_{i}.avro"))
+                    .unwrap(),
+                Some(1),
+                vec![],
+                schema.clone(),
+                partition_spec.clone(),
+            )
+            .build_v2_data();
+            for j in 0..=i {
+                let data_file = test_data_file(format!("s3://testbucket/data/{i}-{j}.parquet"));
+                writer.add_file(data_file, 1).unwrap();
+            }
+            let manifest_file = writer.write_manifest_file().await.unwrap();
+            let input = io.new_input(&manifest_file.manifest_path).unwrap();
+            files.push((manifest_file, input));
         }
+        let mut missing = files[0].0.clone();
+        missing.manifest_path = "memory:/missing.avro".to_string();
+        let input = io.new_input(&missing.manifest_path).unwrap();
+        files.insert(1, (missing, input));
+
+        let results = ManifestReader::default()
+            .stream(files, 2)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(results.len(), 4);
+        assert!(results[1].is_err());
+        let read = results
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|(manifest_file, manifest)| {
+                (manifest_file.manifest_path, manifest.entries().len())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(read, vec![
+            ("memory:/test_manifest_0.avro".to_string(), 1),
+            ("memory:/test_manifest_1.avro".to_string(), 2),
+            ("memory:/test_manifest_2.avro".to_string(), 3),
+        ]);
+    }
+
+    #[test]
+    fn test_manifest_inherit_from() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let writer = ManifestWriterBuilder::new(
+            io.new_output("memory:/test_manifest.avro").unwrap(),
+            Some(5),
+            vec![],
+            schema.clone(),
+            partition_spec.clone(),
+        )
+        .build_v2_data();
+        let (_, mut manifest_file) = writer.write_to_bytes().unwrap();
+        manifest_file.sequence_number = 7;
+
+        let data_file = test_data_file("s3://testbucket/data.parquet");
+        let entry = |status, snapshot_id, sequence_number| ManifestEntry {
+            status,
+            snapshot_id,
+            sequence_number,
+            file_sequence_number: sequence_number,
+            data_file: data_file.clone(),
+        };
+        let metadata = ManifestMetadata::builder()
+            .schema_id(0)
+            .schema(schema)
+            .partition_spec(partition_spec)
+            .format_version(FormatVersion::V2)
+            .content(ManifestContentType::Data)
+            .build();
+        let mut manifest = Manifest::new(metadata, vec![
+            entry(ManifestStatus::Added, None, None),
+            entry(ManifestStatus::Existing, Some(1), Some(3)),
+            entry(ManifestStatus::Existing, Some(1), None),
+        ]);
+
+        manifest.inherit_from(&manifest_file);
+        let inherited = manifest
+            .entries()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.snapshot_id(),
+                    entry.sequence_number(),
+                    entry.file_sequence_number(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(inherited, vec![
+            (Some(5), Some(7), Some(7)),
+            (Some(1), Some(3), Some(3)),
+            (Some(1), None, None),
+        ]);
+
+        // Existing entries inherit sequence numbers from the initial sequence number.
+        manifest_file.sequence_number = INITIAL_SEQUENCE_NUMBER;
+        manifest.inherit_from(&manifest_file);
+        assert_eq!(
+            manifest.entries()[2].sequence_number(),
+            Some(INITIAL_SEQUENCE_NUMBER)
+        );
+    }
+
+    #[test]
+    fn test_timestamptz_ns_bounds_round_trip() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![Arc::new(NestedField::optional(
+                    1,
+                    "ts",
+                    Type::Primitive(PrimitiveType::TimestamptzNs),
+                ))])
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = test_partition_spec(&schema);
+        let lower = Datum::timestamptz_nanos(1_700_000_000_123_456_789);
+        let upper = Datum::timestamptz_nanos(1_700_000_000_987_654_321);
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .record_count(2)
+            .value_counts(HashMap::from([(1, 2)]))
+            .lower_bounds(HashMap::from([(1, lower.clone())]))
+            .upper_bounds(HashMap::from([(1, upper.clone())]))
+            .build()
+            .unwrap();
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+
+        let manifest = Manifest::parse_avro(&bs).unwrap();
+        let data_file = manifest.entries()[0].data_file();
+        assert_eq!(data_file.lower_bounds().get(&1), Some(&lower));
+        assert_eq!(data_file.upper_bounds().get(&1), Some(&upper));
+        assert_eq!(
+            data_file.lower_bounds()[&1].data_type(),
+            &PrimitiveType::TimestamptzNs
+        );
+    }
+
+    #[test]
+    fn test_parse_avro_projected() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
+                    Arc::new(NestedField::optional(
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Int),
+                    )),
+                    Arc::new(NestedField::optional(
+                        2,
+                        "value",
+                        Type::Primitive(PrimitiveType::Double),
+                    )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .column_sizes(HashMap::from([(1, 10), (2, 20)]))
+            .value_counts(HashMap::from([(1, 1), (2, 1)]))
+            .null_value_counts(HashMap::from([(1, 0), (2, 0)]))
+            .nan_value_counts(HashMap::from([(2, 0)]))
+            .lower_bounds(HashMap::from([(1, Datum::int(1)), (2, Datum::double(1.5))]))
+            .upper_bounds(HashMap::from([(1, Datum::int(1)), (2, Datum::double(1.5))]))
+            .build()
+            .unwrap();
+        let mut writer = test_writer_builder(schema.clone(), partition_spec).build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+
+        let projection = Schema::builder()
+            .with_fields(vec![schema.field_by_id(1).unwrap().clone()])
+            .build()
+            .unwrap();
+        let manifest = Manifest::parse_avro_projected(&bs, &projection).unwrap();
+        assert_eq!(manifest.metadata().schema(), &schema);
+        let data_file = manifest.entries()[0].data_file();
+        assert_eq!(data_file.column_sizes(), &HashMap::from([(1, 10)]));
+        assert_eq!(data_file.value_counts(), &HashMap::from([(1, 1)]));
+        assert_eq!(data_file.null_value_counts(), &HashMap::from([(1, 0)]));
+        assert!(data_file.nan_value_counts().is_empty());
+        assert_eq!(
+            data_file.lower_bounds(),
+            &HashMap::from([(1, Datum::int(1))])
+        );
+        assert_eq!(
+            data_file.upper_bounds(),
+            &HashMap::from([(1, Datum::int(1))])
+        );
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use std::fs;
-    use std::io::Cursor;
-    use std::sync::Arc;
 
-    use tempfile::TempDir;
+    #[test]
+    fn test_parse_avro_with_codecs() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+        let expected = Manifest::parse_avro(&bs).unwrap();
+
+        // Rewrite the manifest with every codec the avro crate is built with for tests.
+        let reader = AvroReader::new(&bs[..]).unwrap();
+        let avro_schema = reader.writer_schema().clone();
+        let user_metadata = reader.user_metadata().clone();
+        let values = reader.map(|value| value.unwrap()).collect::<Vec<_>>();
+        for name in ["null", "deflate", "snappy", "zstandard"] {
+            let codec = apache_avro::Codec::from_str(name)
+                .unwrap_or_else(|_| panic!("avro codec {name} isn't compiled in"));
+            let mut avro_writer = AvroWriter::with_codec(&avro_schema, Vec::new(), codec);
+            for (key, value) in &user_metadata {
+                avro_writer.add_user_metadata(key.clone(), value).unwrap();
+            }
+            for value in &values {
+                avro_writer.append(value.clone()).unwrap();
+            }
+            let bs = avro_writer.into_inner().unwrap();
+            assert_eq!(Manifest::parse_avro(&bs).unwrap(), expected, "{codec:?}");
+        }
 
-    use super::*;
-    use crate::io::FileIOBuilder;
-    use crate::spec::{Literal, NestedField, PrimitiveType, Struct, Transform, Type};
+        let key = b"\x14avro.codec\x08null";
+        let pos = bs.windows(key.len()).position(|w| w == key).unwrap();
+        let mut unsupported = bs[..pos].to_vec();
+        unsupported.extend_from_slice(b"\x14avro.codec\x06lzo");
+        unsupported.extend_from_slice(&bs[pos + key.len()..]);
+        let err = Manifest::parse_avro(&unsupported).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::FeatureUnsupported);
+        assert_eq!(err.message(), "unsupported avro codec: lzo");
+    }
 
-    #[tokio::test]
-    async fn test_parse_manifest_v2_unpartition() {
+    #[test]
+    fn test_manifest_writer_with_sort_equality_ids() {
         let schema = Arc::new(
             Schema::builder()
                 .with_fields(vec![
-                    // id v_int v_long v_float v_double v_varchar v_bool v_date v_timestamp v_decimal v_ts_ntz
                     Arc::new(NestedField::optional(
                         1,
                         "id",
-                        Type::Primitive(PrimitiveType::Long),
+                        Type::Primitive(PrimitiveType::Int),
                     )),
                     Arc::new(NestedField::optional(
                         2,
-                        "v_int",
-                        Type::Primitive(PrimitiveType::Int),
+                        "name",
+                        Type::Primitive(PrimitiveType::String),
                     )),
                     Arc::new(NestedField::optional(
                         3,
-                        "v_long",
+                        "ts",
                         Type::Primitive(PrimitiveType::Long),
                     )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = test_partition_spec(&schema);
+        let delete_file = test_data_file_builder("s3://testbucket/eq.parquet")
+            .content(DataContentType::EqualityDeletes)
+            .equality_ids(vec![3, 1, 2])
+            .build()
+            .unwrap();
+        let equality_ids = |sort_equality_ids: bool| {
+            let mut writer = test_writer_builder(schema.clone(), partition_spec.clone())
+                .with_sort_equality_ids(sort_equality_ids)
+                .build_v2_deletes();
+            writer.add_file(delete_file.clone(), 1).unwrap();
+            let (bs, _) = writer.write_to_bytes().unwrap();
+            Manifest::parse_avro(&bs).unwrap().entries()[0]
+                .data_file()
+                .equality_ids()
+                .to_vec()
+        };
+
+        assert_eq!(equality_ids(false), vec![3, 1, 2]);
+        assert_eq!(equality_ids(true), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_manifest_writer_with_bounds_byte_budget() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
                     Arc::new(NestedField::optional(
-                        4,
-                        "v_float",
-                        Type::Primitive(PrimitiveType::Float),
-                    )),
-                    Arc::new(NestedField::optional(
-                        5,
-                        "v_double",
-                        Type::Primitive(PrimitiveType::Double),
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Int),
                     )),
                     Arc::new(NestedField::optional(
-                        6,
-                        "v_varchar",
+                        2,
+                        "name",
                         Type::Primitive(PrimitiveType::String),
                     )),
                     Arc::new(NestedField::optional(
-                        7,
-                        "v_bool",
-                        Type::Primitive(PrimitiveType::Boolean),
-                    )),
-                    Arc::new(NestedField::optional(
-                        8,
-                        "v_date",
-                        Type::Primitive(PrimitiveType::Date),
-                    )),
-                    Arc::new(NestedField::optional(
-                        9,
-                        "v_timestamp",
-                        Type::Primitive(PrimitiveType::Timestamptz),
-                    )),
-                    Arc::new(NestedField::optional(
-                        10,
-                        "v_decimal",
-                        Type::Primitive(PrimitiveType::Decimal {
-                            precision: 36,
-                            scale: 10,
-                        }),
+                        3,
+                        "ts",
+                        Type::Primitive(PrimitiveType::Long),
                     )),
+                ])
+                .build()
+                .unwrap(),
+        );
+        let partition_spec = test_partition_spec(&schema);
+        // The bounds take 8 bytes for id, 20 for name and 16 for ts.
+        let data_file = |path: &str| {
+            test_data_file_builder(path)
+                .value_counts(HashMap::from([(1, 1), (2, 1), (3, 1)]))
+                .lower_bounds(HashMap::from([
+                    (1, Datum::int(1)),
+                    (2, Datum::string("aaaaaaaaaa")),
+                    (3, Datum::long(1)),
+                ]))
+                .upper_bounds(HashMap::from([
+                    (1, Datum::int(9)),
+                    (2, Datum::string("zzzzzzzzzz")),
+                    (3, Datum::long(9)),
+                ]))
+                .build()
+                .unwrap()
+        };
+        let write = |budget: Option<usize>| {
+            let mut writer = test_writer_builder(schema.clone(), partition_spec.clone())
+                .with_bounds_byte_budget(budget)
+                .build_v2_data();
+            writer
+                .add_file(data_file("s3://testbucket/data.parquet"), 1)
+                .unwrap();
+            let dropped = writer.bounds_dropped_by_budget().to_vec();
+            let (bs, _) = writer.write_to_bytes().unwrap();
+            let manifest = Manifest::parse_avro(&bs).unwrap();
+            let data_file = manifest.entries()[0].data_file().clone();
+            (data_file, dropped)
+        };
+        let bound_ids = |data_file: &DataFile| {
+            (
+                data_file
+                    .lower_bounds()
+                    .keys()
+                    .copied()
+                    .sorted()
+                    .collect_vec(),
+                data_file
+                    .upper_bounds()
+                    .keys()
+                    .copied()
+                    .sorted()
+                    .collect_vec(),
+            )
+        };
+
+        let (written, dropped) = write(None);
+        assert_eq!(bound_ids(&written), (vec![1, 2, 3], vec![1, 2, 3]));
+        assert!(dropped.is_empty());
+
+        let (written, dropped) = write(Some(44));
+        assert_eq!(bound_ids(&written), (vec![1, 2, 3], vec![1, 2, 3]));
+        assert!(dropped.is_empty());
+
+        let (written, dropped) = write(Some(30));
+        assert_eq!(bound_ids(&written), (vec![1, 3], vec![1, 3]));
+        assert_eq!(dropped, vec![(
+            "s3://testbucket/data.parquet".to_string(),
+            vec![2]
+        )]);
+        assert_eq!(written.value_counts().len(), 3);
+
+        let (written, dropped) = write(Some(10));
+        assert_eq!(bound_ids(&written), (vec![1], vec![1]));
+        assert_eq!(dropped, vec![(
+            "s3://testbucket/data.parquet".to_string(),
+            vec![2, 3]
+        )]);
+    }
+
+    #[test]
+    fn test_read_lazy_manifest_entries() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .column_sizes(HashMap::from([(1, 10)]))
+            .value_counts(HashMap::from([(1, 1)]))
+            .null_value_counts(HashMap::from([(1, 0)]))
+            .lower_bounds(HashMap::from([(1, Datum::int(3))]))
+            .upper_bounds(HashMap::from([(1, Datum::int(3))]))
+            .build()
+            .unwrap();
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+
+        let reader = ManifestReader::default();
+        let (_, eager) = reader.read_parts(&bs).unwrap();
+        let (_, lazy, _) = reader.read_lazy(&bs).unwrap();
+        assert_eq!(lazy.len(), 1);
+
+        let entry = &lazy[0];
+        assert_eq!(entry.status(), ManifestStatus::Added);
+        assert_eq!(entry.snapshot_id(), Some(1));
+        assert_eq!(entry.data_file(), &eager[0].data_file.without_stats());
+        assert_eq!(entry.decode_stats().unwrap(), &eager[0].data_file);
+        // Decoded once, then served from the cache.
+        assert!(std::ptr::eq(
+            entry.decode_stats().unwrap(),
+            entry.decode_stats().unwrap()
+        ));
+
+        let mut lazy = lazy;
+        assert_eq!(lazy.pop().unwrap().into_entry().unwrap(), eager[0]);
+    }
+
+    #[test]
+    fn test_referenced_data_file_only_for_delete_files() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let file = |content: DataContentType| {
+            test_data_file_builder("s3://testbucket/data/00000.parquet")
+                .content(content)
+                .referenced_data_file(Some("s3://testbucket/data/a.parquet".to_string()))
+                .build()
+                .unwrap()
+        };
+        let io = FileIOBuilder::new("memory").build().unwrap();
+
+        let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+        let mut writer = ManifestWriterBuilder::new(
+            output_file,
+            Some(1),
+            vec![],
+            schema.clone(),
+            partition_spec.clone(),
+        )
+        .build_v2_data();
+        let err = writer.add_file(file(DataContentType::Data), 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+
+        let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(1), vec![], schema, partition_spec)
+                .build_v2_deletes();
+        writer
+            .add_file(file(DataContentType::PositionDeletes), 1)
+            .unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+        let manifest = Manifest::parse_avro(&bs).unwrap();
+        assert_eq!(
+            manifest.entries()[0].referenced_data_file(),
+            Some("s3://testbucket/data/a.parquet")
+        );
+    }
+
+    #[test]
+    fn test_read_manifest_with_mismatched_partition_field_ids() {
+        let schema = test_schema();
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .with_spec_id(0)
+            .add_partition_field("id", "id", Transform::Identity)
+            .unwrap()
+            .build()
+            .unwrap();
+        // The partition tuple as written by a producer that got the field ids wrong.
+        let written_partition_type = StructType::new(vec![Arc::new(NestedField::optional(
+            2000,
+            "id_partition",
+            Type::Primitive(PrimitiveType::Int),
+        ))]);
+        let entry = ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder("s3://testbucket/data/00000.parquet")
+                .partition(Struct::from_iter([Some(Literal::int(7))]))
+                .build()
+                .unwrap(),
+        };
+
+        let avro_schema = manifest_schema_v2(&written_partition_type).unwrap();
+        let mut avro_writer = AvroWriter::new(&avro_schema, Vec::new());
+        avro_writer
+            .add_user_metadata("schema".to_string(), to_vec(&schema).unwrap())
+            .unwrap();
+        avro_writer
+            .add_user_metadata(
+                "partition-spec".to_string(),
+                to_vec(&partition_spec.fields()).unwrap(),
+            )
+            .unwrap();
+        avro_writer
+            .add_user_metadata("partition-spec-id".to_string(), "0")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("format-version".to_string(), "2")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("content".to_string(), "data")
+            .unwrap();
+        let value = to_value(
+            _serde::ManifestEntryV2::try_from(entry.clone(), &written_partition_type).unwrap(),
+        )
+        .unwrap()
+        .resolve(&avro_schema)
+        .unwrap();
+        avro_writer.append(value).unwrap();
+        let bs = avro_writer.into_inner().unwrap();
+
+        let err = ManifestReader::default().read(&bs).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+
+        let (manifest, diagnostics) = ManifestReader::builder()
+            .tolerant_partition_field_ids(true)
+            .build()
+            .read_with_diagnostics(&bs)
+            .unwrap();
+        assert_eq!(manifest.entries(), &[Arc::new(entry)]);
+        assert_eq!(
+            manifest.entries()[0].partition(),
+            &Struct::from_iter([Some(Literal::int(7))])
+        );
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_read_manifest_with_partition_spec_without_field_ids() {
+        let schema = Arc::new(
+            Schema::builder()
+                .with_fields(vec![
                     Arc::new(NestedField::optional(
-                        11,
-                        "v_ts_ntz",
-                        Type::Primitive(PrimitiveType::Timestamp),
+                        1,
+                        "id",
+                        Type::Primitive(PrimitiveType::Int),
                     )),
                     Arc::new(NestedField::optional(
-                        12,
-                        "v_ts_ns_ntz",
-                        Type::Primitive(PrimitiveType::TimestampNs),
+                        2,
+                        "category",
+                        Type::Primitive(PrimitiveType::String),
                     )),
                 ])
                 .build()
                 .unwrap(),
         );
-        let metadata = ManifestMetadata {
-            schema_id: 0,
-            schema: schema.clone(),
-            partition_spec: PartitionSpec::builder(schema)
-                .with_spec_id(0)
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .with_spec_id(0)
+            .add_partition_field("id", "id", Transform::Identity)
+            .unwrap()
+            .add_partition_field("category", "category", Transform::Identity)
+            .unwrap()
+            .build()
+            .unwrap();
+        let partition_type = partition_spec.partition_type(&schema).unwrap();
+        let entry = ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder("s3://testbucket/data/00000.parquet")
+                .partition(Struct::from_iter([
+                    Some(Literal::int(7)),
+                    Some(Literal::string("a")),
+                ]))
                 .build()
                 .unwrap(),
-            content: ManifestContentType::Data,
-            format_version: FormatVersion::V2,
         };
-        let mut entries = vec![
-                ManifestEntry {
-                    status: ManifestStatus::Added,
-                    snapshot_id: None,
-                    sequence_number: None,
-                    file_sequence_number: None,
-                    data_file: DataFile {content:DataContentType::Data,file_path:"s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),file_format:DataFileFormat::Parquet,partition:Struct::empty(),record_count:1,file_size_in_bytes:5442,column_sizes:HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),value_counts:HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),null_value_counts:HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),nan_value_counts:HashMap::new(),lower_bounds:HashMap::new(),upper_bounds:HashMap::new(),key_metadata:None,split_offsets:vec![4],equality_ids:Vec::new(),sort_order_id:None, partition_spec_id: 0 }
+
+        let avro_schema = manifest_schema_v2(&partition_type).unwrap();
+        let mut avro_writer = AvroWriter::new(&avro_schema, Vec::new());
+        avro_writer
+            .add_user_metadata("schema".to_string(), to_vec(&schema).unwrap())
+            .unwrap();
+        // The partition spec as written by a producer that relied on positional ids.
+        avro_writer
+            .add_user_metadata(
+                "partition-spec".to_string(),
+                r#"[
+                    {"source-id": 1, "name": "id", "transform": "identity"},
+                    {"source-id": 2, "name": "category", "transform": "identity"}
+                ]"#,
+            )
+            .unwrap();
+        avro_writer
+            .add_user_metadata("partition-spec-id".to_string(), "0")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("format-version".to_string(), "2")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("content".to_string(), "data")
+            .unwrap();
+        let value =
+            to_value(_serde::ManifestEntryV2::try_from(entry.clone(), &partition_type).unwrap())
+                .unwrap()
+                .resolve(&avro_schema)
+                .unwrap();
+        avro_writer.append(value).unwrap();
+        let bs = avro_writer.into_inner().unwrap();
+
+        let err = ManifestReader::default().read(&bs).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+
+        let manifest = ManifestReader::builder()
+            .assign_missing_partition_field_ids(true)
+            .build()
+            .read(&bs)
+            .unwrap();
+        assert_eq!(manifest.metadata().partition_spec, partition_spec);
+        assert_eq!(
+            manifest
+                .metadata()
+                .partition_spec
+                .fields()
+                .iter()
+                .map(|field| field.field_id)
+                .collect::<Vec<_>>(),
+            vec![1000, 1001]
+        );
+        assert_eq!(manifest.entries(), &[Arc::new(entry)]);
+    }
+
+    #[test]
+    fn test_read_manifest_normalizing_sort_order_id() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = |path: &str, sort_order_id: i32| {
+            test_data_file_builder(path)
+                .sort_order_id(sort_order_id)
+                .build()
+                .unwrap()
+        };
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer
+            .add_file(data_file("s3://testbucket/unsorted.parquet", 0), 1)
+            .unwrap();
+        writer
+            .add_file(data_file("s3://testbucket/sorted.parquet", 1), 1)
+            .unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+
+        let sort_order_ids = |manifest: &Manifest| {
+            manifest
+                .entries()
+                .iter()
+                .map(|entry| entry.data_file().sort_order_id())
+                .collect::<Vec<_>>()
+        };
+        let manifest = ManifestReader::default().read(&bs).unwrap();
+        assert_eq!(sort_order_ids(&manifest), vec![Some(0), Some(1)]);
+
+        let reader = ManifestReader::builder()
+            .normalize_sort_order_id(true)
+            .build();
+        let manifest = reader.read(&bs).unwrap();
+        assert_eq!(sort_order_ids(&manifest), vec![None, Some(1)]);
+        let (_, entries, _) = reader.read_lazy(&bs).unwrap();
+        assert_eq!(entries[0].data_file().sort_order_id(), None);
+        assert_eq!(entries[0].decode_stats().unwrap().sort_order_id(), None);
+    }
+
+    #[test]
+    fn test_read_manifest_with_negative_record_count() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let partition_type = partition_spec.partition_type(&schema).unwrap();
+        let entry = |path: &str| ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder(path)
+                .record_count(5)
+                .build()
+                .unwrap(),
+        };
+
+        let avro_schema = manifest_schema_v2(&partition_type).unwrap();
+        let mut avro_writer = AvroWriter::new(&avro_schema, Vec::new());
+        avro_writer
+            .add_user_metadata("schema".to_string(), to_vec(&schema).unwrap())
+            .unwrap();
+        avro_writer
+            .add_user_metadata("partition-spec".to_string(), b"[]")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("format-version".to_string(), "2")
+            .unwrap();
+        for (path, record_count) in [
+            ("s3://testbucket/data/00000.parquet", -1),
+            ("s3://testbucket/data/00001.parquet", 5),
+        ] {
+            let mut value =
+                to_value(_serde::ManifestEntryV2::try_from(entry(path), &partition_type).unwrap())
+                    .unwrap();
+            // A producer writing a negative record count as a sentinel.
+            let apache_avro::types::Value::Record(fields) = &mut value else {
+                panic!("manifest entry should be a record");
+            };
+            for (name, field) in fields.iter_mut() {
+                if let ("data_file", apache_avro::types::Value::Record(data_file)) =
+                    (name.as_str(), field)
+                {
+                    for (name, field) in data_file.iter_mut() {
+                        if name == "record_count" {
+                            *field = apache_avro::types::Value::Long(record_count);
+                        }
+                    }
                 }
-            ];
+            }
+            avro_writer
+                .append(value.resolve(&avro_schema).unwrap())
+                .unwrap();
+        }
+        let bs = avro_writer.into_inner().unwrap();
+
+        assert!(Manifest::parse_avro(&bs).is_err());
+
+        let (manifest, diagnostics) = ManifestReader::builder()
+            .lenient_record_count(true)
+            .build()
+            .read_with_diagnostics(&bs)
+            .unwrap();
+        let record_counts = manifest
+            .entries()
+            .iter()
+            .map(|entry| entry.record_count())
+            .collect::<Vec<_>>();
+        assert_eq!(record_counts, vec![0, 5]);
+        assert_eq!(diagnostics, vec![
+            "Negative record count -1 of data file s3://testbucket/data/00000.parquet read as 0"
+                .to_string()
+        ]);
+
+        let (_, entries, lazy_diagnostics) = ManifestReader::builder()
+            .lenient_record_count(true)
+            .build()
+            .read_lazy(&bs)
+            .unwrap();
+        assert_eq!(entries[0].data_file().record_count(), 0);
+        assert_eq!(lazy_diagnostics, diagnostics);
+    }
+
+    #[test]
+    fn test_parse_avro_multi() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let write = |paths: &[&str]| {
+            let mut writer =
+                test_writer_builder(schema.clone(), partition_spec.clone()).build_v2_data();
+            for path in paths {
+                let data_file = test_data_file(path);
+                writer.add_file(data_file, 1).unwrap();
+            }
+            writer.write_to_bytes().unwrap().0
+        };
+        let first = write(&["s3://testbucket/data/00000.parquet"]);
+        let second = write(&[
+            "s3://testbucket/data/00001.parquet",
+            "s3://testbucket/data/00002.parquet",
+        ]);
+        let empty = write(&[]);
+
+        let bundle = [&first[..], &second[..], &empty[..]].concat();
+        let manifests = Manifest::parse_avro_multi(&bundle).unwrap();
+        assert_eq!(manifests, vec![
+            Manifest::parse_avro(&first).unwrap(),
+            Manifest::parse_avro(&second).unwrap(),
+            Manifest::parse_avro(&empty).unwrap(),
+        ]);
+
+        // A single file is a bundle of one.
+        assert_eq!(Manifest::parse_avro_multi(&first).unwrap().len(), 1);
+
+        // Trailing garbage, or a truncated last file, can't be split cleanly.
+        let err = Manifest::parse_avro_multi(&[&first[..], b"garbage"].concat()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        let truncated = [&first[..], &second[..second.len() - 1]].concat();
+        let err = Manifest::parse_avro_multi(&truncated).unwrap_err();
+        assert_eq!(
+            err.message(),
+            format!(
+                "Bytes at offset {} are not a complete avro object container file",
+                first.len()
+            )
+        );
+        assert!(Manifest::parse_avro_multi(&[]).is_err());
+    }
+
+    #[test]
+    fn test_parse_manifest_with_oversized_split_offsets_length() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let split_offset = 123456789;
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .split_offsets(vec![split_offset])
+            .build()
+            .unwrap();
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+        assert!(Manifest::parse_avro(&bs).is_ok());
+
+        // The avro file is the header, ended by the sync marker, followed by a single
+        // block: object count, block size, the encoded entries and the sync marker.
+        let sync = &bs[bs.len() - 16..];
+        let header_end = bs.windows(16).position(|w| w == sync).unwrap() + 16;
+        let mut pos = header_end;
+        let count = read_avro_long(&bs, &mut pos);
+        let size = read_avro_long(&bs, &mut pos) as usize;
+        let block = &bs[pos..pos + size];
+
+        // Replace the length of the split offsets array, encoded before its only item.
+        let mut item = vec![];
+        write_avro_long(1, &mut item);
+        write_avro_long(split_offset, &mut item);
+        let item_pos = block.windows(item.len()).position(|w| w == item).unwrap();
+        // Lengths under the 512 MiB allocation limit of the avro decoder, counted in
+        // items, still reserve gigabytes of decoded values.
+        for len in [1_i64 << 40, 300_000_000, block.len() as i64] {
+            let mut hostile_block = block[..item_pos].to_vec();
+            write_avro_long(len, &mut hostile_block);
+            hostile_block.extend_from_slice(&block[item_pos + 1..]);
+
+            let mut hostile = bs[..header_end].to_vec();
+            write_avro_long(count, &mut hostile);
+            write_avro_long(hostile_block.len() as i64, &mut hostile);
+            hostile.extend_from_slice(&hostile_block);
+            hostile.extend_from_slice(sync);
+
+            let err = Manifest::parse_avro(&hostile).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::DataInvalid);
+            assert!(err
+                .message()
+                .contains(&format!("declares a length of {len}")));
+        }
+    }
+
+    #[test]
+    fn test_parse_manifest_with_oversized_compressed_block() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+
+        // Switch the codec to zstandard and replace the block with compressed zeros, a
+        // tiny fraction of the size they decompress to.
+        let key = b"\x14avro.codec\x08null";
+        let pos = bs.windows(key.len()).position(|w| w == key).unwrap();
+        let sync = &bs[bs.len() - 16..];
+        let header_end = bs.windows(16).position(|w| w == sync).unwrap() + 16;
+        let mut hostile = bs[..pos].to_vec();
+        hostile.extend_from_slice(b"\x14avro.codec\x12zstandard");
+        hostile.extend_from_slice(&bs[pos + key.len()..header_end]);
+        let block = zstd::encode_all(&vec![0; MAX_AVRO_BLOCK_SIZE + 1][..], 0).unwrap();
+        write_avro_long(1, &mut hostile);
+        write_avro_long(block.len() as i64, &mut hostile);
+        hostile.extend_from_slice(&block);
+        hostile.extend_from_slice(sync);
+
+        let err = Manifest::parse_avro(&hostile).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        assert_eq!(
+            err.message(),
+            format!(
+                "Manifest file has an avro block larger than {MAX_AVRO_BLOCK_SIZE} bytes once decompressed"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_avro_expecting() {
+        let schema = Schema::builder()
+            .with_schema_id(1)
+            .with_fields(vec![Arc::new(NestedField::optional(
+                1,
+                "id",
+                Type::Primitive(PrimitiveType::Int),
+            ))])
+            .build()
+            .unwrap();
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
 
-        // write manifest to file
         let tmp_dir = TempDir::new().unwrap();
         let path = tmp_dir.path().join("test_manifest.avro");
         let io = FileIOBuilder::new_fs_io().build().unwrap();
         let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let partition_spec = PartitionSpec::builder(Arc::new(schema.clone()))
+            .with_spec_id(0)
+            .build()
+            .unwrap();
         let mut writer = ManifestWriterBuilder::new(
             output_file,
             Some(1),
             vec![],
-            metadata.schema.clone(),
-            metadata.partition_spec.clone(),
+            Arc::new(schema.clone()),
+            partition_spec,
         )
         .build_v2_data();
-        for entry in &entries {
-            writer.add_entry(entry.clone()).unwrap();
-        }
+        writer.add_file(data_file, 1).unwrap();
         writer.write_manifest_file().await.unwrap();
+        let bs = fs::read(path).expect("read_file must succeed");
 
-        // read back the manifest file and check the content
-        let actual_manifest =
-            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
-                .unwrap();
-        // The snapshot id is assigned when the entry is added to the manifest.
-        entries[0].snapshot_id = Some(1);
-        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+        let manifest = Manifest::parse_avro_expecting(&bs, &schema).unwrap();
+        assert_eq!(manifest.entries().len(), 1);
+
+        let other_id = Schema::builder()
+            .with_schema_id(2)
+            .with_fields(schema.as_struct().fields().to_vec())
+            .build()
+            .unwrap();
+        assert!(Manifest::parse_avro_expecting(&bs, &other_id).is_err());
+
+        let other_fields = Schema::builder()
+            .with_schema_id(1)
+            .with_fields(vec![Arc::new(NestedField::required(
+                1,
+                "id",
+                Type::Primitive(PrimitiveType::Long),
+            ))])
+            .build()
+            .unwrap();
+        assert!(Manifest::parse_avro_expecting(&bs, &other_fields).is_err());
     }
 
-    #[tokio::test]
-    async fn test_parse_manifest_v2_partition() {
-        let schema = Arc::new(
-            Schema::builder()
-                .with_fields(vec![
-                    Arc::new(NestedField::optional(
-                        1,
-                        "id",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                    Arc::new(NestedField::optional(
-                        2,
-                        "v_int",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                    Arc::new(NestedField::optional(
-                        3,
-                        "v_long",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                    Arc::new(NestedField::optional(
-                        4,
-                        "v_float",
-                        Type::Primitive(PrimitiveType::Float),
-                    )),
-                    Arc::new(NestedField::optional(
-                        5,
-                        "v_double",
-                        Type::Primitive(PrimitiveType::Double),
-                    )),
-                    Arc::new(NestedField::optional(
-                        6,
-                        "v_varchar",
-                        Type::Primitive(PrimitiveType::String),
-                    )),
-                    Arc::new(NestedField::optional(
-                        7,
-                        "v_bool",
-                        Type::Primitive(PrimitiveType::Boolean),
-                    )),
-                    Arc::new(NestedField::optional(
-                        8,
-                        "v_date",
-                        Type::Primitive(PrimitiveType::Date),
-                    )),
-                    Arc::new(NestedField::optional(
-                        9,
-                        "v_timestamp",
-                        Type::Primitive(PrimitiveType::Timestamptz),
-                    )),
-                    Arc::new(NestedField::optional(
-                        10,
-                        "v_decimal",
-                        Type::Primitive(PrimitiveType::Decimal {
-                            precision: 36,
-                            scale: 10,
-                        }),
-                    )),
-                    Arc::new(NestedField::optional(
-                        11,
-                        "v_ts_ntz",
-                        Type::Primitive(PrimitiveType::Timestamp),
-                    )),
-                    Arc::new(NestedField::optional(
-                        12,
-                        "v_ts_ns_ntz",
-                        Type::Primitive(PrimitiveType::TimestampNs),
-                    )),
-                ])
+    #[test]
+    fn test_parse_avro_with_schema() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let partition_type = partition_spec.partition_type(&schema).unwrap();
+        let entry = ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder("s3://testbucket/data/00000.parquet")
+                .lower_bounds(HashMap::from([(1, Datum::int(1))]))
+                .upper_bounds(HashMap::from([(1, Datum::int(9))]))
                 .build()
                 .unwrap(),
-        );
-        let metadata = ManifestMetadata {
-            schema_id: 0,
-            schema: schema.clone(),
-            partition_spec: PartitionSpec::builder(schema)
-                .with_spec_id(0)
-                .add_partition_field("v_int", "v_int", Transform::Identity)
-                .unwrap()
-                .add_partition_field("v_long", "v_long", Transform::Identity)
+        };
+
+        let avro_schema = manifest_schema_v2(&partition_type).unwrap();
+        let mut avro_writer = AvroWriter::new(&avro_schema, Vec::new());
+        avro_writer
+            .add_user_metadata("schema".to_string(), b"{not json")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("partition-spec".to_string(), b"[]")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("format-version".to_string(), "2")
+            .unwrap();
+        avro_writer
+            .add_user_metadata("content".to_string(), "data")
+            .unwrap();
+        let value =
+            to_value(_serde::ManifestEntryV2::try_from(entry.clone(), &partition_type).unwrap())
                 .unwrap()
-                .build()
-                .unwrap(),
-            content: ManifestContentType::Data,
-            format_version: FormatVersion::V2,
+                .resolve(&avro_schema)
+                .unwrap();
+        avro_writer.append(value).unwrap();
+        let bs = avro_writer.into_inner().unwrap();
+
+        assert!(Manifest::parse_avro(&bs).is_err());
+
+        let manifest = Manifest::parse_avro_with_schema(&bs, schema.clone()).unwrap();
+        assert_eq!(manifest.metadata().schema(), &schema);
+        assert_eq!(manifest.entries().len(), 1);
+        assert_eq!(manifest.entries()[0].as_ref(), &entry);
+    }
+
+    #[tokio::test]
+    async fn test_parse_avro_with_schema_rescales_decimal_bounds() {
+        let decimal_schema = |precision, scale| {
+            Arc::new(
+                Schema::builder()
+                    .with_fields(vec![Arc::new(NestedField::optional(
+                        1,
+                        "price",
+                        Type::Primitive(PrimitiveType::Decimal { precision, scale }),
+                    ))])
+                    .build()
+                    .unwrap(),
+            )
         };
-        let mut entries = vec![ManifestEntry {
-                status: ManifestStatus::Added,
-                snapshot_id: None,
-                sequence_number: None,
-                file_sequence_number: None,
-                data_file: DataFile {
-                    content: DataContentType::Data,
-                    file_format: DataFileFormat::Parquet,
-                    file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-378b56f5-5c52-4102-a2c2-f05f8a7cbe4a-00000.parquet".to_string(),
-                    partition: Struct::from_iter(
-                        vec![
-                            Some(Literal::int(1)),
-                            Some(Literal::long(1000)),
-                        ]
-                            .into_iter()
-                    ),
-                    record_count: 1,
-                    file_size_in_bytes: 5442,
-                    column_sizes: HashMap::from([
-                        (0, 73),
-                        (6, 34),
-                        (2, 73),
-                        (7, 61),
-                        (3, 61),
-                        (5, 62),
-                        (9, 79),
-                        (10, 73),
-                        (1, 61),
-                        (4, 73),
-                        (8, 73)
-                    ]),
-                    value_counts: HashMap::from([
-                        (4, 1),
-                        (5, 1),
-                        (2, 1),
-                        (0, 1),
-                        (3, 1),
-                        (6, 1),
-                        (8, 1),
-                        (1, 1),
-                        (10, 1),
-                        (7, 1),
-                        (9, 1)
-                    ]),
-                    null_value_counts: HashMap::from([
-                        (1, 0),
-                        (6, 0),
-                        (2, 0),
-                        (8, 0),
-                        (0, 0),
-                        (3, 0),
-                        (5, 0),
-                        (9, 0),
-                        (7, 0),
-                        (4, 0),
-                        (10, 0)
-                    ]),
-                    nan_value_counts: HashMap::new(),
-                    lower_bounds: HashMap::new(),
-                    upper_bounds: HashMap::new(),
-                    key_metadata: None,
-                    split_offsets: vec![4],
-                    equality_ids: vec![],
-                    sort_order_id: None,
-                    partition_spec_id: 0
+        let decimal = |unscaled, scale| {
+            Datum::new(
+                PrimitiveType::Decimal {
+                    precision: 10,
+                    scale,
                 },
-            }];
+                PrimitiveLiteral::Int128(unscaled),
+            )
+        };
+        let schema = decimal_schema(10, 2);
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .lower_bounds(HashMap::from([(1, decimal(150, 2))]))
+            .upper_bounds(HashMap::from([(1, decimal(1234, 2))]))
+            .build()
+            .unwrap();
+
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+        let (bs, _) = writer.write_to_bytes().unwrap();
+
+        let (manifest, diagnostics) =
+            Manifest::parse_avro_with_schema_diagnostics(&bs, decimal_schema(10, 3)).unwrap();
+        assert!(diagnostics.is_empty());
+        let data_file = manifest.entries()[0].data_file();
+        assert_eq!(data_file.lower_bounds()[&1], decimal(1500, 3));
+        assert_eq!(data_file.upper_bounds()[&1], decimal(12340, 3));
+
+        let (manifest, diagnostics) =
+            Manifest::parse_avro_with_schema_diagnostics(&bs, decimal_schema(10, 1)).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].contains("upper bound of field id 1"));
+        let data_file = manifest.entries()[0].data_file();
+        assert_eq!(data_file.lower_bounds()[&1], decimal(15, 1));
+        assert!(!data_file.upper_bounds().contains_key(&1));
+    }
+
+    #[tokio::test]
+    async fn test_manifest_writer_with_sequence_number() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
 
-        // write manifest to file and check the return manifest file.
         let tmp_dir = TempDir::new().unwrap();
-        let path = tmp_dir.path().join("test_manifest.avro");
         let io = FileIOBuilder::new_fs_io().build().unwrap();
-        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        for (sequence_number, expected) in [(None, UNASSIGNED_SEQUENCE_NUMBER), (Some(5), 5)] {
+            let path = tmp_dir
+                .path()
+                .join(format!("test_manifest_{expected}.avro"));
+            let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+            let mut builder = ManifestWriterBuilder::new(
+                output_file,
+                Some(1),
+                vec![],
+                schema.clone(),
+                partition_spec.clone(),
+            );
+            if let Some(sequence_number) = sequence_number {
+                builder = builder.with_sequence_number(sequence_number);
+            }
+            let mut writer = builder.build_v2_data();
+            writer
+                .add_file(data_file.clone(), UNASSIGNED_SEQUENCE_NUMBER)
+                .unwrap();
+            let manifest_file = writer.write_manifest_file().await.unwrap();
+            assert_eq!(manifest_file.sequence_number, expected);
+            assert_eq!(manifest_file.min_sequence_number, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_file_with_snapshot_id() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = |path: &str| test_data_file(path);
+
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(5), vec![], schema, partition_spec)
+                .build_v2_data();
+        writer
+            .add_file(data_file("s3://testbucket/data/00000.parquet"), 5)
+            .unwrap();
+        writer
+            .add_file_with_snapshot_id(data_file("s3://testbucket/data/00001.parquet"), 3, 3)
+            .unwrap();
+        let (bs, manifest_file) = writer.write_to_bytes().unwrap();
+        assert_eq!(manifest_file.added_snapshot_id, 5);
+        assert_eq!(manifest_file.added_files_count, Some(2));
+
+        let manifest = Manifest::parse_avro(&bs).unwrap();
+        let entries: Vec<_> = manifest
+            .entries()
+            .iter()
+            .map(|entry| (entry.status(), entry.snapshot_id(), entry.sequence_number()))
+            .collect();
+        assert_eq!(entries, vec![
+            (ManifestStatus::Added, Some(5), Some(5)),
+            (ManifestStatus::Added, Some(3), Some(3)),
+        ]);
+    }
+
+    #[test]
+    fn test_manifest_writer_add_rewritten_entries() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let entry = |path: &str, status: ManifestStatus, sequence_number: i64| ManifestEntry {
+            status,
+            snapshot_id: Some(sequence_number),
+            sequence_number: Some(sequence_number),
+            file_sequence_number: Some(sequence_number),
+            data_file: test_data_file(path),
+        };
+        let entries = vec![
+            Arc::new(entry("s3://testbucket/a.parquet", ManifestStatus::Added, 1)),
+            Arc::new(entry(
+                "s3://testbucket/b.parquet",
+                ManifestStatus::Existing,
+                2,
+            )),
+            Arc::new(entry(
+                "s3://testbucket/c.parquet",
+                ManifestStatus::Deleted,
+                2,
+            )),
+            Arc::new(entry("s3://testbucket/d.parquet", ManifestStatus::Added, 3)),
+        ];
+
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(4), vec![], schema, partition_spec)
+                .build_v2_data();
+        writer
+            .add_rewritten_entries(
+                &entries,
+                &HashSet::from(["s3://testbucket/b.parquet", "s3://testbucket/d.parquet"]),
+            )
+            .unwrap();
+        let (bytes, _) = writer.write_to_bytes().unwrap();
+
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        let actual: Vec<_> = manifest
+            .entries()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.file_path(),
+                    entry.status(),
+                    entry.snapshot_id(),
+                    entry.sequence_number(),
+                    entry.file_sequence_number(),
+                )
+            })
+            .collect();
+        assert_eq!(actual, vec![
+            (
+                "s3://testbucket/a.parquet",
+                ManifestStatus::Existing,
+                Some(1),
+                Some(1),
+                Some(1)
+            ),
+            (
+                "s3://testbucket/b.parquet",
+                ManifestStatus::Deleted,
+                Some(4),
+                Some(2),
+                Some(2)
+            ),
+            (
+                "s3://testbucket/d.parquet",
+                ManifestStatus::Deleted,
+                Some(4),
+                Some(3),
+                Some(3)
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_manifest_writer_with_known_sort_orders() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = |sort_order_id: Option<i32>| DataFile {
+            sort_order_id,
+            ..test_data_file("s3://testbucket/data/00000.parquet")
+        };
+        let builder = || test_writer_builder(schema.clone(), partition_spec.clone());
+
+        let mut writer = builder().build_v2_data();
+        writer.add_file(data_file(Some(7)), 1).unwrap();
+
+        let mut writer = builder()
+            .with_known_sort_orders(HashSet::from([0, 1]))
+            .build_v2_data();
+        writer.add_file(data_file(Some(1)), 1).unwrap();
+        writer.add_file(data_file(None), 1).unwrap();
+        assert!(writer.add_file(data_file(Some(7)), 1).is_err());
+        assert!(writer
+            .add_existing_file(data_file(Some(7)), 1, 1, Some(1))
+            .is_err());
+        assert_eq!(writer.row_counts().added, 2);
+    }
+
+    #[test]
+    fn test_manifest_writer_with_strict_sequence_numbers() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+        let builder = || test_writer_builder(schema.clone(), partition_spec.clone());
+
+        let mut writer = builder().build_v2_data();
+        writer.add_file(data_file.clone(), -5).unwrap();
+        let (bytes, _) = writer.write_to_bytes().unwrap();
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        assert_eq!(manifest.entries()[0].sequence_number(), None);
+
+        let mut writer = builder().with_strict_sequence_numbers(true).build_v2_data();
+        assert!(writer.add_file(data_file.clone(), -5).is_err());
+        assert!(writer
+            .add_file_with_snapshot_id(data_file.clone(), -5, 1)
+            .is_err());
+        writer.add_file(data_file, 0).unwrap();
+        assert_eq!(writer.row_counts().added, 1);
+    }
+
+    #[test]
+    fn test_manifest_writer_with_path_check() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = |path: &str| test_data_file(path);
+        let builder = || test_writer_builder(schema.clone(), partition_spec.clone());
+
+        let mut writer = builder().build_v2_data();
+        writer.add_file(data_file(""), 1).unwrap();
+        writer.add_file(data_file("data/00000.parquet"), 1).unwrap();
+
+        let mut writer = builder().with_path_check(true).build_v2_data();
+        let err = writer.add_file(data_file(""), 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        assert!(err.message().contains("\"\""));
+        assert!(writer.add_file(data_file("data/00000.parquet"), 1).is_err());
+        writer
+            .add_file(data_file("s3://testbucket/data/00000.parquet"), 1)
+            .unwrap();
+        writer
+            .add_file(data_file("/tmp/data/00000.parquet"), 1)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_manifest_writer_row_counts() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = |path: &str, record_count: u64| {
+            test_data_file_builder(path)
+                .record_count(record_count)
+                .build()
+                .unwrap()
+        };
+
+        let io = FileIOBuilder::new("memory").build().unwrap();
         let mut writer = ManifestWriterBuilder::new(
-            output_file,
-            Some(2),
+            io.new_output("memory:/test_manifest.avro").unwrap(),
+            Some(3),
             vec![],
-            metadata.schema.clone(),
-            metadata.partition_spec.clone(),
+            schema,
+            partition_spec,
         )
         .build_v2_data();
-        for entry in &entries {
-            writer.add_entry(entry.clone()).unwrap();
-        }
+        assert_eq!(writer.row_counts(), RowCounts::default());
+
+        writer
+            .add_file(data_file("s3://testbucket/data/00000.parquet", 3), 3)
+            .unwrap();
+        writer
+            .add_file(data_file("s3://testbucket/data/00001.parquet", 4), 3)
+            .unwrap();
+        writer
+            .add_existing_file(
+                data_file("s3://testbucket/data/00002.parquet", 5),
+                1,
+                1,
+                Some(1),
+            )
+            .unwrap();
+        writer
+            .add_delete_file(
+                data_file("s3://testbucket/data/00003.parquet", 7),
+                2,
+                Some(2),
+            )
+            .unwrap();
+        let row_counts = writer.row_counts();
+        assert_eq!(row_counts, RowCounts {
+            added: 7,
+            existing: 5,
+            deleted: 7,
+        });
+
         let manifest_file = writer.write_manifest_file().await.unwrap();
-        assert_eq!(manifest_file.sequence_number, UNASSIGNED_SEQUENCE_NUMBER);
-        assert_eq!(
-            manifest_file.min_sequence_number,
-            UNASSIGNED_SEQUENCE_NUMBER
+        assert_eq!(manifest_file.added_rows_count, Some(row_counts.added));
+        assert_eq!(manifest_file.existing_rows_count, Some(row_counts.existing));
+        assert_eq!(manifest_file.deleted_rows_count, Some(row_counts.deleted));
+    }
+
+    #[test]
+    fn test_manifest_equivalent_to() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let metadata = ManifestMetadata::new(
+            schema,
+            0,
+            partition_spec,
+            FormatVersion::V2,
+            ManifestContentType::Data,
         );
+        let entry = |path: &str, status: ManifestStatus| ManifestEntry {
+            status,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file(path),
+        };
 
-        // read back the manifest file and check the content
-        let actual_manifest =
-            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
-                .unwrap();
-        // The snapshot id is assigned when the entry is added to the manifest.
-        entries[0].snapshot_id = Some(2);
-        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+        let manifest = Manifest::new(metadata.clone(), vec![
+            entry("s3://testbucket/a.parquet", ManifestStatus::Added),
+            entry("s3://testbucket/b.parquet", ManifestStatus::Existing),
+            entry("s3://testbucket/b.parquet", ManifestStatus::Deleted),
+        ]);
+        let reordered = Manifest::new(metadata.clone(), vec![
+            entry("s3://testbucket/b.parquet", ManifestStatus::Deleted),
+            entry("s3://testbucket/a.parquet", ManifestStatus::Added),
+            entry("s3://testbucket/b.parquet", ManifestStatus::Existing),
+        ]);
+        let changed = Manifest::new(metadata, vec![
+            entry("s3://testbucket/b.parquet", ManifestStatus::Deleted),
+            entry("s3://testbucket/a.parquet", ManifestStatus::Existing),
+            entry("s3://testbucket/b.parquet", ManifestStatus::Existing),
+        ]);
+
+        assert_ne!(manifest, reordered);
+        assert!(manifest.equivalent_to(&reordered));
+        assert!(!manifest.equivalent_to(&changed));
     }
 
-    #[tokio::test]
-    async fn test_parse_manifest_v1_unpartition() {
-        let schema = Arc::new(
-            Schema::builder()
-                .with_schema_id(1)
-                .with_fields(vec![
-                    Arc::new(NestedField::optional(
-                        1,
-                        "id",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                    Arc::new(NestedField::optional(
-                        2,
-                        "data",
-                        Type::Primitive(PrimitiveType::String),
-                    )),
-                    Arc::new(NestedField::optional(
-                        3,
-                        "comment",
-                        Type::Primitive(PrimitiveType::String),
-                    )),
-                ])
+    #[test]
+    fn test_manifest_writer_write_to_bytes() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        writer.add_file(data_file.clone(), 1).unwrap();
+        let (bytes, manifest_file) = writer.write_to_bytes().unwrap();
+
+        assert_eq!(manifest_file.manifest_path, "memory:/test_manifest.avro");
+        assert_eq!(manifest_file.manifest_length, bytes.len() as i64);
+        assert_eq!(manifest_file.added_files_count, Some(1));
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        assert_eq!(manifest.data_files().collect::<Vec<_>>(), vec![&data_file]);
+    }
+
+    #[test]
+    fn test_manifest_writer_v1_rejects_delete_files() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        for content in [
+            DataContentType::PositionDeletes,
+            DataContentType::EqualityDeletes,
+        ] {
+            let mut writer = test_writer_builder(schema.clone(), partition_spec.clone()).build_v1();
+            let delete_file = test_data_file_builder("s3://testbucket/data/delete-00000.parquet")
+                .content(content)
                 .build()
-                .unwrap(),
+                .unwrap();
+
+            let err = writer.add_file(delete_file, 1).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::DataInvalid);
+            assert!(err
+                .message()
+                .contains("Format version 1 doesn't support delete files"));
+        }
+    }
+
+    #[test]
+    fn test_manifest_writer_add_delete_file_without_file_sequence_number() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(2), vec![], schema, partition_spec)
+                .build_v2_data();
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+
+        let err = writer
+            .add_delete_file(data_file.clone(), 1, None)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        assert!(err
+            .message()
+            .contains("Delete files must preserve file_sequence_number"));
+        writer.add_delete_file(data_file, 1, Some(1)).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_spec_id_mismatches() {
+        let schema = test_schema();
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .with_spec_id(1)
+            .build()
+            .unwrap();
+        let metadata = ManifestMetadata::new(
+            schema,
+            0,
+            partition_spec,
+            FormatVersion::V2,
+            ManifestContentType::Data,
         );
-        let metadata = ManifestMetadata {
-            schema_id: 1,
-            schema: schema.clone(),
-            partition_spec: PartitionSpec::builder(schema)
-                .with_spec_id(0)
+        let entry = |path: &str, partition_spec_id: i32| ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder(path)
+                .partition_spec_id(partition_spec_id)
                 .build()
                 .unwrap(),
-            content: ManifestContentType::Data,
-            format_version: FormatVersion::V1,
         };
-        let mut entries = vec![ManifestEntry {
-                status: ManifestStatus::Added,
-                snapshot_id: Some(0),
-                sequence_number: Some(0),
-                file_sequence_number: Some(0),
-                data_file: DataFile {
-                    content: DataContentType::Data,
-                    file_path: "s3://testbucket/iceberg_data/iceberg_ctl/iceberg_db/iceberg_tbl/data/00000-7-45268d71-54eb-476c-b42c-942d880c04a1-00001.parquet".to_string(),
-                    file_format: DataFileFormat::Parquet,
-                    partition: Struct::empty(),
-                    record_count: 1,
-                    file_size_in_bytes: 875,
-                    column_sizes: HashMap::from([(1,47),(2,48),(3,52)]),
-                    value_counts: HashMap::from([(1,1),(2,1),(3,1)]),
-                    null_value_counts: HashMap::from([(1,0),(2,0),(3,0)]),
-                    nan_value_counts: HashMap::new(),
-                    lower_bounds: HashMap::from([(1,Datum::int(1)),(2,Datum::string("a")),(3,Datum::string("AC/DC"))]),
-                    upper_bounds: HashMap::from([(1,Datum::int(1)),(2,Datum::string("a")),(3,Datum::string("AC/DC"))]),
-                    key_metadata: None,
-                    split_offsets: vec![4],
-                    equality_ids: vec![],
-                    sort_order_id: Some(0),
-                    partition_spec_id: 0
+
+        let manifest = Manifest::new(metadata, vec![
+            entry("s3://testbucket/a.parquet", 1),
+            entry("s3://testbucket/b.parquet", 0),
+        ]);
+        let mismatches = manifest.spec_id_mismatches();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].file_path(), "s3://testbucket/b.parquet");
+        assert!(!manifest.is_single_spec());
+        assert_eq!(manifest.spec_ids(), HashSet::from([0, 1]));
+
+        let (bytes, _) = {
+            let io = FileIOBuilder::new("memory").build().unwrap();
+            let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+            let mut writer = ManifestWriterBuilder::new(
+                output_file,
+                Some(1),
+                vec![],
+                manifest.metadata().schema().clone(),
+                manifest.metadata().partition_spec().clone(),
+            )
+            .build_v2_data();
+            for data_file in manifest.data_files() {
+                if data_file.partition_spec_id == 1 {
+                    writer.add_file(data_file.clone(), 1).unwrap();
                 }
-            }];
+            }
+            writer.write_to_bytes().unwrap()
+        };
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        assert!(manifest.spec_id_mismatches().is_empty());
+        assert!(manifest.is_single_spec());
+        assert_eq!(manifest.spec_ids(), HashSet::from([1]));
+    }
 
-        // write manifest to file
-        let tmp_dir = TempDir::new().unwrap();
-        let path = tmp_dir.path().join("test_manifest.avro");
-        let io = FileIOBuilder::new_fs_io().build().unwrap();
-        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
-        let mut writer = ManifestWriterBuilder::new(
-            output_file,
-            Some(3),
-            vec![],
-            metadata.schema.clone(),
-            metadata.partition_spec.clone(),
-        )
-        .build_v1();
-        for entry in &entries {
-            writer.add_entry(entry.clone()).unwrap();
+    #[test]
+    fn test_manifest_writer_with_user_metadata_map() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let builder = || test_writer_builder(schema.clone(), partition_spec.clone());
+
+        for key in ["schema", "format-version", "content", "avro.codec"] {
+            assert!(builder()
+                .with_user_metadata_map(HashMap::from([(key.to_string(), b"x".to_vec())]))
+                .is_err());
         }
-        writer.write_manifest_file().await.unwrap();
 
-        // read back the manifest file and check the content
-        let actual_manifest =
-            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
-                .unwrap();
-        // The snapshot id is assigned when the entry is added to the manifest.
-        entries[0].snapshot_id = Some(3);
-        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+        let user_metadata = HashMap::from([
+            ("lineage.job".to_string(), b"compaction".to_vec()),
+            ("lineage.run-id".to_string(), b"42".to_vec()),
+        ]);
+        let writer = builder()
+            .with_user_metadata_map(user_metadata.clone())
+            .unwrap()
+            .build_v2_data();
+        let (bytes, _) = writer.write_to_bytes().unwrap();
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        assert_eq!(manifest.metadata().extra_metadata(), &user_metadata);
+
+        let raw_metadata = Manifest::read_raw_metadata(&bytes).unwrap();
+        assert_eq!(raw_metadata["lineage.job"], b"compaction");
+        assert_eq!(raw_metadata["lineage.run-id"], b"42");
+        assert_eq!(raw_metadata["format-version"], b"2");
+        assert_eq!(raw_metadata["content"], b"data");
+        assert!(raw_metadata.contains_key("schema"));
+        assert!(!raw_metadata.contains_key("avro.codec"));
     }
 
-    #[tokio::test]
-    async fn test_parse_manifest_v1_partition() {
-        let schema = Arc::new(
-            Schema::builder()
-                .with_fields(vec![
-                    Arc::new(NestedField::optional(
-                        1,
-                        "id",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                    Arc::new(NestedField::optional(
-                        2,
-                        "data",
-                        Type::Primitive(PrimitiveType::String),
-                    )),
-                    Arc::new(NestedField::optional(
-                        3,
-                        "category",
-                        Type::Primitive(PrimitiveType::String),
-                    )),
-                ])
-                .build()
-                .unwrap(),
+    #[test]
+    fn test_manifest_writer_with_schema_fingerprint() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let builder = || test_writer_builder(schema.clone(), partition_spec.clone());
+
+        let (bytes, _) = builder().build_v2_data().write_to_bytes().unwrap();
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        assert!(manifest.metadata().extra_metadata().is_empty());
+
+        let (bytes, _) = builder()
+            .with_schema_fingerprint(true)
+            .build_v2_data()
+            .write_to_bytes()
+            .unwrap();
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        let partition_type = partition_spec.partition_type(&schema).unwrap();
+        let expected = manifest_schema_v2(&partition_type)
+            .unwrap()
+            .fingerprint::<Rabin>()
+            .to_string();
+        assert_eq!(expected.len(), 16);
+        assert_eq!(
+            manifest
+                .metadata()
+                .extra_metadata()
+                .get(MANIFEST_SCHEMA_FINGERPRINT_KEY),
+            Some(&expected.into_bytes())
         );
-        let metadata = ManifestMetadata {
-            schema_id: 0,
-            schema: schema.clone(),
-            partition_spec: PartitionSpec::builder(schema)
-                .add_partition_field("category", "category", Transform::Identity)
-                .unwrap()
+    }
+
+    #[test]
+    fn test_manifest_from_refs() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let metadata = ManifestMetadata::new(
+            schema,
+            0,
+            partition_spec,
+            FormatVersion::V2,
+            ManifestContentType::Data,
+        );
+        let entry = |path: &str, status: ManifestStatus| ManifestEntry {
+            status,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file(path),
+        };
+        let manifest = Manifest::new(metadata.clone(), vec![
+            entry("s3://testbucket/a.parquet", ManifestStatus::Added),
+            entry("s3://testbucket/b.parquet", ManifestStatus::Deleted),
+        ]);
+
+        let live = manifest
+            .entries()
+            .iter()
+            .filter(|entry| entry.is_alive())
+            .cloned()
+            .collect::<Vec<_>>();
+        let filtered = Manifest::from_refs(metadata.clone(), live);
+        assert_eq!(filtered.entries().len(), 1);
+        assert!(Arc::ptr_eq(&filtered.entries()[0], &manifest.entries()[0]));
+        assert_eq!(
+            filtered,
+            Manifest::new(metadata, vec![entry(
+                "s3://testbucket/a.parquet",
+                ManifestStatus::Added
+            )])
+        );
+    }
+
+    #[test]
+    fn test_manifest_writer_rejects_mismatched_partition_spec_id() {
+        let schema = test_schema();
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .with_spec_id(1)
+            .build()
+            .unwrap();
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_data();
+        let data_file = |partition_spec_id: i32| {
+            test_data_file_builder("s3://testbucket/data/00000.parquet")
+                .partition_spec_id(partition_spec_id)
                 .build()
-                .unwrap(),
-            content: ManifestContentType::Data,
-            format_version: FormatVersion::V1,
+                .unwrap()
         };
-        let mut entries = vec![
-                ManifestEntry {
-                    status: ManifestStatus::Added,
-                    snapshot_id: Some(0),
-                    sequence_number: Some(0),
-                    file_sequence_number: Some(0),
-                    data_file: DataFile {
-                        content: DataContentType::Data,
-                        file_path: "s3://testbucket/prod/db/sample/data/category=x/00010-1-d5c93668-1e52-41ac-92a6-bba590cbf249-00001.parquet".to_string(),
-                        file_format: DataFileFormat::Parquet,
-                        partition: Struct::from_iter(
-                            vec![
-                                Some(
-                                    Literal::string("x"),
-                                ),
-                            ]
-                                .into_iter()
-                        ),
-                        record_count: 1,
-                        file_size_in_bytes: 874,
-                        column_sizes: HashMap::from([(1, 46), (2, 48), (3, 48)]),
-                        value_counts: HashMap::from([(1, 1), (2, 1), (3, 1)]),
-                        null_value_counts: HashMap::from([(1, 0), (2, 0), (3, 0)]),
-                        nan_value_counts: HashMap::new(),
-                        lower_bounds: HashMap::from([
-                        (1, Datum::long(1)),
-                        (2, Datum::string("a")),
-                        (3, Datum::string("x"))
-                        ]),
-                        upper_bounds: HashMap::from([
-                        (1, Datum::long(1)),
-                        (2, Datum::string("a")),
-                        (3, Datum::string("x"))
-                        ]),
-                        key_metadata: None,
-                        split_offsets: vec![4],
-                        equality_ids: vec![],
-                        sort_order_id: Some(0),
-                        partition_spec_id: 0
-                    },
+
+        let err = writer.add_file(data_file(0), 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        assert!(err
+            .message()
+            .contains("doesn't match the manifest partition spec id 1"));
+        writer.add_file(data_file(1), 1).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_manifest_writer_in_memory() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+        let writer = || {
+            let mut writer = ManifestWriterBuilder::new_in_memory(
+                "placeholder.avro",
+                Some(1),
+                vec![],
+                schema.clone(),
+                partition_spec.clone(),
+            )
+            .build_v2_data();
+            writer.add_file(data_file.clone(), 1).unwrap();
+            writer
+        };
+
+        let (bytes, manifest_file) = writer().write_to_bytes().unwrap();
+        assert_eq!(manifest_file.manifest_path, "placeholder.avro");
+        assert_eq!(manifest_file.manifest_length, bytes.len() as i64);
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        assert_eq!(manifest.data_files().collect::<Vec<_>>(), vec![&data_file]);
+
+        let err = writer().write_manifest_file().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+    }
+    #[tokio::test]
+
+    async fn test_write_manifests() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let paths = (0..5)
+            .map(|i| format!("memory:/test_manifest_{i}.avro"))
+            .collect::<Vec<_>>();
+        let writers = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let mut writer = ManifestWriterBuilder::new(
+                    io.new_output(path).unwrap(),
+                    Some(1),
+                    vec![],
+                    schema.clone(),
+                    partition_spec.clone(),
+                )
+                .build_v2_data();
+                for j in 0..=i {
+                    let data_file = test_data_file(format!("s3://testbucket/data/{i}-{j}.parquet"));
+                    writer.add_file(data_file, 1).unwrap();
                 }
-            ];
+                writer
+            })
+            .collect();
 
-        // write manifest to file
-        let tmp_dir = TempDir::new().unwrap();
-        let path = tmp_dir.path().join("test_manifest.avro");
-        let io = FileIOBuilder::new_fs_io().build().unwrap();
-        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
-        let mut writer = ManifestWriterBuilder::new(
-            output_file,
-            Some(2),
-            vec![],
-            metadata.schema.clone(),
-            metadata.partition_spec.clone(),
-        )
-        .build_v1();
-        for entry in &entries {
-            writer.add_entry(entry.clone()).unwrap();
+        let manifest_files = write_manifests(writers, 2).await.unwrap();
+        assert_eq!(
+            manifest_files
+                .iter()
+                .map(|manifest_file| manifest_file.manifest_path.clone())
+                .collect::<Vec<_>>(),
+            paths
+        );
+        for (i, manifest_file) in manifest_files.iter().enumerate() {
+            assert_eq!(manifest_file.added_files_count, Some(i as u32 + 1));
+            let bs = io
+                .new_input(&manifest_file.manifest_path)
+                .unwrap()
+                .read()
+                .await
+                .unwrap();
+            assert_eq!(Manifest::parse_avro(&bs).unwrap().entries().len(), i + 1);
         }
-        let manifest_file = writer.write_manifest_file().await.unwrap();
-        assert_eq!(manifest_file.partitions.len(), 1);
+    }
+
+    #[test]
+    fn test_manifest_validate() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let metadata = ManifestMetadata::builder()
+            .schema_id(0)
+            .schema(schema)
+            .partition_spec(partition_spec)
+            .format_version(FormatVersion::V2)
+            .content(ManifestContentType::Data)
+            .build();
+        let entry = |data_file: DataFile| ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file,
+        };
+        let valid = DataFile::builder()
+            .content(DataContentType::Data)
+            .file_path("s3://testbucket/valid.parquet".to_string())
+            .file_format(DataFileFormat::Parquet)
+            .partition(Struct::empty())
+            .record_count(1)
+            .file_size_in_bytes(100)
+            .value_counts(HashMap::from([(1, 1)]))
+            .lower_bounds(HashMap::from([(1, Datum::int(1))]))
+            .upper_bounds(HashMap::from([(1, Datum::int(5))]))
+            .split_offsets(vec![4, 100])
+            .partition_spec_id(0)
+            .build()
+            .unwrap();
+        let mut invalid = valid.clone();
+        invalid.file_path = "s3://testbucket/invalid.parquet".to_string();
+        invalid.content = DataContentType::EqualityDeletes;
+        invalid.lower_bounds = HashMap::from([(1, Datum::int(9)), (7, Datum::int(0))]);
+        invalid.split_offsets = vec![100, 4];
+        invalid.partition_spec_id = 3;
+
+        let manifest = Manifest::new(metadata, vec![entry(valid), entry(invalid)]);
+        let issues = manifest.validate().unwrap();
+        assert!(issues
+            .iter()
+            .all(|issue| issue.file_path == "s3://testbucket/invalid.parquet"));
         assert_eq!(
-            manifest_file.partitions[0].lower_bound,
-            Some(Datum::string("x"))
+            issues
+                .iter()
+                .map(|issue| issue.message.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "Content type EqualityDeletes is not allowed in a data manifest",
+                "Field id 7 has bounds but no value count",
+                "Bound for field id 7 not found in schema",
+                "Equality delete file has no equality ids",
+                "Split offsets [100, 4] are not sorted ascending",
+                "Partition spec id 3 doesn't match the manifest partition spec id 0",
+                "Field id 1 has lower bound 9 above upper bound 5",
+            ]
         );
         assert_eq!(
-            manifest_file.partitions[0].upper_bound,
-            Some(Datum::string("x"))
+            issues[0].to_string(),
+            "s3://testbucket/invalid.parquet: Content type EqualityDeletes is not allowed in a data manifest"
         );
+    }
 
-        // read back the manifest file and check the content
-        let actual_manifest =
-            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
-                .unwrap();
-        // The snapshot id is assigned when the entry is added to the manifest.
-        entries[0].snapshot_id = Some(2);
-        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+    #[test]
+    fn test_manifest_validate_content() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = |path: &str, content: DataContentType| {
+            test_data_file_builder(path)
+                .content(content)
+                .build()
+                .unwrap()
+        };
+        let mut writer =
+            test_writer_builder(schema.clone(), partition_spec.clone()).build_v2_data();
+        writer
+            .add_file(
+                data_file("s3://testbucket/data.parquet", DataContentType::Data),
+                1,
+            )
+            .unwrap();
+        let (bytes, _) = writer.write_to_bytes().unwrap();
+        Manifest::parse_avro(&bytes)
+            .unwrap()
+            .validate_content()
+            .unwrap();
+
+        // Simulate a buggy producer that labels a manifest with a delete file as data.
+        let mut writer = test_writer_builder(schema, partition_spec).build_v2_deletes();
+        writer
+            .add_file(
+                data_file(
+                    "s3://testbucket/delete.parquet",
+                    DataContentType::PositionDeletes,
+                ),
+                1,
+            )
+            .unwrap();
+        writer.metadata.content = ManifestContentType::Data;
+        let (bytes, _) = writer.write_to_bytes().unwrap();
+        let manifest = Manifest::parse_avro(&bytes).unwrap();
+        assert!(manifest.is_data_manifest());
+        let err = manifest.validate_content().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::DataInvalid);
+        assert!(err.message().contains("s3://testbucket/delete.parquet"));
     }
 
     #[tokio::test]
-    async fn test_parse_manifest_with_schema_evolution() {
-        let schema = Arc::new(
-            Schema::builder()
-                .with_fields(vec![
-                    Arc::new(NestedField::optional(
-                        1,
-                        "id",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                    Arc::new(NestedField::optional(
-                        2,
-                        "v_int",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                ])
-                .build()
-                .unwrap(),
-        );
-        let metadata = ManifestMetadata {
-            schema_id: 0,
-            schema: schema.clone(),
-            partition_spec: PartitionSpec::builder(schema)
-                .with_spec_id(0)
+    async fn test_count_by_content() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let delete_file = |path: &str, content: DataContentType| {
+            test_data_file_builder(path)
+                .content(content)
                 .build()
-                .unwrap(),
-            content: ManifestContentType::Data,
-            format_version: FormatVersion::V2,
+                .unwrap()
         };
-        let entries = vec![ManifestEntry {
-                status: ManifestStatus::Added,
-                snapshot_id: None,
-                sequence_number: None,
-                file_sequence_number: None,
-                data_file: DataFile {
-                    content: DataContentType::Data,
-                    file_format: DataFileFormat::Parquet,
-                    file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-378b56f5-5c52-4102-a2c2-f05f8a7cbe4a-00000.parquet".to_string(),
-                    partition: Struct::empty(),
-                    record_count: 1,
-                    file_size_in_bytes: 5442,
-                    column_sizes: HashMap::from([
-                        (1, 61),
-                        (2, 73),
-                        (3, 61),
-                    ]),
-                    value_counts: HashMap::default(),
-                    null_value_counts: HashMap::default(),
-                    nan_value_counts: HashMap::new(),
-                    lower_bounds: HashMap::from([
-                        (1, Datum::long(1)),
-                        (2, Datum::int(2)),
-                        (3, Datum::string("x"))
-                    ]),
-                    upper_bounds: HashMap::from([
-                        (1, Datum::long(1)),
-                        (2, Datum::int(2)),
-                        (3, Datum::string("x"))
-                    ]),
-                    key_metadata: None,
-                    split_offsets: vec![4],
-                    equality_ids: vec![],
-                    sort_order_id: None,
-                    partition_spec_id: 0
-                },
-            }];
 
-        // write manifest to file
         let tmp_dir = TempDir::new().unwrap();
         let path = tmp_dir.path().join("test_manifest.avro");
         let io = FileIOBuilder::new_fs_io().build().unwrap();
         let output_file = io.new_output(path.to_str().unwrap()).unwrap();
-        let mut writer = ManifestWriterBuilder::new(
-            output_file,
-            Some(2),
-            vec![],
-            metadata.schema.clone(),
-            metadata.partition_spec.clone(),
-        )
-        .build_v2_data();
-        for entry in &entries {
-            writer.add_entry(entry.clone()).unwrap();
-        }
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(2), vec![], schema, partition_spec)
+                .build_v2_deletes();
+        writer
+            .add_file(
+                delete_file(
+                    "s3://testbucket/d1.parquet",
+                    DataContentType::PositionDeletes,
+                ),
+                2,
+            )
+            .unwrap();
+        writer
+            .add_file(
+                delete_file(
+                    "s3://testbucket/d2.parquet",
+                    DataContentType::PositionDeletes,
+                ),
+                2,
+            )
+            .unwrap();
+        writer
+            .add_file(
+                delete_file(
+                    "s3://testbucket/d3.parquet",
+                    DataContentType::EqualityDeletes,
+                ),
+                2,
+            )
+            .unwrap();
+        writer
+            .add_delete_file(
+                delete_file(
+                    "s3://testbucket/d4.parquet",
+                    DataContentType::EqualityDeletes,
+                ),
+                1,
+                Some(1),
+            )
+            .unwrap();
         writer.write_manifest_file().await.unwrap();
 
-        // read back the manifest file and check the content
-        let actual_manifest =
+        let manifest =
             Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
                 .unwrap();
+        assert_eq!(
+            manifest.count_by_content(false),
+            HashMap::from([
+                (DataContentType::PositionDeletes, 2),
+                (DataContentType::EqualityDeletes, 1)
+            ])
+        );
+        assert_eq!(
+            manifest.count_by_content(true),
+            HashMap::from([
+                (DataContentType::PositionDeletes, 2),
+                (DataContentType::EqualityDeletes, 2)
+            ])
+        );
+        let deleted = manifest
+            .entries()
+            .iter()
+            .find(|entry| entry.status() == ManifestStatus::Deleted)
+            .unwrap();
+        assert_eq!(deleted.sequence_number(), Some(1));
+        assert_eq!(deleted.file_sequence_number(), Some(1));
+    }
 
-        // Compared with original manifest, the lower_bounds and upper_bounds no longer has data for field 3, and
-        // other parts should be same.
-        // The snapshot id is assigned when the entry is added to the manifest.
-        let schema = Arc::new(
-            Schema::builder()
-                .with_fields(vec![
-                    Arc::new(NestedField::optional(
-                        1,
-                        "id",
-                        Type::Primitive(PrimitiveType::Long),
-                    )),
-                    Arc::new(NestedField::optional(
-                        2,
-                        "v_int",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                ])
+    #[test]
+    fn test_manifest_diff() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let metadata = ManifestMetadata::new(
+            schema,
+            0,
+            partition_spec,
+            FormatVersion::V2,
+            ManifestContentType::Data,
+        );
+        let entry = |path: &str, status: ManifestStatus, record_count: u64| ManifestEntry {
+            status,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder(path)
+                .record_count(record_count)
                 .build()
                 .unwrap(),
+        };
+
+        let old = Manifest::new(metadata.clone(), vec![
+            entry("s3://testbucket/a.parquet", ManifestStatus::Added, 1),
+            entry("s3://testbucket/b.parquet", ManifestStatus::Added, 1),
+            entry("s3://testbucket/c.parquet", ManifestStatus::Added, 1),
+            entry("s3://testbucket/d.parquet", ManifestStatus::Added, 1),
+        ]);
+        let new = Manifest::new(metadata, vec![
+            entry("s3://testbucket/e.parquet", ManifestStatus::Added, 1),
+            entry("s3://testbucket/d.parquet", ManifestStatus::Existing, 1),
+            entry("s3://testbucket/c.parquet", ManifestStatus::Added, 2),
+            entry("s3://testbucket/a.parquet", ManifestStatus::Added, 1),
+        ]);
+
+        assert!(Manifest::diff(&old, &old).is_empty());
+
+        let diff = Manifest::diff(&old, &new);
+        let paths = |entries: &[&ManifestEntryRef]| {
+            entries
+                .iter()
+                .map(|entry| entry.file_path().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(paths(&diff.added), vec!["s3://testbucket/e.parquet"]);
+        assert_eq!(paths(&diff.removed), vec!["s3://testbucket/b.parquet"]);
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].0, &old.entries()[2]);
+        assert_eq!(diff.changed[0].1, &new.entries()[2]);
+        assert_eq!(diff.changed[1].0, &old.entries()[3]);
+        assert_eq!(diff.changed[1].1, &new.entries()[1]);
+    }
+
+    #[test]
+    fn test_manifest_rewrite_paths() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let metadata = ManifestMetadata::new(
+            schema,
+            0,
+            partition_spec,
+            FormatVersion::V2,
+            ManifestContentType::Deletes,
         );
-        let expected_manifest = Manifest {
-            metadata: ManifestMetadata {
-                schema_id: 0,
-                schema: schema.clone(),
-                partition_spec: PartitionSpec::builder(schema).with_spec_id(0).build().unwrap(),
-                content: ManifestContentType::Data,
-                format_version: FormatVersion::V2,
-            },
-            entries: vec![Arc::new(ManifestEntry {
-                status: ManifestStatus::Added,
-                snapshot_id: Some(2),
-                sequence_number: None,
-                file_sequence_number: None,
-                data_file: DataFile {
-                    content: DataContentType::Data,
-                    file_format: DataFileFormat::Parquet,
-                    file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-378b56f5-5c52-4102-a2c2-f05f8a7cbe4a-00000.parquet".to_string(),
-                    partition: Struct::empty(),
-                    record_count: 1,
-                    file_size_in_bytes: 5442,
-                    column_sizes: HashMap::from([
-                        (1, 61),
-                        (2, 73),
-                        (3, 61),
-                    ]),
-                    value_counts: HashMap::default(),
-                    null_value_counts: HashMap::default(),
-                    nan_value_counts: HashMap::new(),
-                    lower_bounds: HashMap::from([
-                        (1, Datum::long(1)),
-                        (2, Datum::int(2)),
-                    ]),
-                    upper_bounds: HashMap::from([
-                        (1, Datum::long(1)),
-                        (2, Datum::int(2)),
-                    ]),
-                    key_metadata: None,
-                    split_offsets: vec![4],
-                    equality_ids: vec![],
-                    sort_order_id: None,
-                    partition_spec_id: 0
-                },
-            })],
+        let entry = |path: &str, referenced_data_file: Option<&str>| ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder(path)
+                .content(DataContentType::PositionDeletes)
+                .referenced_data_file(referenced_data_file.map(str::to_string))
+                .build()
+                .unwrap(),
         };
 
-        assert_eq!(actual_manifest, expected_manifest);
+        let mut manifest = Manifest::new(metadata, vec![
+            entry("s3://old/d1.parquet", Some("s3://old/a.parquet")),
+            entry("s3://old/d2.parquet", None),
+            entry("s3://other/d3.parquet", Some("s3://other/b.parquet")),
+        ]);
+        let original = manifest.clone();
+        manifest.rewrite_paths(|path| {
+            path.strip_prefix("s3://old/")
+                .map(|rest| format!("s3://new/{rest}"))
+        });
+
+        let paths: Vec<_> = manifest
+            .entries()
+            .iter()
+            .map(|entry| (entry.file_path(), entry.data_file().referenced_data_file()))
+            .collect();
+        assert_eq!(paths, vec![
+            ("s3://new/d1.parquet", Some("s3://new/a.parquet")),
+            ("s3://new/d2.parquet", None),
+            ("s3://other/d3.parquet", Some("s3://other/b.parquet")),
+        ]);
+        // The entries of the cloned manifest are shared, but not changed.
+        assert_eq!(original.entries()[0].file_path(), "s3://old/d1.parquet");
+        assert!(Arc::ptr_eq(&original.entries()[2], &manifest.entries()[2]));
     }
 
-    #[tokio::test]
-    async fn test_manifest_summary() {
+    #[test]
+    fn test_manifest_column_null_fraction() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let metadata = ManifestMetadata::new(
+            schema,
+            0,
+            partition_spec,
+            FormatVersion::V2,
+            ManifestContentType::Data,
+        );
+        let entry = |status: ManifestStatus,
+                     value_counts: HashMap<i32, u64>,
+                     null_value_counts: HashMap<i32, u64>| ManifestEntry {
+            status,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder("s3://testbucket/data/00000.parquet")
+                .value_counts(value_counts)
+                .null_value_counts(null_value_counts)
+                .build()
+                .unwrap(),
+        };
+
+        let manifest = Manifest::new(metadata, vec![
+            entry(
+                ManifestStatus::Added,
+                HashMap::from([(1, 10), (2, 4), (3, 0)]),
+                HashMap::from([(1, 1), (2, 4)]),
+            ),
+            entry(
+                ManifestStatus::Existing,
+                HashMap::from([(1, 10), (2, 4)]),
+                HashMap::from([(1, 3), (4, 2)]),
+            ),
+            entry(
+                ManifestStatus::Deleted,
+                HashMap::from([(1, 100)]),
+                HashMap::from([(1, 100)]),
+            ),
+        ]);
+        assert_eq!(
+            manifest.column_null_fraction(),
+            HashMap::from([(1, 0.2), (2, 0.5)])
+        );
+    }
+
+    #[test]
+    fn test_manifest_status_counts() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let metadata = ManifestMetadata::new(
+            schema,
+            0,
+            partition_spec,
+            FormatVersion::V2,
+            ManifestContentType::Deletes,
+        );
+        let entry = |path: &str, content: DataContentType, status: ManifestStatus| ManifestEntry {
+            status,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder(path)
+                .content(content)
+                .build()
+                .unwrap(),
+        };
+
+        assert_eq!(
+            Manifest::new(metadata.clone(), vec![]).status_counts(),
+            (0, 0, 0)
+        );
+        let manifest = Manifest::new(metadata, vec![
+            entry(
+                "s3://testbucket/d1.parquet",
+                DataContentType::PositionDeletes,
+                ManifestStatus::Added,
+            ),
+            entry(
+                "s3://testbucket/d2.parquet",
+                DataContentType::EqualityDeletes,
+                ManifestStatus::Added,
+            ),
+            entry(
+                "s3://testbucket/d3.parquet",
+                DataContentType::PositionDeletes,
+                ManifestStatus::Existing,
+            ),
+            entry(
+                "s3://testbucket/d4.parquet",
+                DataContentType::EqualityDeletes,
+                ManifestStatus::Deleted,
+            ),
+        ]);
+        assert_eq!(manifest.status_counts(), (2, 1, 1));
+    }
+
+    #[test]
+    fn test_status_and_content_type_as_str() {
+        assert_eq!(ManifestStatus::Existing.as_str(), "existing");
+        assert_eq!(ManifestStatus::Added.as_str(), "added");
+        assert_eq!(ManifestStatus::Deleted.as_str(), "deleted");
+        assert_eq!(DataContentType::Data.as_str(), "data");
+        assert_eq!(
+            DataContentType::PositionDeletes.as_str(),
+            "position-deletes"
+        );
+        assert_eq!(
+            DataContentType::EqualityDeletes.as_str(),
+            "equality-deletes"
+        );
+    }
+
+    #[test]
+    fn test_manifest_content_hash() {
         let schema = Arc::new(
             Schema::builder()
                 .with_fields(vec![
                     Arc::new(NestedField::optional(
                         1,
-                        "time",
-                        Type::Primitive(PrimitiveType::Date),
+                        "id",
+                        Type::Primitive(PrimitiveType::Int),
                     )),
                     Arc::new(NestedField::optional(
                         2,
-                        "v_float",
-                        Type::Primitive(PrimitiveType::Float),
-                    )),
-                    Arc::new(NestedField::optional(
-                        3,
-                        "v_double",
-                        Type::Primitive(PrimitiveType::Double),
+                        "name",
+                        Type::Primitive(PrimitiveType::String),
                     )),
                 ])
                 .build()
@@ -2572,185 +9797,460 @@ mod tests {
         );
         let partition_spec = PartitionSpec::builder(schema.clone())
             .with_spec_id(0)
-            .add_partition_field("time", "year_of_time", Transform::Year)
-            .unwrap()
-            .add_partition_field("v_float", "f", Transform::Identity)
-            .unwrap()
-            .add_partition_field("v_double", "d", Transform::Identity)
+            .add_partition_field("id", "id", Transform::Identity)
             .unwrap()
             .build()
             .unwrap();
-        let metadata = ManifestMetadata {
-            schema_id: 0,
+        let metadata = ManifestMetadata::new(
             schema,
+            0,
             partition_spec,
-            content: ManifestContentType::Data,
-            format_version: FormatVersion::V2,
+            FormatVersion::V2,
+            ManifestContentType::Data,
+        );
+        let entry = |path: &str, value_counts: Vec<(i32, u64)>| ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: test_data_file_builder(path)
+                .partition(Struct::from_iter([Some(Literal::int(1))]))
+                .record_count(10)
+                .value_counts(value_counts.into_iter().collect())
+                .lower_bounds(HashMap::from([(1, Datum::int(1)), (2, Datum::string("a"))]))
+                .build()
+                .unwrap(),
+        };
+
+        let manifest = Manifest::new(metadata.clone(), vec![
+            entry("s3://testbucket/a.parquet", vec![(1, 10), (2, 8)]),
+            entry("s3://testbucket/b.parquet", vec![(1, 10)]),
+        ]);
+        let hash = manifest.content_hash();
+
+        // Entry order and map insertion order don't matter.
+        let reordered = Manifest::new(metadata.clone(), vec![
+            entry("s3://testbucket/b.parquet", vec![(1, 10)]),
+            entry("s3://testbucket/a.parquet", vec![(2, 8), (1, 10)]),
+        ]);
+        assert_eq!(reordered.content_hash(), hash);
+
+        let changed = Manifest::new(metadata.clone(), vec![
+            entry("s3://testbucket/a.parquet", vec![(1, 10), (2, 9)]),
+            entry("s3://testbucket/b.parquet", vec![(1, 10)]),
+        ]);
+        assert_ne!(changed.content_hash(), hash);
+
+        let moved = Manifest::new(metadata, vec![
+            entry("s3://testbucket/a.parquet", vec![(1, 10), (2, 8)]),
+            entry("s3://testbucket/c.parquet", vec![(1, 10)]),
+        ]);
+        assert_ne!(moved.content_hash(), hash);
+    }
+
+    #[test]
+    fn test_manifest_row_counts_by_content() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let metadata = ManifestMetadata::new(
+            schema,
+            0,
+            partition_spec,
+            FormatVersion::V2,
+            ManifestContentType::Deletes,
+        );
+        let entry =
+            |content: DataContentType, status: ManifestStatus, record_count: u64| ManifestEntry {
+                status,
+                snapshot_id: Some(1),
+                sequence_number: Some(1),
+                file_sequence_number: Some(1),
+                data_file: test_data_file_builder("s3://testbucket/d.parquet")
+                    .content(content)
+                    .record_count(record_count)
+                    .build()
+                    .unwrap(),
+            };
+
+        let manifest = Manifest::new(metadata, vec![
+            entry(DataContentType::PositionDeletes, ManifestStatus::Added, 10),
+            entry(
+                DataContentType::PositionDeletes,
+                ManifestStatus::Existing,
+                5,
+            ),
+            entry(DataContentType::EqualityDeletes, ManifestStatus::Added, 3),
+            entry(
+                DataContentType::EqualityDeletes,
+                ManifestStatus::Deleted,
+                100,
+            ),
+        ]);
+        assert_eq!(
+            manifest.row_counts_by_content(),
+            HashMap::from([
+                (DataContentType::PositionDeletes, 15),
+                (DataContentType::EqualityDeletes, 3),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_group_deletes_by_referenced_file() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let delete_file = |path: &str, referenced_data_file: Option<&str>| {
+            test_data_file_builder(path)
+                .content(DataContentType::PositionDeletes)
+                .referenced_data_file(referenced_data_file.map(str::to_string))
+                .build()
+                .unwrap()
         };
-        let entries = vec![
-                ManifestEntry {
-                    status: ManifestStatus::Added,
-                    snapshot_id: None,
-                    sequence_number: None,
-                    file_sequence_number: None,
-                    data_file: DataFile {
-                        content: DataContentType::Data,
-                        file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
-                        file_format: DataFileFormat::Parquet,
-                        partition: Struct::from_iter(
-                            vec![
-                                Some(Literal::int(2021)),
-                                Some(Literal::float(1.0)),
-                                Some(Literal::double(2.0)),
-                            ]
-                        ),
-                        record_count: 1,
-                        file_size_in_bytes: 5442,
-                        column_sizes: HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),
-                        value_counts: HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),
-                        null_value_counts: HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),
-                        nan_value_counts: HashMap::new(),
-                        lower_bounds: HashMap::new(),
-                        upper_bounds: HashMap::new(),
-                        key_metadata: None,
-                        split_offsets: vec![4],
-                        equality_ids: Vec::new(),
-                        sort_order_id: None,
-                        partition_spec_id: 0
-                    }
-                },
-                    ManifestEntry {
-                        status: ManifestStatus::Added,
-                        snapshot_id: None,
-                        sequence_number: None,
-                        file_sequence_number: None,
-                        data_file: DataFile {
-                            content: DataContentType::Data,
-                            file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
-                            file_format: DataFileFormat::Parquet,
-                            partition: Struct::from_iter(
-                                vec![
-                                    Some(Literal::int(1111)),
-                                    Some(Literal::float(15.5)),
-                                    Some(Literal::double(25.5)),
-                                ]
-                            ),
-                            record_count: 1,
-                            file_size_in_bytes: 5442,
-                            column_sizes: HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),
-                            value_counts: HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),
-                            null_value_counts: HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),
-                            nan_value_counts: HashMap::new(),
-                            lower_bounds: HashMap::new(),
-                            upper_bounds: HashMap::new(),
-                            key_metadata: None,
-                            split_offsets: vec![4],
-                            equality_ids: Vec::new(),
-                            sort_order_id: None,
-                            partition_spec_id: 0
-                        }
-                    },
-                    ManifestEntry {
-                        status: ManifestStatus::Added,
-                        snapshot_id: None,
-                        sequence_number: None,
-                        file_sequence_number: None,
-                        data_file: DataFile {
-                            content: DataContentType::Data,
-                            file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
-                            file_format: DataFileFormat::Parquet,
-                            partition: Struct::from_iter(
-                                vec![
-                                    Some(Literal::int(1211)),
-                                    Some(Literal::float(f32::NAN)),
-                                    Some(Literal::double(1.0)),
-                                ]
-                            ),
-                            record_count: 1,
-                            file_size_in_bytes: 5442,
-                            column_sizes: HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),
-                            value_counts: HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),
-                            null_value_counts: HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),
-                            nan_value_counts: HashMap::new(),
-                            lower_bounds: HashMap::new(),
-                            upper_bounds: HashMap::new(),
-                            key_metadata: None,
-                            split_offsets: vec![4],
-                            equality_ids: Vec::new(),
-                            sort_order_id: None,
-                            partition_spec_id: 0
-                        }
-                    },
-                    ManifestEntry {
-                        status: ManifestStatus::Added,
-                        snapshot_id: None,
-                        sequence_number: None,
-                        file_sequence_number: None,
-                        data_file: DataFile {
-                            content: DataContentType::Data,
-                            file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
-                            file_format: DataFileFormat::Parquet,
-                            partition: Struct::from_iter(
-                                vec![
-                                    Some(Literal::int(1111)),
-                                    None,
-                                    Some(Literal::double(11.0)),
-                                ]
-                            ),
-                            record_count: 1,
-                            file_size_in_bytes: 5442,
-                            column_sizes: HashMap::from([(0,73),(6,34),(2,73),(7,61),(3,61),(5,62),(9,79),(10,73),(1,61),(4,73),(8,73)]),
-                            value_counts: HashMap::from([(4,1),(5,1),(2,1),(0,1),(3,1),(6,1),(8,1),(1,1),(10,1),(7,1),(9,1)]),
-                            null_value_counts: HashMap::from([(1,0),(6,0),(2,0),(8,0),(0,0),(3,0),(5,0),(9,0),(7,0),(4,0),(10,0)]),
-                            nan_value_counts: HashMap::new(),
-                            lower_bounds: HashMap::new(),
-                            upper_bounds: HashMap::new(),
-                            key_metadata: None,
-                            split_offsets: vec![4],
-                            equality_ids: Vec::new(),
-                            sort_order_id: None,
-                            partition_spec_id: 0
-                        }
-                    },
-            ];
 
-        // write manifest to file
         let tmp_dir = TempDir::new().unwrap();
         let path = tmp_dir.path().join("test_manifest.avro");
         let io = FileIOBuilder::new_fs_io().build().unwrap();
         let output_file = io.new_output(path.to_str().unwrap()).unwrap();
-        let mut writer = ManifestWriterBuilder::new(
-            output_file,
-            Some(1),
-            vec![],
-            metadata.schema.clone(),
-            metadata.partition_spec.clone(),
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(1), vec![], schema, partition_spec)
+                .build_v2_deletes();
+        writer
+            .add_file(
+                delete_file(
+                    "s3://testbucket/data/d1.parquet",
+                    Some("s3://testbucket/a.parquet"),
+                ),
+                1,
+            )
+            .unwrap();
+        writer
+            .add_file(
+                delete_file(
+                    "s3://testbucket/data/d2.parquet",
+                    Some("s3://testbucket/a.parquet"),
+                ),
+                1,
+            )
+            .unwrap();
+        writer
+            .add_file(
+                delete_file(
+                    "s3://testbucket/data/d3.parquet",
+                    Some("s3://testbucket/b.parquet"),
+                ),
+                1,
+            )
+            .unwrap();
+        writer
+            .add_file(delete_file("s3://testbucket/data/d4.parquet", None), 1)
+            .unwrap();
+        writer.write_manifest_file().await.unwrap();
+
+        let manifest =
+            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
+                .unwrap();
+        let (scoped, unscoped) = manifest.group_deletes_by_referenced_file();
+        let paths = |files: &Vec<&DataFile>| {
+            files
+                .iter()
+                .map(|file| file.file_path().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(scoped.len(), 2);
+        assert_eq!(paths(&scoped["s3://testbucket/a.parquet"]), vec![
+            "s3://testbucket/data/d1.parquet",
+            "s3://testbucket/data/d2.parquet"
+        ]);
+        assert_eq!(paths(&scoped["s3://testbucket/b.parquet"]), vec![
+            "s3://testbucket/data/d3.parquet"
+        ]);
+        assert_eq!(paths(&unscoped), vec!["s3://testbucket/data/d4.parquet"]);
+    }
+
+    fn test_schema() -> SchemaRef {
+        Arc::new(
+            Schema::builder()
+                .with_fields(vec![Arc::new(NestedField::optional(
+                    1,
+                    "id",
+                    Type::Primitive(PrimitiveType::Int),
+                ))])
+                .build()
+                .unwrap(),
         )
-        .build_v2_data();
-        for entry in &entries {
-            writer.add_entry(entry.clone()).unwrap();
-        }
-        let res = writer.write_manifest_file().await.unwrap();
+    }
+
+    fn test_partition_spec(schema: &SchemaRef) -> PartitionSpec {
+        PartitionSpec::builder(schema.clone())
+            .with_spec_id(0)
+            .build()
+            .unwrap()
+    }
+
+    /// A builder of an unpartitioned parquet data file at `path`, with one record.
+    fn test_data_file_builder(path: impl ToString) -> DataFileBuilder {
+        let mut builder = DataFileBuilder::default();
+        builder
+            .content(DataContentType::Data)
+            .file_path(path.to_string())
+            .file_format(DataFileFormat::Parquet)
+            .partition(Struct::empty())
+            .record_count(1)
+            .file_size_in_bytes(100)
+            .partition_spec_id(0);
+        builder
+    }
+
+    fn test_data_file(path: impl ToString) -> DataFile {
+        test_data_file_builder(path).build().unwrap()
+    }
+
+    /// A writer builder for a manifest of snapshot 1 written to `memory:/test_manifest.avro`.
+    fn test_writer_builder(
+        schema: SchemaRef,
+        partition_spec: PartitionSpec,
+    ) -> ManifestWriterBuilder {
+        let io = FileIOBuilder::new("memory").build().unwrap();
+        let output_file = io.new_output("memory:/test_manifest.avro").unwrap();
+        ManifestWriterBuilder::new(output_file, Some(1), vec![], schema, partition_spec)
+    }
+
+    #[tokio::test]
+    async fn test_manifest_writer_min_entries() {
+        let schema = test_schema();
+        let partition_spec = test_partition_spec(&schema);
+        let data_file = test_data_file("s3://testbucket/data/00000.parquet");
+
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("test_manifest.avro");
+        let io = FileIOBuilder::new_fs_io().build().unwrap();
+        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
+        let mut writer =
+            ManifestWriterBuilder::new(output_file, Some(1), vec![], schema, partition_spec)
+                .with_min_entries(10)
+                .build_v2_data();
+        writer.add_file(data_file, 1).unwrap();
+
+        assert!(writer.is_below_min_entries());
+        assert!(writer.write_manifest_file().await.is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_data_file_sorted_stats() {
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .record_count(3)
+            .column_sizes(HashMap::from([(3, 30), (1, 10), (2, 20)]))
+            .value_counts(HashMap::from([(2, 3), (3, 3), (1, 3)]))
+            .null_value_counts(HashMap::from([(3, 0), (1, 1)]))
+            .nan_value_counts(HashMap::from([(3, 0)]))
+            .lower_bounds(HashMap::from([(2, Datum::string("a")), (1, Datum::int(1))]))
+            .upper_bounds(HashMap::from([(2, Datum::string("z")), (1, Datum::int(9))]))
+            .build()
+            .unwrap();
+
+        assert_eq!(data_file.column_sizes_sorted(), vec![
+            (1, 10),
+            (2, 20),
+            (3, 30)
+        ]);
+        assert_eq!(data_file.value_counts_sorted(), vec![
+            (1, 3),
+            (2, 3),
+            (3, 3)
+        ]);
+        assert_eq!(data_file.null_value_counts_sorted(), vec![(1, 1), (3, 0)]);
+        assert_eq!(data_file.nan_value_counts_sorted(), vec![(3, 0)]);
+        assert_eq!(data_file.lower_bounds_sorted(), vec![
+            (1, &Datum::int(1)),
+            (2, &Datum::string("a"))
+        ]);
+        assert_eq!(data_file.upper_bounds_sorted(), vec![
+            (1, &Datum::int(9)),
+            (2, &Datum::string("z"))
+        ]);
+    }
+
+    #[test]
+    fn test_data_file_stat_column_ids() {
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .record_count(3)
+            .column_sizes(HashMap::from([(3, 30), (1, 10)]))
+            .null_value_counts(HashMap::from([(5, 0)]))
+            .nan_value_counts(HashMap::from([(4, 0)]))
+            .upper_bounds(HashMap::from([(2, Datum::string("z")), (1, Datum::int(9))]))
+            .build()
+            .unwrap();
+        assert_eq!(data_file.stat_column_ids(), BTreeSet::from([1, 2, 3, 4, 5]));
+
+        let no_stats = data_file.without_stats();
+        assert!(no_stats.stat_column_ids().is_empty());
+    }
+
+    #[test]
+    fn test_data_file_with_metrics() {
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .content(DataContentType::EqualityDeletes)
+            .partition(Struct::from_iter([Some(Literal::int(7))]))
+            .record_count(3)
+            .column_sizes(HashMap::from([(1, 10)]))
+            .lower_bounds(HashMap::from([(1, Datum::int(0))]))
+            .key_metadata(Some(vec![1, 2]))
+            .split_offsets(vec![4])
+            .equality_ids(vec![1])
+            .sort_order_id(1)
+            .partition_spec_id(3)
+            .build()
+            .unwrap();
+
+        let recomputed = data_file.clone().with_metrics(
+            HashMap::from([(1, 12), (2, 20)]),
+            HashMap::from([(1, 3), (2, 3)]),
+            HashMap::from([(1, 0), (2, 1)]),
+            HashMap::from([(2, 0)]),
+            HashMap::from([(1, Datum::int(1)), (2, Datum::double(0.5))]),
+            HashMap::from([(1, Datum::int(9)), (2, Datum::double(2.5))]),
+        );
+        assert_eq!(
+            recomputed.column_sizes(),
+            &HashMap::from([(1, 12), (2, 20)])
+        );
+        assert_eq!(recomputed.value_counts(), &HashMap::from([(1, 3), (2, 3)]));
+        assert_eq!(
+            recomputed.null_value_counts(),
+            &HashMap::from([(1, 0), (2, 1)])
+        );
+        assert_eq!(recomputed.nan_value_counts(), &HashMap::from([(2, 0)]));
+        assert_eq!(recomputed.bounds(1), Some((&Datum::int(1), &Datum::int(9))));
+        assert_eq!(
+            recomputed.bounds(2),
+            Some((&Datum::double(0.5), &Datum::double(2.5)))
+        );
+        assert_eq!(recomputed.without_stats(), data_file.without_stats());
+    }
+
+    #[test]
+    fn test_data_file_bounds() {
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .record_count(3)
+            .lower_bounds(HashMap::from([(1, Datum::int(1)), (2, Datum::string("a"))]))
+            .upper_bounds(HashMap::from([(1, Datum::int(9)), (3, Datum::long(5))]))
+            .build()
+            .unwrap();
+
+        assert_eq!(data_file.bounds(1), Some((&Datum::int(1), &Datum::int(9))));
+        // Only one side of the range is known.
+        assert_eq!(data_file.bounds(2), None);
+        assert_eq!(data_file.bounds(3), None);
+        assert_eq!(data_file.bounds(4), None);
+    }
+
+    #[test]
+    fn test_data_file_validate_nan_value_counts() {
+        let schema = Schema::builder()
+            .with_fields(vec![
+                Arc::new(NestedField::optional(
+                    1,
+                    "id",
+                    Type::Primitive(PrimitiveType::Int),
+                )),
+                Arc::new(NestedField::optional(
+                    2,
+                    "f",
+                    Type::Primitive(PrimitiveType::Float),
+                )),
+                Arc::new(NestedField::optional(
+                    3,
+                    "d",
+                    Type::Primitive(PrimitiveType::Double),
+                )),
+            ])
+            .build()
+            .unwrap();
+        let data_file = |nan_value_counts: HashMap<i32, u64>| {
+            test_data_file_builder("s3://testbucket/data/00000.parquet")
+                .record_count(3)
+                .nan_value_counts(nan_value_counts)
+                .build()
+                .unwrap()
+        };
 
-        assert_eq!(res.partitions.len(), 3);
-        assert_eq!(res.partitions[0].lower_bound, Some(Datum::int(1111)));
-        assert_eq!(res.partitions[0].upper_bound, Some(Datum::int(2021)));
-        assert!(!res.partitions[0].contains_null);
-        assert_eq!(res.partitions[0].contains_nan, Some(false));
+        assert!(data_file(HashMap::new()).validate(&schema).is_ok());
+        assert!(data_file(HashMap::from([(2, 1), (3, 0)]))
+            .validate(&schema)
+            .is_ok());
+        assert!(data_file(HashMap::from([(1, 0), (2, 1)]))
+            .validate(&schema)
+            .is_err());
+        assert!(data_file(HashMap::from([(4, 0)]))
+            .validate(&schema)
+            .is_err());
+    }
 
-        assert_eq!(res.partitions[1].lower_bound, Some(Datum::float(1.0)));
-        assert_eq!(res.partitions[1].upper_bound, Some(Datum::float(15.5)));
-        assert!(res.partitions[1].contains_null);
-        assert_eq!(res.partitions[1].contains_nan, Some(true));
+    #[test]
+    fn test_data_file_validate_bounds_without_value_counts() {
+        let schema = Schema::builder()
+            .with_fields(vec![
+                Arc::new(NestedField::optional(
+                    1,
+                    "id",
+                    Type::Primitive(PrimitiveType::Int),
+                )),
+                Arc::new(NestedField::optional(
+                    2,
+                    "name",
+                    Type::Primitive(PrimitiveType::String),
+                )),
+            ])
+            .build()
+            .unwrap();
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .record_count(3)
+            .value_counts(HashMap::from([(1, 3)]))
+            .lower_bounds(HashMap::from([(1, Datum::int(1)), (2, Datum::string("a"))]))
+            .upper_bounds(HashMap::from([(1, Datum::int(9)), (2, Datum::string("z"))]))
+            .build()
+            .unwrap();
 
-        assert_eq!(res.partitions[2].lower_bound, Some(Datum::double(1.0)));
-        assert_eq!(res.partitions[2].upper_bound, Some(Datum::double(25.5)));
-        assert!(!res.partitions[2].contains_null);
-        assert_eq!(res.partitions[2].contains_nan, Some(false));
+        assert_eq!(data_file.validate(&schema).unwrap(), vec![
+            "Field id 2 has bounds but no value count".to_string()
+        ]);
     }
 
-    #[tokio::test]
-    async fn test_add_delete_existing() {
+    #[test]
+    fn test_data_file_validate_avro_column_sizes() {
+        let schema = Schema::builder()
+            .with_fields(vec![Arc::new(NestedField::optional(
+                1,
+                "id",
+                Type::Primitive(PrimitiveType::Int),
+            ))])
+            .build()
+            .unwrap();
+        let data_file = |file_format: DataFileFormat| {
+            test_data_file_builder("s3://testbucket/data/00000")
+                .file_format(file_format)
+                .record_count(3)
+                .column_sizes(HashMap::from([(1, 12)]))
+                .build()
+                .unwrap()
+        };
+
+        assert!(data_file(DataFileFormat::Parquet)
+            .validate(&schema)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            data_file(DataFileFormat::Avro).validate(&schema).unwrap(),
+            vec![
+            "Avro file has column sizes for 1 columns, they should be null for row-oriented files"
+                .to_string()
+        ]
+        );
+    }
+
+    #[test]
+    fn test_data_file_validate_bounds_against_partition() {
         let schema = Arc::new(
             Schema::builder()
                 .with_fields(vec![
@@ -2761,194 +10261,237 @@ mod tests {
                     )),
                     Arc::new(NestedField::optional(
                         2,
-                        "name",
+                        "category",
                         Type::Primitive(PrimitiveType::String),
                     )),
                 ])
                 .build()
                 .unwrap(),
         );
-        let metadata = ManifestMetadata {
-            schema_id: 0,
-            schema: schema.clone(),
-            partition_spec: PartitionSpec::builder(schema)
-                .with_spec_id(0)
+        let partition_spec = PartitionSpec::builder(schema)
+            .add_partition_field("category", "category", Transform::Identity)
+            .unwrap()
+            .add_partition_field("id", "id_bucket", Transform::Bucket(4))
+            .unwrap()
+            .build()
+            .unwrap();
+        let data_file = |lower: &str, upper: &str| {
+            test_data_file_builder("s3://testbucket/data/category=x/00000.parquet")
+                .record_count(3)
+                .lower_bounds(HashMap::from([
+                    (1, Datum::int(100)),
+                    (2, Datum::string(lower)),
+                ]))
+                .upper_bounds(HashMap::from([
+                    (1, Datum::int(900)),
+                    (2, Datum::string(upper)),
+                ]))
                 .build()
+                .unwrap()
+        };
+        let partition = |category: Option<&str>| {
+            Struct::from_iter(vec![category.map(Literal::string), Some(Literal::int(1))])
+        };
+
+        // Bounds of the bucketed column are never checked.
+        assert!(data_file("x", "x")
+            .validate_bounds_against_partition(&partition_spec, &partition(Some("x")))
+            .unwrap()
+            .is_empty());
+        assert!(data_file("w", "xa")
+            .validate_bounds_against_partition(&partition_spec, &partition(Some("x")))
+            .unwrap()
+            .is_empty());
+
+        let diagnostics = data_file("a", "c")
+            .validate_bounds_against_partition(&partition_spec, &partition(Some("x")))
+            .unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].starts_with("Field id 2 has upper bound"));
+
+        let diagnostics = data_file("y", "z")
+            .validate_bounds_against_partition(&partition_spec, &partition(Some("x")))
+            .unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].starts_with("Field id 2 has lower bound"));
+
+        assert_eq!(
+            data_file("x", "x")
+                .validate_bounds_against_partition(&partition_spec, &partition(None))
                 .unwrap(),
-            content: ManifestContentType::Data,
-            format_version: FormatVersion::V2,
+            vec!["Field id 2 has bounds but partition category is null".to_string()]
+        );
+
+        assert!(data_file("x", "x")
+            .validate_bounds_against_partition(&partition_spec, &Struct::empty())
+            .is_err());
+    }
+
+    #[test]
+    fn test_data_file_partition_values() {
+        let schema = Schema::builder()
+            .with_fields(vec![
+                Arc::new(NestedField::optional(
+                    1,
+                    "id",
+                    Type::Primitive(PrimitiveType::Int),
+                )),
+                Arc::new(NestedField::optional(
+                    2,
+                    "ts",
+                    Type::Primitive(PrimitiveType::Timestamp),
+                )),
+            ])
+            .build()
+            .unwrap();
+        let partition_spec = PartitionSpec::builder(schema.clone())
+            .add_partition_field("id", "id", Transform::Identity)
+            .unwrap()
+            .add_partition_field("ts", "ts_day", Transform::Day)
+            .unwrap()
+            .build()
+            .unwrap();
+        let data_file = |partition: Struct| {
+            test_data_file_builder("s3://testbucket/data/00000.parquet")
+                .partition(partition)
+                .build()
+                .unwrap()
         };
-        let mut entries = vec![
-                ManifestEntry {
-                    status: ManifestStatus::Added,
-                    snapshot_id: None,
-                    sequence_number: Some(1),
-                    file_sequence_number: Some(1),
-                    data_file: DataFile {
-                        content: DataContentType::Data,
-                        file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
-                        file_format: DataFileFormat::Parquet,
-                        partition: Struct::empty(),
-                        record_count: 1,
-                        file_size_in_bytes: 5442,
-                        column_sizes: HashMap::from([(1, 61), (2, 73)]),
-                        value_counts: HashMap::from([(1, 1), (2, 1)]),
-                        null_value_counts: HashMap::from([(1, 0), (2, 0)]),
-                        nan_value_counts: HashMap::new(),
-                        lower_bounds: HashMap::new(),
-                        upper_bounds: HashMap::new(),
-                        key_metadata: Some(Vec::new()),
-                        split_offsets: vec![4],
-                        equality_ids: Vec::new(),
-                        sort_order_id: None,
-                        partition_spec_id: 0
-                    },
-                },
-                ManifestEntry {
-                    status: ManifestStatus::Deleted,
-                    snapshot_id: Some(1),
-                    sequence_number: Some(1),
-                    file_sequence_number: Some(1),
-                    data_file: DataFile {
-                        content: DataContentType::Data,
-                        file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
-                        file_format: DataFileFormat::Parquet,
-                        partition: Struct::empty(),
-                        record_count: 1,
-                        file_size_in_bytes: 5442,
-                        column_sizes: HashMap::from([(1, 61), (2, 73)]),
-                        value_counts: HashMap::from([(1, 1), (2, 1)]),
-                        null_value_counts: HashMap::from([(1, 0), (2, 0)]),
-                        nan_value_counts: HashMap::new(),
-                        lower_bounds: HashMap::new(),
-                        upper_bounds: HashMap::new(),
-                        key_metadata: Some(Vec::new()),
-                        split_offsets: vec![4],
-                        equality_ids: Vec::new(),
-                        sort_order_id: None,
-                        partition_spec_id: 0
-                    },
-                },
-                ManifestEntry {
-                    status: ManifestStatus::Existing,
-                    snapshot_id: Some(1),
-                    sequence_number: Some(1),
-                    file_sequence_number: Some(1),
-                    data_file: DataFile {
-                        content: DataContentType::Data,
-                        file_path: "s3a://icebergdata/demo/s1/t1/data/00000-0-ba56fbfa-f2ff-40c9-bb27-565ad6dc2be8-00000.parquet".to_string(),
-                        file_format: DataFileFormat::Parquet,
-                        partition: Struct::empty(),
-                        record_count: 1,
-                        file_size_in_bytes: 5442,
-                        column_sizes: HashMap::from([(1, 61), (2, 73)]),
-                        value_counts: HashMap::from([(1, 1), (2, 1)]),
-                        null_value_counts: HashMap::from([(1, 0), (2, 0)]),
-                        nan_value_counts: HashMap::new(),
-                        lower_bounds: HashMap::new(),
-                        upper_bounds: HashMap::new(),
-                        key_metadata: Some(Vec::new()),
-                        split_offsets: vec![4],
-                        equality_ids: Vec::new(),
-                        sort_order_id: None,
-                        partition_spec_id: 0
-                    },
-                },
-            ];
 
-        // write manifest to file
-        let tmp_dir = TempDir::new().unwrap();
-        let path = tmp_dir.path().join("test_manifest.avro");
-        let io = FileIOBuilder::new_fs_io().build().unwrap();
-        let output_file = io.new_output(path.to_str().unwrap()).unwrap();
-        let mut writer = ManifestWriterBuilder::new(
-            output_file,
-            Some(3),
-            vec![],
-            metadata.schema.clone(),
-            metadata.partition_spec.clone(),
-        )
-        .build_v2_data();
-        writer.add_entry(entries[0].clone()).unwrap();
-        writer.add_delete_entry(entries[1].clone()).unwrap();
-        writer.add_existing_entry(entries[2].clone()).unwrap();
-        writer.write_manifest_file().await.unwrap();
+        let partition_values = data_file(Struct::from_iter([Some(Literal::int(7)), None]))
+            .partition_values(&partition_spec, &schema)
+            .unwrap();
+        assert_eq!(
+            partition_values,
+            BTreeMap::from([
+                ("id".to_string(), Some(Datum::int(7))),
+                ("ts_day".to_string(), None)
+            ])
+        );
 
-        // read back the manifest file and check the content
-        let actual_manifest =
-            Manifest::parse_avro(fs::read(path).expect("read_file must succeed").as_slice())
-                .unwrap();
+        let partition_values = data_file(Struct::from_iter([None, Some(Literal::date(19000))]))
+            .partition_values(&partition_spec, &schema)
+            .unwrap();
+        assert_eq!(partition_values["ts_day"], Some(Datum::date(19000)));
 
-        // The snapshot id is assigned when the entry is added and delete to the manifest. Existing entries are keep original.
-        entries[0].snapshot_id = Some(3);
-        entries[1].snapshot_id = Some(3);
-        // file sequence number is assigned to None when the entry is added and delete to the manifest.
-        entries[0].file_sequence_number = None;
-        assert_eq!(actual_manifest, Manifest::new(metadata, entries));
+        assert!(data_file(Struct::from_iter([Some(Literal::int(7))]))
+            .partition_values(&partition_spec, &schema)
+            .is_err());
     }
 
-    #[tokio::test]
-    async fn test_data_file_serialize_deserialize() {
-        let schema = Arc::new(
-            Schema::builder()
-                .with_fields(vec![
-                    Arc::new(NestedField::optional(
-                        1,
-                        "v1",
-                        Type::Primitive(PrimitiveType::Int),
-                    )),
-                    Arc::new(NestedField::optional(
-                        2,
-                        "v2",
-                        Type::Primitive(PrimitiveType::String),
-                    )),
-                    Arc::new(NestedField::optional(
+    #[test]
+    fn test_data_file_equality_field_names() {
+        let schema = Schema::builder()
+            .with_fields(vec![
+                Arc::new(NestedField::required(
+                    1,
+                    "id",
+                    Type::Primitive(PrimitiveType::Int),
+                )),
+                Arc::new(NestedField::optional(
+                    2,
+                    "location",
+                    Type::Struct(StructType::new(vec![Arc::new(NestedField::optional(
                         3,
-                        "v3",
+                        "city",
                         Type::Primitive(PrimitiveType::String),
-                    )),
-                ])
+                    ))])),
+                )),
+            ])
+            .build()
+            .unwrap();
+        let delete_file = |equality_ids: Vec<i32>| {
+            test_data_file_builder("s3://testbucket/data/00000-deletes.parquet")
+                .content(DataContentType::EqualityDeletes)
+                .equality_ids(equality_ids)
                 .build()
+                .unwrap()
+        };
+
+        assert_eq!(
+            delete_file(vec![3, 1])
+                .equality_field_names(&schema)
                 .unwrap(),
+            vec!["location.city".to_string(), "id".to_string()]
         );
-        let data_files = vec![DataFile {
-            content: DataContentType::Data,
-            file_path: "s3://testbucket/iceberg_data/iceberg_ctl/iceberg_db/iceberg_tbl/data/00000-7-45268d71-54eb-476c-b42c-942d880c04a1-00001.parquet".to_string(),
-            file_format: DataFileFormat::Parquet,
-            partition: Struct::empty(),
-            record_count: 1,
-            file_size_in_bytes: 875,
-            column_sizes: HashMap::from([(1,47),(2,48),(3,52)]),
-            value_counts: HashMap::from([(1,1),(2,1),(3,1)]),
-            null_value_counts: HashMap::from([(1,0),(2,0),(3,0)]),
-            nan_value_counts: HashMap::new(),
-            lower_bounds: HashMap::from([(1,Datum::int(1)),(2,Datum::string("a")),(3,Datum::string("AC/DC"))]),
-            upper_bounds: HashMap::from([(1,Datum::int(1)),(2,Datum::string("a")),(3,Datum::string("AC/DC"))]),
-            key_metadata: None,
-            split_offsets: vec![4],
-            equality_ids: vec![],
-            sort_order_id: Some(0),
-            partition_spec_id: 0
-        }];
+        assert!(delete_file(vec![1, 4])
+            .equality_field_names(&schema)
+            .is_err());
+    }
 
-        let mut buffer = Vec::new();
-        let _ = write_data_files_to_avro(
-            &mut buffer,
-            data_files.clone().into_iter(),
-            &StructType::new(vec![]),
-            FormatVersion::V2,
-        )
-        .unwrap();
+    #[test]
+    fn test_data_file_bounds_i64() {
+        let data_file = test_data_file_builder("s3://testbucket/data/00000.parquet")
+            .record_count(3)
+            .lower_bounds(HashMap::from([
+                (1, Datum::int(-1)),
+                (2, Datum::long(1_i64 << 40)),
+                (3, Datum::date(19000)),
+                (4, Datum::timestamp_micros(1_000_000)),
+                (5, Datum::string("a")),
+                (6, Datum::double(1.5)),
+            ]))
+            .upper_bounds(HashMap::from([(1, Datum::int(9))]))
+            .build()
+            .unwrap();
 
-        let actual_data_file = read_data_files_from_avro(
-            &mut Cursor::new(buffer),
-            &schema,
-            0,
-            &StructType::new(vec![]),
-            FormatVersion::V2,
-        )
-        .unwrap();
+        assert_eq!(data_file.lower_bound_i64(1), Some(-1));
+        assert_eq!(data_file.lower_bound_i64(2), Some(1 << 40));
+        assert_eq!(data_file.lower_bound_i64(3), Some(19000));
+        assert_eq!(data_file.lower_bound_i64(4), Some(1_000_000));
+        assert_eq!(data_file.lower_bound_i64(5), None);
+        assert_eq!(data_file.lower_bound_i64(6), None);
+        assert_eq!(data_file.lower_bound_i64(7), None);
+        assert_eq!(data_file.upper_bound_i64(1), Some(9));
+        assert_eq!(data_file.upper_bound_i64(2), None);
+    }
 
-        assert_eq!(data_files, actual_data_file);
+    #[test]
+    fn test_manifest_entry_without_stats() {
+        let entry = ManifestEntry {
+            status: ManifestStatus::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            file_sequence_number: Some(1),
+            data_file: DataFile {
+                content: DataContentType::Data,
+                file_path: "s3://testbucket/data/00000.parquet".to_string(),
+                file_format: DataFileFormat::Parquet,
+                partition: Struct::from_iter([Some(Literal::int(1))]),
+                record_count: 10,
+                file_size_in_bytes: 875,
+                block_size_in_bytes: None,
+                column_sizes: HashMap::from([(1, 47)]),
+                value_counts: HashMap::from([(1, 10)]),
+                null_value_counts: HashMap::from([(1, 0)]),
+                nan_value_counts: HashMap::from([(1, 0)]),
+                lower_bounds: HashMap::from([(1, Datum::int(1))]),
+                upper_bounds: HashMap::from([(1, Datum::int(9))]),
+                key_metadata: None,
+                split_offsets: vec![4],
+                equality_ids: vec![],
+                sort_order_id: Some(0),
+                referenced_data_file: None,
+                partition_spec_id: 0,
+            },
+        };
+
+        let light = entry.without_stats();
+        let data_file = light.data_file();
+        assert!(data_file.column_sizes().is_empty());
+        assert!(data_file.value_counts().is_empty());
+        assert!(data_file.null_value_counts().is_empty());
+        assert!(data_file.nan_value_counts().is_empty());
+        assert!(data_file.lower_bounds().is_empty());
+        assert!(data_file.upper_bounds().is_empty());
+
+        assert_eq!(light.status(), entry.status());
+        assert_eq!(light.sequence_number(), entry.sequence_number());
+        assert_eq!(data_file.file_path(), entry.file_path());
+        assert_eq!(data_file.content_type(), entry.content_type());
+        assert_eq!(data_file.record_count(), entry.record_count());
+        assert_eq!(data_file.file_size_in_bytes(), entry.file_size_in_bytes());
+        assert_eq!(data_file.partition(), entry.data_file().partition());
+        assert_eq!(data_file.split_offsets(), entry.data_file().split_offsets());
     }
 }
@@ -17,6 +17,7 @@
 
 //! ManifestList for Iceberg.
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::str::FromStr;
 
@@ -608,8 +609,9 @@ pub enum ManifestContentType {
 impl FromStr for ManifestContentType {
     type Err = Error;
 
+    /// Parse the content type case-insensitively, since some producers write it uppercase.
     fn from_str(s: &str) -> Result<Self> {
-        match s {
+        match s.to_ascii_lowercase().as_str() {
             "data" => Ok(ManifestContentType::Data),
             "deletes" => Ok(ManifestContentType::Deletes),
             _ => Err(Error::new(
@@ -654,14 +656,12 @@ impl ManifestFile {
     pub async fn load_manifest(&self, file_io: &FileIO) -> Result<Manifest> {
         let avro = file_io.new_input(&self.manifest_path)?.read().await?;
 
-        let (metadata, mut entries) = Manifest::try_from_avro_bytes(&avro)?;
+        let (metadata, entries) = Manifest::try_from_avro_bytes(&avro)?;
 
         // Let entries inherit values from the manifest list entry.
-        for entry in &mut entries {
-            entry.inherit_data(self);
-        }
-
-        Ok(Manifest::new(metadata, entries))
+        let mut manifest = Manifest::new(metadata, entries);
+        manifest.inherit_from(self);
+        Ok(manifest)
     }
 }
 
@@ -689,6 +689,42 @@ pub struct FieldSummary {
     pub upper_bound: Option<Datum>,
 }
 
+impl FieldSummary {
+    /// Returns false if no partition summarized by this field summary can have the value
+    /// `datum`, which allows pruning a whole manifest without reading it.
+    ///
+    /// NaN values are matched against `contains_nan`, treating an unknown `contains_nan`
+    /// as possibly containing NaN. Other values are matched against the bounds, which are
+    /// absent when all partition values are null or NaN. Null values are covered by
+    /// `contains_null` instead. Values that can't be compared with the bounds, e.g. of a
+    /// different type, may be contained.
+    pub fn may_contain(&self, datum: &Datum) -> bool {
+        if datum.is_nan() {
+            return self.contains_nan != Some(false);
+        }
+        let (Some(lower_bound), Some(upper_bound)) = (&self.lower_bound, &self.upper_bound) else {
+            return false;
+        };
+        lower_bound.partial_cmp(datum) != Some(Ordering::Greater)
+            && upper_bound.partial_cmp(datum) != Some(Ordering::Less)
+    }
+
+    /// Returns false if no partition summarized by this field summary can have a value in
+    /// the inclusive range `[lo, hi]`, where `None` leaves that side of the range open.
+    ///
+    /// Like comparisons in a filter, a range never matches null values, so a summary whose
+    /// partition values are all null doesn't overlap any range, not even a fully open one.
+    /// `IS NULL` predicates are answered by `contains_null` instead. Range ends that can't
+    /// be compared with the bounds may overlap.
+    pub fn overlaps_range(&self, lo: Option<&Datum>, hi: Option<&Datum>) -> bool {
+        let (Some(lower_bound), Some(upper_bound)) = (&self.lower_bound, &self.upper_bound) else {
+            return false;
+        };
+        lo.and_then(|lo| upper_bound.partial_cmp(lo)) != Some(Ordering::Less)
+            && hi.and_then(|hi| lower_bound.partial_cmp(hi)) != Some(Ordering::Greater)
+    }
+}
+
 /// This is a helper module that defines types to help with serialization/deserialization.
 /// For deserialization the input first gets read into either the [ManifestFileV1] or [ManifestFileV2] struct
 /// and then converted into the [ManifestFile] struct. Serialization works the other way around.
@@ -1524,6 +1560,93 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn test_manifest_content_type_from_str() {
+        for (s, expected) in [
+            ("data", ManifestContentType::Data),
+            ("DATA", ManifestContentType::Data),
+            ("deletes", ManifestContentType::Deletes),
+            ("Deletes", ManifestContentType::Deletes),
+        ] {
+            assert_eq!(s.parse::<ManifestContentType>().unwrap(), expected);
+        }
+        assert!("delete".parse::<ManifestContentType>().is_err());
+    }
+
+    #[test]
+    fn test_field_summary_may_contain() {
+        let summary = FieldSummary {
+            contains_null: true,
+            contains_nan: Some(false),
+            lower_bound: Some(Datum::int(10)),
+            upper_bound: Some(Datum::int(20)),
+        };
+        assert!(summary.may_contain(&Datum::int(10)));
+        assert!(summary.may_contain(&Datum::int(15)));
+        assert!(summary.may_contain(&Datum::int(20)));
+        assert!(!summary.may_contain(&Datum::int(9)));
+        assert!(!summary.may_contain(&Datum::int(21)));
+        // Not comparable with the bounds.
+        assert!(summary.may_contain(&Datum::string("a")));
+
+        // All partition values are null.
+        let all_null = FieldSummary {
+            contains_null: true,
+            contains_nan: Some(false),
+            lower_bound: None,
+            upper_bound: None,
+        };
+        assert!(!all_null.may_contain(&Datum::int(15)));
+
+        let doubles = |contains_nan| FieldSummary {
+            contains_null: false,
+            contains_nan,
+            lower_bound: Some(Datum::double(1.0)),
+            upper_bound: Some(Datum::double(2.0)),
+        };
+        assert!(doubles(Some(false)).may_contain(&Datum::double(1.5)));
+        assert!(!doubles(Some(false)).may_contain(&Datum::double(f64::NAN)));
+        assert!(doubles(Some(true)).may_contain(&Datum::double(f64::NAN)));
+        assert!(doubles(None).may_contain(&Datum::double(f64::NAN)));
+    }
+
+    #[test]
+    fn test_field_summary_overlaps_range() {
+        let summary = FieldSummary {
+            contains_null: true,
+            contains_nan: Some(false),
+            lower_bound: Some(Datum::int(10)),
+            upper_bound: Some(Datum::int(20)),
+        };
+        let overlaps = |lo: Option<i32>, hi: Option<i32>| {
+            summary.overlaps_range(lo.map(Datum::int).as_ref(), hi.map(Datum::int).as_ref())
+        };
+        assert!(overlaps(Some(5), Some(10)));
+        assert!(overlaps(Some(12), Some(15)));
+        assert!(overlaps(Some(20), Some(25)));
+        assert!(overlaps(Some(0), Some(30)));
+        assert!(!overlaps(Some(0), Some(9)));
+        assert!(!overlaps(Some(21), Some(30)));
+        // Open-ended ranges.
+        assert!(overlaps(None, Some(10)));
+        assert!(!overlaps(None, Some(9)));
+        assert!(overlaps(Some(20), None));
+        assert!(!overlaps(Some(21), None));
+        assert!(overlaps(None, None));
+        // Not comparable with the bounds.
+        assert!(summary.overlaps_range(Some(&Datum::string("a")), None));
+
+        // All partition values are null, only IS NULL can match.
+        let all_null = FieldSummary {
+            contains_null: true,
+            contains_nan: Some(false),
+            lower_bound: None,
+            upper_bound: None,
+        };
+        assert!(!all_null.overlaps_range(None, None));
+        assert!(all_null.contains_null);
+    }
+
     async fn read_avro_schema_fields_as_str(bs: Vec<u8>) -> String {
         let reader = Reader::new(&bs[..]).unwrap();
         let schema = reader.writer_schema();
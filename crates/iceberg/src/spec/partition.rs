@@ -24,7 +24,7 @@ use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
 use super::transform::Transform;
-use super::{NestedField, Schema, SchemaRef, StructType};
+use super::{FieldSummary, NestedField, Schema, SchemaRef, StructType};
 use crate::{Error, ErrorKind, Result};
 
 pub(crate) const UNPARTITIONED_LAST_ASSIGNED_ID: i32 = 999;
@@ -127,6 +127,26 @@ impl PartitionSpec {
         self.fields.iter().map(|f| f.field_id).max()
     }
 
+    /// Pair the partition field summaries of a manifest file, which are positional, with
+    /// the fields of this spec. Fails if there isn't a summary for every field.
+    pub fn summaries_with_fields<'a>(
+        &'a self,
+        summaries: &'a [FieldSummary],
+    ) -> Result<impl Iterator<Item = (&'a PartitionField, &'a FieldSummary)>> {
+        if summaries.len() != self.fields.len() {
+            return Err(Error::new(
+                ErrorKind::DataInvalid,
+                format!(
+                    "Got {} field summaries for partition spec {} with {} fields",
+                    summaries.len(),
+                    self.spec_id,
+                    self.fields.len()
+                ),
+            ));
+        }
+        Ok(self.fields.iter().zip(summaries))
+    }
+
     /// Check if this partition spec is compatible with another partition spec.
     ///
     /// Returns true if the partition spec is equal to the other spec with partition field ids ignored and
@@ -661,7 +681,7 @@ impl CorePartitionSpecValidator for UnboundPartitionSpecBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::spec::{PrimitiveType, Type};
+    use crate::spec::{Datum, PrimitiveType, Type};
 
     #[test]
     fn test_partition_spec() {
@@ -1615,6 +1635,57 @@ mod tests {
         assert_eq!(Some(1001), spec.highest_field_id());
     }
 
+    #[test]
+    fn test_summaries_with_fields() {
+        let schema = Schema::builder()
+            .with_fields(vec![
+                NestedField::required(1, "id", Type::Primitive(crate::spec::PrimitiveType::Int))
+                    .into(),
+                NestedField::required(
+                    2,
+                    "name",
+                    Type::Primitive(crate::spec::PrimitiveType::String),
+                )
+                .into(),
+            ])
+            .build()
+            .unwrap();
+        let spec = PartitionSpec::builder(schema)
+            .with_spec_id(1)
+            .add_partition_field("id", "id_bucket", Transform::Bucket(4))
+            .unwrap()
+            .add_partition_field("name", "name", Transform::Identity)
+            .unwrap()
+            .build()
+            .unwrap();
+        let summaries = vec![
+            FieldSummary {
+                contains_null: false,
+                contains_nan: None,
+                lower_bound: Some(Datum::int(0)),
+                upper_bound: Some(Datum::int(3)),
+            },
+            FieldSummary {
+                contains_null: true,
+                contains_nan: None,
+                lower_bound: Some(Datum::string("a")),
+                upper_bound: Some(Datum::string("z")),
+            },
+        ];
+
+        let pairs: Vec<_> = spec
+            .summaries_with_fields(&summaries)
+            .unwrap()
+            .map(|(field, summary)| (field.name.as_str(), summary))
+            .collect();
+        assert_eq!(pairs, vec![
+            ("id_bucket", &summaries[0]),
+            ("name", &summaries[1])
+        ]);
+
+        assert!(spec.summaries_with_fields(&summaries[..1]).is_err());
+    }
+
     #[test]
     fn test_has_sequential_ids() {
         let schema = Schema::builder()
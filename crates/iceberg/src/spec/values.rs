@@ -509,6 +509,50 @@ impl Datum {
         Ok(buf)
     }
 
+    /// Returns the length of the binary representation produced by [`Datum::to_bytes`],
+    /// without serializing the value.
+    pub fn serialized_len(&self) -> Result<usize> {
+        let len = match &self.literal {
+            PrimitiveLiteral::Boolean(_) => 1,
+            PrimitiveLiteral::Int(_) | PrimitiveLiteral::Float(_) => 4,
+            PrimitiveLiteral::Long(_) | PrimitiveLiteral::Double(_) => 8,
+            PrimitiveLiteral::UInt128(_) => 16,
+            PrimitiveLiteral::String(val) => val.len(),
+            PrimitiveLiteral::Binary(val) => val.len(),
+            PrimitiveLiteral::Int128(val) => {
+                let PrimitiveType::Decimal { precision, .. } = self.r#type else {
+                    return Err(Error::new(
+                        ErrorKind::DataInvalid,
+                        format!(
+                            "PrimitiveLiteral Int128 must be PrimitiveType Decimal but got {}",
+                            &self.r#type
+                        ),
+                    ));
+                };
+                let Ok(required_bytes) = Type::decimal_required_bytes(precision) else {
+                    return Err(Error::new(
+                        ErrorKind::DataInvalid,
+                        format!(
+                            "PrimitiveType Decimal must has valid precision but got {}",
+                            precision
+                        ),
+                    ));
+                };
+                // Length of the minimal two's-complement representation, including the sign bit.
+                let magnitude = if *val < 0 { !*val } else { *val };
+                let len = (128 - magnitude.leading_zeros()) as usize / 8 + 1;
+                len.min(required_bytes as usize)
+            }
+            PrimitiveLiteral::AboveMax | PrimitiveLiteral::BelowMin => {
+                return Err(Error::new(
+                    ErrorKind::DataInvalid,
+                    "Cannot convert AboveMax or BelowMin to bytes".to_string(),
+                ));
+            }
+        };
+        Ok(len)
+    }
+
     /// Creates a boolean value.
     ///
     /// Example:
@@ -3205,6 +3249,17 @@ mod tests {
         check_avro_bytes_serde(bytes, Datum::long(32), &PrimitiveType::Long);
     }
 
+    #[test]
+    fn avro_bytes_timestamptz_ns() {
+        let bytes = vec![32u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8, 0u8];
+
+        check_avro_bytes_serde(
+            bytes,
+            Datum::timestamptz_nanos(32),
+            &PrimitiveType::TimestamptzNs,
+        );
+    }
+
     #[test]
     fn avro_bytes_float() {
         let bytes = vec![0u8, 0u8, 128u8, 63u8];
@@ -3840,6 +3895,38 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_datum_serialized_len() {
+        let datums = vec![
+            Datum::bool(true),
+            Datum::int(-5),
+            Datum::long(1_i64 << 40),
+            Datum::float(1.5),
+            Datum::double(-2.5),
+            Datum::date(19000),
+            Datum::timestamp_micros(1),
+            Datum::string(""),
+            Datum::string("iceberg"),
+            Datum::uuid(Uuid::parse_str("f79c3e09-677c-4e19-aa58-b4b8bd3b1a45").unwrap()),
+            Datum::fixed(vec![1u8, 2, 3]),
+            Datum::binary(vec![1u8; 20]),
+            Datum::decimal(Decimal::new(0, 2)).unwrap(),
+            Datum::decimal(Decimal::new(127, 2)).unwrap(),
+            Datum::decimal(Decimal::new(128, 2)).unwrap(),
+            Datum::decimal(Decimal::new(-128, 2)).unwrap(),
+            Datum::decimal(Decimal::new(-129, 2)).unwrap(),
+            Datum::decimal(Decimal::new(i64::MAX, 0)).unwrap(),
+            Datum::decimal(Decimal::new(i64::MIN, 0)).unwrap(),
+        ];
+        for datum in datums {
+            assert_eq!(
+                datum.serialized_len().unwrap(),
+                datum.to_bytes().unwrap().len(),
+                "serialized length of {datum}"
+            );
+        }
+    }
+
     #[test]
     fn test_datum_string_convert_to_timestamptz() {
         let datum = Datum::string("1925-05-20T19:25:00.000 UTC");